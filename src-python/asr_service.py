@@ -9,8 +9,25 @@ import sys
 import json
 import os
 import base64
+import threading
 import requests
 
+# Protocol version spoken with the app; bump when the request/response format changes
+PROTOCOL_VERSION = 1
+CAPABILITIES = ["language_hint", "translate", "audio_base64", "cancel"]
+
+# Target language names expected by the DashScope translation model
+LANGUAGE_NAMES = {
+    "en": "English",
+    "zh": "Chinese",
+    "ja": "Japanese",
+    "ko": "Korean",
+    "fr": "French",
+    "de": "German",
+    "es": "Spanish",
+    "ru": "Russian",
+}
+
 def get_api_key():
     """Get API key from environment variable or config file."""
     # Try environment variable first
@@ -32,8 +49,14 @@ def get_api_key():
 
     return None
 
-def transcribe_audio(audio_path: str, api_key: str) -> dict:
-    """Transcribe audio using Alibaba Cloud Qwen3-ASR API."""
+def parse_request(line: str) -> dict:
+    """Parse a request line: either a bare audio path or a JSON object."""
+    if line.startswith("{"):
+        return json.loads(line)
+    return {"audio_path": line}
+
+def transcribe_audio(audio_path: str, api_key: str, language: str = None) -> dict:
+    """Transcribe an audio file using Alibaba Cloud Qwen3-ASR API."""
     # Read and encode audio file as base64
     with open(audio_path, "rb") as f:
         audio_data = f.read()
@@ -50,9 +73,19 @@ def transcribe_audio(audio_path: str, api_key: str) -> dict:
     else:
         mime_type = "audio/wav"  # Default to wav
 
+    return transcribe_base64(audio_base64, mime_type, api_key, language)
+
+def transcribe_base64(audio_base64: str, mime_type: str, api_key: str, language: str = None) -> dict:
+    """Transcribe base64-encoded audio; used directly for in-memory requests so nothing touches disk."""
     # Create data URI
     audio_uri = f"data:{mime_type};base64,{audio_base64}"
 
+    asr_options = {
+        "enable_itn": True  # Enable Inverse Text Normalization for better formatting
+    }
+    if language:
+        asr_options["language"] = language
+
     # Call DashScope API
     url = "https://dashscope.aliyuncs.com/api/v1/services/aigc/multimodal-generation/generation"
     headers = {
@@ -68,9 +101,7 @@ def transcribe_audio(audio_path: str, api_key: str) -> dict:
             ]
         },
         "parameters": {
-            "asr_options": {
-                "enable_itn": True  # Enable Inverse Text Normalization for better formatting
-            }
+            "asr_options": asr_options
         }
     }
 
@@ -84,9 +115,16 @@ def transcribe_audio(audio_path: str, api_key: str) -> dict:
     if "output" in result and "choices" in result["output"]:
         choices = result["output"]["choices"]
         if choices and "message" in choices[0]:
-            content = choices[0]["message"].get("content", [])
+            message = choices[0]["message"]
+            content = message.get("content", [])
+            # Detected language is reported in the audio_info annotation
+            detected = None
+            for annotation in message.get("annotations", []):
+                if annotation.get("language"):
+                    detected = annotation["language"]
+                    break
             if content and "text" in content[0]:
-                return {"text": content[0]["text"], "language": None}
+                return {"text": content[0]["text"], "language": detected}
 
     # Fallback: check for error
     if "code" in result:
@@ -94,8 +132,105 @@ def transcribe_audio(audio_path: str, api_key: str) -> dict:
 
     return {"text": "", "language": None}
 
+def translate_text(text: str, target: str, api_key: str, endpoint: str = None) -> str:
+    """Translate text via a custom endpoint or the DashScope translation model."""
+    if endpoint:
+        # Custom endpoint: POST {"text", "target"} -> {"text"}
+        response = requests.post(endpoint, json={"text": text, "target": target}, timeout=30)
+        response.raise_for_status()
+        return response.json().get("text", "")
+
+    url = "https://dashscope.aliyuncs.com/compatible-mode/v1/chat/completions"
+    headers = {
+        "Authorization": f"Bearer {api_key}",
+        "Content-Type": "application/json"
+    }
+    payload = {
+        "model": "qwen-mt-turbo",
+        "messages": [{"role": "user", "content": text}],
+        "translation_options": {
+            "source_lang": "auto",
+            "target_lang": LANGUAGE_NAMES.get(target.lower(), target)
+        }
+    }
+    response = requests.post(url, headers=headers, json=payload, timeout=30)
+    response.raise_for_status()
+    choices = response.json().get("choices", [])
+    if choices:
+        return choices[0].get("message", {}).get("content", "")
+    return ""
+
+# The request currently being transcribed; a cancel answers it immediately and the
+# late result is dropped, so every request still gets exactly one response line
+_inflight_lock = threading.Lock()
+_inflight = None
+
+def respond(token, response: dict):
+    """Write the response for a request unless it has already been answered (cancelled)."""
+    global _inflight
+    with _inflight_lock:
+        if _inflight is not token:
+            return
+        _inflight = None
+        print(json.dumps(response), flush=True)
+
+def cancel_inflight():
+    """Answer the in-flight request with a cancellation error; ignored when idle."""
+    global _inflight
+    with _inflight_lock:
+        if _inflight is None:
+            return
+        _inflight = None
+        print(json.dumps({"error": "Transcription cancelled"}), flush=True)
+
+def handle_transcription(request: dict, api_key: str, token):
+    """Transcribe (and optionally translate) one request on a worker thread."""
+    audio_data = request.get("audio_base64")
+    audio_path = request.get("audio_path", "")
+
+    try:
+        # Transcribe the audio
+        if audio_data:
+            result = transcribe_base64(audio_data, "audio/wav", api_key, request.get("language"))
+        else:
+            result = transcribe_audio(audio_path, api_key, request.get("language"))
+
+        # Output result; optional timing info is passed through when available
+        response = {
+            "text": result.get("text", "").strip(),
+            "language": result.get("language")
+        }
+        for key in ("segments", "words"):
+            if result.get(key):
+                response[key] = result[key]
+
+        # Optional translation; on failure the original text is still returned
+        target = request.get("translate_to")
+        if target and response["text"]:
+            try:
+                response["translation"] = translate_text(
+                    response["text"], target, api_key, request.get("translate_endpoint")
+                ).strip()
+            except Exception as e:
+                print(f"Warning: Translation failed: {e}", file=sys.stderr, flush=True)
+        respond(token, response)
+
+    except requests.exceptions.Timeout:
+        respond(token, {
+            "error": "API request timed out"
+        })
+    except requests.exceptions.RequestException as e:
+        respond(token, {
+            "error": f"Network error: {str(e)}"
+        })
+    except Exception as e:
+        respond(token, {
+            "error": str(e)
+        })
+
 def main():
     """Main loop: read audio paths from stdin, output transcriptions to stdout."""
+    global _inflight
     print("ASR Service starting...", file=sys.stderr, flush=True)
 
     # Check API key on startup
@@ -109,44 +244,54 @@ def main():
 
     print("API key configured", file=sys.stderr, flush=True)
     print("ASR Service ready (using Qwen3-ASR)", file=sys.stderr, flush=True)
+    # Tell the app it can start sending requests
+    print(json.dumps({"status": "ready"}), flush=True)
 
     for line in sys.stdin:
-        audio_path = line.strip()
+        line = line.strip()
 
-        if not audio_path:
+        if not line:
             continue
 
-        if audio_path == "quit":
+        if line == "quit":
             break
 
-        if not os.path.exists(audio_path):
+        try:
+            request = parse_request(line)
+        except json.JSONDecodeError as e:
             print(json.dumps({
-                "error": f"Audio file not found: {audio_path}"
+                "error": f"Invalid request: {e}"
             }), flush=True)
             continue
 
-        try:
-            # Transcribe the audio
-            result = transcribe_audio(audio_path, api_key)
-
-            # Output result
+        # Handshake: report protocol version and supported features
+        if request.get("command") == "hello":
             print(json.dumps({
-                "text": result.get("text", "").strip(),
-                "language": result.get("language")
+                "version": PROTOCOL_VERSION,
+                "capabilities": CAPABILITIES
             }), flush=True)
+            continue
 
-        except requests.exceptions.Timeout:
-            print(json.dumps({
-                "error": "API request timed out"
-            }), flush=True)
-        except requests.exceptions.RequestException as e:
-            print(json.dumps({
-                "error": f"Network error: {str(e)}"
-            }), flush=True)
-        except Exception as e:
+        # Cancel the running transcription; produces no response of its own
+        if request.get("command") == "cancel":
+            cancel_inflight()
+            continue
+
+        # In-memory requests carry the WAV bytes instead of a file path
+        audio_data = request.get("audio_base64")
+        audio_path = request.get("audio_path", "")
+
+        if not audio_data and not os.path.exists(audio_path):
             print(json.dumps({
-                "error": str(e)
+                "error": f"Audio file not found: {audio_path}"
             }), flush=True)
+            continue
+
+        # Transcribe on a worker thread so a cancel can be read while the API call runs
+        token = object()
+        with _inflight_lock:
+            _inflight = token
+        threading.Thread(target=handle_transcription, args=(request, api_key, token), daemon=True).start()
 
     print("ASR Service stopping...", file=sys.stderr, flush=True)
 
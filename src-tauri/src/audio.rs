@@ -1,15 +1,125 @@
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use hound::{WavSpec, WavWriter};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::sync::{Arc, Mutex};
 use std::path::PathBuf;
 use std::thread::{self, JoinHandle};
+use std::time::Duration;
 use tempfile::NamedTempFile;
 use tauri::{AppHandle, Emitter, Manager};
 
+use crate::transcriber::{AudioHandoff, RecordedAudio};
+
 pub enum RecorderCommand {
     Start(Option<AppHandle>), // 可选的 AppHandle 用于发送实时音频数据
-    Stop(Sender<Result<PathBuf, String>>),
+    Stop(AudioHandoff, Sender<Result<RecordedAudio, String>>),
+}
+
+// 音量事件的发送间隔
+pub const AMPLITUDE_INTERVAL_MS: u128 = 50;
+
+// 音量事件节流：使用电池时降低发送频率或完全关闭，减少唤醒 webview
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AmplitudeThrottle {
+    pub battery_interval_ms: u64,
+    pub disable_on_battery: bool,
+}
+
+impl Default for AmplitudeThrottle {
+    fn default() -> Self {
+        AmplitudeThrottle { battery_interval_ms: AMPLITUDE_INTERVAL_MS as u64, disable_on_battery: false }
+    }
+}
+
+// 当前电源下的音量事件间隔，None 表示不发送
+fn amplitude_interval_ms() -> Option<u128> {
+    if !crate::power::on_battery() {
+        return Some(AMPLITUDE_INTERVAL_MS);
+    }
+    let throttle: AmplitudeThrottle = crate::get_setting("amplitude_throttle");
+    if throttle.disable_on_battery {
+        log::info!("On battery power, amplitude events disabled");
+        return None;
+    }
+    Some((throttle.battery_interval_ms as u128).max(AMPLITUDE_INTERVAL_MS))
+}
+
+// recording-elapsed 事件的默认发送间隔
+pub const DEFAULT_ELAPSED_INTERVAL_MS: u64 = 250;
+
+// 录音缓冲区默认预分配的时长（秒），避免长录音时频繁扩容
+pub const DEFAULT_RESERVE_DURATION_SECS: u32 = 30;
+pub const MAX_RESERVE_DURATION_SECS: u32 = 600;
+
+// 麦克风测试结果
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct MicTestResult {
+    pub device_name: String,
+    pub peak: f32,
+    pub rms: f32,
+    pub clipped: bool,
+    pub sample_count: usize,
+}
+
+// 接近满幅的样本视为削波
+const CLIP_LEVEL: f32 = 0.99;
+// 削波样本占比超过该值时发出 recording-clipped
+pub const DEFAULT_CLIP_THRESHOLD: f32 = 0.01;
+
+// 开头丢弃时长的上限
+pub const MAX_WARMUP_DISCARD_MS: u64 = 1000;
+
+// 部分设备刚启动采集时的几十毫秒是爆音或杂音，丢弃开头 discard_ms 的样本；
+// 多声道时缓冲区按帧交错存放，按整帧丢弃
+fn discard_warmup(samples: &mut Vec<f32>, sample_rate: u32, channels: u16, discard_ms: u64) {
+    if discard_ms == 0 {
+        return;
+    }
+    let frames = sample_rate as u64 * discard_ms / 1000;
+    let count = (frames * channels.max(1) as u64).min(samples.len() as u64) as usize;
+    samples.drain(..count);
+    log::info!("Discarded {} warm-up samples ({} ms)", count, discard_ms);
+}
+
+// 最近一次录音的诊断信息，便于排查增益过高等问题
+#[derive(Clone, Debug, Default, serde::Serialize)]
+pub struct RecordingDiagnostics {
+    pub sample_count: usize,
+    pub sample_rate: u32,
+    pub peak: f32,
+    pub clipped_fraction: f32,
+}
+
+static LAST_DIAGNOSTICS: Mutex<Option<RecordingDiagnostics>> = Mutex::new(None);
+
+pub fn last_diagnostics() -> Option<RecordingDiagnostics> {
+    LAST_DIAGNOSTICS.lock().ok().and_then(|d| d.clone())
+}
+
+// 计算录音诊断信息，削波比例超过阈值时记录警告并通知界面
+fn record_diagnostics(samples: &[f32], sample_rate: u32, app_handle: Option<&AppHandle>) {
+    let peak = samples.iter().fold(0.0f32, |max, s| max.max(s.abs()));
+    let clipped = samples.iter().filter(|s| s.abs() >= CLIP_LEVEL).count();
+    let fraction = clipped as f32 / samples.len().max(1) as f32;
+
+    let threshold = crate::get_setting::<Option<f32>>("clip_threshold").unwrap_or(DEFAULT_CLIP_THRESHOLD);
+    if fraction > threshold {
+        log::warn!("Recording clipped: {:.2}% of samples at full scale", fraction * 100.0);
+        if let Some(handle) = app_handle {
+            let _ = handle.emit("recording-clipped", serde_json::json!({ "fraction": fraction }));
+        }
+    }
+
+    if let Ok(mut last) = LAST_DIAGNOSTICS.lock() {
+        *last = Some(RecordingDiagnostics {
+            sample_count: samples.len(),
+            sample_rate,
+            peak,
+            clipped_fraction: fraction,
+        });
+    }
 }
 
 pub struct AudioRecorderHandle {
@@ -37,10 +147,11 @@ impl AudioRecorderHandle {
             .map_err(|e| format!("Failed to send start command: {}", e))
     }
 
-    pub fn stop_recording(&self) -> Result<PathBuf, String> {
+    // handoff 为 Wav 时写入临时文件，为 Samples 时直接返回样本
+    pub fn stop_recording(&self, handoff: AudioHandoff) -> Result<RecordedAudio, String> {
         let (result_tx, result_rx) = mpsc::channel();
         self.command_tx
-            .send(RecorderCommand::Stop(result_tx))
+            .send(RecorderCommand::Stop(handoff, result_tx))
             .map_err(|e| format!("Failed to send stop command: {}", e))?;
 
         result_rx
@@ -53,22 +164,30 @@ fn recorder_thread(command_rx: Receiver<RecorderCommand>) {
     let samples: Arc<Mutex<Vec<f32>>> = Arc::new(Mutex::new(Vec::new()));
     let mut _stream_holder: Option<cpal::Stream> = None;
     let mut sample_rate: u32 = 44100;
+    let mut channels: u16 = 1;
+    // 丢弃 Sender 即可让计时线程退出
+    let mut _elapsed_timer: Option<Sender<()>> = None;
+    // 本次录音的 AppHandle，停止时用于发送诊断事件
+    let mut app_handle: Option<AppHandle> = None;
 
     loop {
         match command_rx.recv() {
             Ok(RecorderCommand::Start(handle)) => {
-                // Clear samples
-                if let Ok(mut s) = samples.lock() {
-                    s.clear();
-                }
+                _elapsed_timer = handle.clone().map(start_elapsed_timer);
+                app_handle = handle.clone();
+
+                // 上次停止时缓冲区已被取走；取消或启动失败时可能残留样本，这里确保从空缓冲区开始
+                clear_samples(&samples);
 
                 // Create stream with amplitude monitoring
-                match create_input_stream_with_amplitude(
-                    Arc::clone(&samples),
-                    handle.clone(),
-                ) {
-                    Ok((stream, rate)) => {
+                let stream = capture_device().and_then(|device| {
+                    reserve_samples(&device, &samples);
+                    create_input_stream_with_amplitude(&device, Arc::clone(&samples), handle.clone(), None)
+                });
+                match stream {
+                    Ok((stream, rate, stream_channels)) => {
                         sample_rate = rate;
+                        channels = stream_channels;
                         if let Err(e) = stream.play() {
                             log::error!("Failed to start stream: {}", e);
                         } else {
@@ -81,12 +200,25 @@ fn recorder_thread(command_rx: Receiver<RecorderCommand>) {
                     }
                 }
             }
-            Ok(RecorderCommand::Stop(result_tx)) => {
+            Ok(RecorderCommand::Stop(handoff, result_tx)) => {
                 // Stop stream
                 _stream_holder = None;
+                _elapsed_timer = None;
 
-                // Save to file
-                let result = save_samples_to_wav(&samples, sample_rate);
+                let recorded = take_samples(&samples);
+                let result = recorded.and_then(|mut recorded| {
+                    discard_warmup(&mut recorded, sample_rate, channels, crate::get_setting("warmup_discard_ms"));
+                    if recorded.is_empty() {
+                        return Err("No audio recorded".to_string());
+                    }
+                    log::info!("Recorded {} samples", recorded.len());
+                    record_diagnostics(&recorded, sample_rate, app_handle.as_ref());
+                    let recorded = crate::denoise::apply_configured(recorded, sample_rate);
+                    match handoff {
+                        AudioHandoff::Wav => write_wav(&recorded, sample_rate).map(RecordedAudio::Wav),
+                        AudioHandoff::Samples => Ok(RecordedAudio::Samples { samples: recorded, sample_rate }),
+                    }
+                });
                 let _ = result_tx.send(result);
             }
             Err(_) => {
@@ -96,80 +228,591 @@ fn recorder_thread(command_rx: Receiver<RecorderCommand>) {
     }
 }
 
+// 直接取走样本缓冲区（留下空 Vec），不复制整段录音，锁只在交换时持有
+fn take_samples(samples: &Mutex<Vec<f32>>) -> Result<Vec<f32>, String> {
+    samples
+        .lock()
+        .map(|mut s| std::mem::take(&mut *s))
+        .map_err(|e| e.to_string())
+}
+
+fn clear_samples(samples: &Mutex<Vec<f32>>) {
+    if let Ok(mut s) = samples.lock() {
+        s.clear();
+    }
+}
+
+// 启动计时线程，按间隔发送 recording-elapsed（毫秒）；返回的 Sender 被丢弃时线程退出
+fn start_elapsed_timer(app_handle: AppHandle) -> Sender<()> {
+    let (stop_tx, stop_rx) = mpsc::channel::<()>();
+    let interval = crate::get_setting::<Option<u64>>("elapsed_interval_ms")
+        .unwrap_or(DEFAULT_ELAPSED_INTERVAL_MS)
+        .clamp(50, 5000);
+
+    thread::spawn(move || {
+        let started = std::time::Instant::now();
+        while let Err(mpsc::RecvTimeoutError::Timeout) = stop_rx.recv_timeout(Duration::from_millis(interval)) {
+            let _ = app_handle.emit("recording-elapsed", started.elapsed().as_millis() as u64);
+        }
+    });
+
+    stop_tx
+}
+
+// 按预期时长和设备采样率预分配样本缓冲区，超出时 Vec 仍会自动扩容
+fn reserve_samples(device: &cpal::Device, samples: &Mutex<Vec<f32>>) {
+    let secs = crate::get_setting::<Option<u32>>("reserve_duration_secs")
+        .unwrap_or(DEFAULT_RESERVE_DURATION_SECS)
+        .min(MAX_RESERVE_DURATION_SECS);
+    let Ok(config) = default_config(device) else {
+        return;
+    };
+
+    let capacity = config.sample_rate().0 as usize * config.channels() as usize * secs as usize;
+    if let Ok(mut s) = samples.lock() {
+        s.reserve(capacity);
+    }
+}
+
+// 当前平台可用的音频后端名称（如 ALSA、JACK、CoreAudio）
+pub fn list_hosts() -> Vec<String> {
+    cpal::available_hosts()
+        .into_iter()
+        .map(|id| id.name().to_string())
+        .collect()
+}
+
+pub fn find_host_id(name: &str) -> Option<cpal::HostId> {
+    cpal::available_hosts()
+        .into_iter()
+        .find(|id| id.name().eq_ignore_ascii_case(name))
+}
+
+// 使用配置中选择的音频后端，不可用时退回默认后端
+pub fn selected_host() -> cpal::Host {
+    if let Some(name) = crate::get_setting::<Option<String>>("audio_host") {
+        match find_host_id(&name).map(cpal::host_from_id) {
+            Some(Ok(host)) => return host,
+            Some(Err(e)) => log::warn!("Audio host {} unavailable: {}, using default", name, e),
+            None => log::warn!("Audio host {} not found, using default", name),
+        }
+    }
+    cpal::default_host()
+}
+
+// 输入采样格式偏好
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SampleFormatPreference {
+    #[default]
+    DeviceDefault,  // 使用设备默认配置
+    PreferF32,
+    PreferI16,
+}
+
+// 设备的默认录音配置；Windows loopback 使用的是输出设备，只有输出配置
+fn default_config(device: &cpal::Device) -> Result<cpal::SupportedStreamConfig, String> {
+    device
+        .default_input_config()
+        .or_else(|e| {
+            if cfg!(all(feature = "loopback-capture", target_os = "windows")) {
+                device.default_output_config()
+            } else {
+                Err(e)
+            }
+        })
+        .map_err(|e| format!("Failed to get default input config: {}", e))
+}
+
+// 按偏好选择输入配置：设备支持对应格式时使用该格式（保持默认采样率），否则使用默认配置
+fn select_input_config(device: &cpal::Device) -> Result<cpal::SupportedStreamConfig, String> {
+    let default = default_config(device)?;
+
+    let wanted = match crate::get_setting::<SampleFormatPreference>("sample_format") {
+        SampleFormatPreference::DeviceDefault => return Ok(default),
+        SampleFormatPreference::PreferF32 => cpal::SampleFormat::F32,
+        SampleFormatPreference::PreferI16 => cpal::SampleFormat::I16,
+    };
+    if default.sample_format() == wanted {
+        return Ok(default);
+    }
+
+    let rate = default.sample_rate();
+    let preferred = device.supported_input_configs().ok().and_then(|configs| {
+        configs
+            .filter(|c| c.sample_format() == wanted && c.min_sample_rate() <= rate && rate <= c.max_sample_rate())
+            // 优先与默认配置相同的声道数
+            .min_by_key(|c| c.channels() != default.channels())
+            .map(|c| c.with_sample_rate(rate))
+    });
+
+    match preferred {
+        Some(config) => {
+            log::info!("Using {:?} input config: {:?}", wanted, config);
+            Ok(config)
+        }
+        None => {
+            log::warn!("Device does not support {:?} input, using default {:?}", wanted, default.sample_format());
+            Ok(default)
+        }
+    }
+}
+
+// 默认输入设备支持的缓冲区大小范围（帧），设备未提供时返回 None
+pub fn supported_buffer_range() -> Option<(u32, u32)> {
+    let config = default_input_device().ok()?.default_input_config().ok()?;
+    match *config.buffer_size() {
+        cpal::SupportedBufferSize::Range { min, max } => Some((min, max)),
+        cpal::SupportedBufferSize::Unknown => None,
+    }
+}
+
+// 按 buffer_size 设置（帧）构造输入流配置；未设置、超出设备支持范围或范围未知时使用默认缓冲区
+fn build_stream_config(config: &cpal::SupportedStreamConfig) -> cpal::StreamConfig {
+    let mut stream_config: cpal::StreamConfig = config.clone().into();
+    let Some(frames) = crate::get_setting::<Option<u32>>("buffer_size") else {
+        return stream_config;
+    };
+
+    match *config.buffer_size() {
+        cpal::SupportedBufferSize::Range { min, max } if (min..=max).contains(&frames) => {
+            stream_config.buffer_size = cpal::BufferSize::Fixed(frames);
+            log::info!("Using input buffer size: {} frames", frames);
+        }
+        cpal::SupportedBufferSize::Range { min, max } => {
+            log::warn!("Buffer size {} outside supported range {}-{}, using default", frames, min, max);
+        }
+        cpal::SupportedBufferSize::Unknown => {
+            log::warn!("Device does not report buffer size range, using default");
+        }
+    }
+    stream_config
+}
+
+// 录音来源
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureSource {
+    #[default]
+    Microphone,  // 默认输入设备
+    Loopback,    // 系统播放的声音（需以 loopback-capture 特性构建）
+}
+
+// 按 capture_source 设置选择录音设备
+fn capture_device() -> Result<cpal::Device, String> {
+    match crate::get_setting::<CaptureSource>("capture_source") {
+        CaptureSource::Microphone => default_input_device(),
+        CaptureSource::Loopback => loopback_device(),
+    }
+}
+
+// Windows：在默认输出设备上建立输入流，cpal 会使用 WASAPI loopback 模式
+#[cfg(all(feature = "loopback-capture", target_os = "windows"))]
+pub fn loopback_device() -> Result<cpal::Device, String> {
+    selected_host()
+        .default_output_device()
+        .ok_or_else(|| "No output device available for loopback".to_string())
+}
+
+// macOS / Linux：使用 loopback_device 设置中选择的输入设备（虚拟声卡或 monitor）；
+// 未选择时 Linux 使用第一个 monitor 设备，macOS 没有系统级 loopback API，必须手动选择
+#[cfg(all(feature = "loopback-capture", not(target_os = "windows")))]
+pub fn loopback_device() -> Result<cpal::Device, String> {
+    let mut devices = selected_host()
+        .input_devices()
+        .map_err(|e| format!("Failed to list input devices: {}", e))?;
+    match crate::get_setting::<Option<String>>("loopback_device") {
+        Some(name) => devices
+            .find(|d| d.name().ok().as_deref() == Some(name.as_str()))
+            .ok_or_else(|| format!("Loopback device not found: {}", name)),
+        None if cfg!(target_os = "macos") => {
+            Err("Select the virtual audio device (such as BlackHole) to capture system audio from".to_string())
+        }
+        None => devices
+            .find(|d| d.name().is_ok_and(|name| name.to_lowercase().contains("monitor")))
+            .ok_or_else(|| "No PulseAudio/PipeWire monitor source found, select the loopback device manually".to_string()),
+    }
+}
+
+#[cfg(not(feature = "loopback-capture"))]
+pub fn loopback_device() -> Result<cpal::Device, String> {
+    Err("Loopback capture is not available in this build".to_string())
+}
+
+// 列出可用的输入设备名称（macOS/Linux 上从中选择回环来源）
+pub fn list_input_devices() -> Result<Vec<String>, String> {
+    let devices = selected_host()
+        .input_devices()
+        .map_err(|e| format!("Failed to list input devices: {}", e))?;
+    Ok(devices.filter_map(|device| device.name().ok()).collect())
+}
+
+// 当前默认输入设备的名称
+pub fn input_device_name() -> Option<String> {
+    default_input_device().ok()?.name().ok()
+}
+
+fn default_input_device() -> Result<cpal::Device, String> {
+    let host = selected_host();
+    host.default_input_device()
+        .ok_or_else(|| "No input device available".to_string())
+}
+
+// 独立打开输入流录制一段时间并计算电平，不影响正常录音状态
+pub fn test_microphone(duration_ms: u64) -> Result<MicTestResult, String> {
+    let device = default_input_device()?;
+    let device_name = device.name().unwrap_or_else(|_| "Unknown".to_string());
+
+    let samples: Arc<Mutex<Vec<f32>>> = Arc::new(Mutex::new(Vec::new()));
+    let (stream, _, _) = create_input_stream_with_amplitude(&device, Arc::clone(&samples), None, None)?;
+    stream
+        .play()
+        .map_err(|e| format!("Failed to start stream: {}", e))?;
+    thread::sleep(Duration::from_millis(duration_ms));
+    drop(stream);
+
+    let samples = samples.lock().map_err(|e| e.to_string())?;
+    let peak = samples.iter().fold(0.0f32, |max, s| max.max(s.abs()));
+    let rms = if samples.is_empty() {
+        0.0
+    } else {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    };
+
+    log::info!("Microphone test on {}: peak {:.3}, rms {:.3}, {} samples", device_name, peak, rms, samples.len());
+
+    Ok(MicTestResult {
+        device_name,
+        peak,
+        rms,
+        clipped: peak >= 0.999,
+        sample_count: samples.len(),
+    })
+}
+
+// 录制一段环境声并计算噪声频谱，录制期间应保持安静
+pub fn capture_noise_profile(duration_ms: u64) -> Result<crate::denoise::NoiseProfile, String> {
+    let device = capture_device()?;
+    let samples: Arc<Mutex<Vec<f32>>> = Arc::new(Mutex::new(Vec::new()));
+    let (stream, sample_rate, _) = create_input_stream_with_amplitude(&device, Arc::clone(&samples), None, None)?;
+    stream
+        .play()
+        .map_err(|e| format!("Failed to start stream: {}", e))?;
+    thread::sleep(Duration::from_millis(duration_ms));
+    drop(stream);
+
+    let samples = samples.lock().map_err(|e| e.to_string())?;
+    let profile = crate::denoise::compute_profile(&samples, sample_rate)?;
+    log::info!("Noise profile captured: {} samples at {} Hz", samples.len(), sample_rate);
+    Ok(profile)
+}
+
+// 采集回调的性能统计，回调中只做原子累加，尽量不影响被测量的开销
+#[derive(Default)]
+pub struct CaptureProfile {
+    callbacks: AtomicU64,
+    busy_ns: AtomicU64,
+    max_ns: AtomicU64,
+    lock_wait_ns: AtomicU64,
+    locks: AtomicU64,
+}
+
+impl CaptureProfile {
+    fn record_callback(&self, elapsed: Duration) {
+        let ns = elapsed.as_nanos() as u64;
+        self.callbacks.fetch_add(1, Ordering::Relaxed);
+        self.busy_ns.fetch_add(ns, Ordering::Relaxed);
+        self.max_ns.fetch_max(ns, Ordering::Relaxed);
+    }
+
+    fn record_lock_wait(&self, elapsed: Duration) {
+        self.lock_wait_ns.fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+        self.locks.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+// 加锁；提供 profile 时记录等待时间
+fn lock_profiled<'a, T>(
+    mutex: &'a Mutex<T>,
+    profile: Option<&CaptureProfile>,
+) -> std::sync::LockResult<std::sync::MutexGuard<'a, T>> {
+    let Some(profile) = profile else {
+        return mutex.lock();
+    };
+    let started = std::time::Instant::now();
+    let guard = mutex.lock();
+    profile.record_lock_wait(started.elapsed());
+    guard
+}
+
+// 采集开销报告
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct CaptureReport {
+    pub device_name: String,
+    pub duration_ms: u64,
+    pub callbacks: u64,
+    pub sample_count: usize,
+    pub avg_callback_us: f64,
+    pub max_callback_us: f64,
+    pub lock_count: u64,
+    pub total_lock_wait_us: f64,
+    pub avg_lock_wait_us: f64,
+}
+
+// 独立打开输入流录制一段时间，统计回调次数、回调耗时和加锁等待时间
+pub fn profile_capture(duration_ms: u64) -> Result<CaptureReport, String> {
+    let device = default_input_device()?;
+    let device_name = device.name().unwrap_or_else(|_| "Unknown".to_string());
+
+    let samples: Arc<Mutex<Vec<f32>>> = Arc::new(Mutex::new(Vec::new()));
+    let profile = Arc::new(CaptureProfile::default());
+    let (stream, _, _) = create_input_stream_with_amplitude(&device, Arc::clone(&samples), None, Some(Arc::clone(&profile)))?;
+    stream
+        .play()
+        .map_err(|e| format!("Failed to start stream: {}", e))?;
+    thread::sleep(Duration::from_millis(duration_ms));
+    drop(stream);
+
+    let sample_count = samples.lock().map_err(|e| e.to_string())?.len();
+    let callbacks = profile.callbacks.load(Ordering::Relaxed);
+    let locks = profile.locks.load(Ordering::Relaxed);
+    let busy_us = profile.busy_ns.load(Ordering::Relaxed) as f64 / 1000.0;
+    let lock_wait_us = profile.lock_wait_ns.load(Ordering::Relaxed) as f64 / 1000.0;
+
+    let report = CaptureReport {
+        device_name,
+        duration_ms,
+        callbacks,
+        sample_count,
+        avg_callback_us: busy_us / callbacks.max(1) as f64,
+        max_callback_us: profile.max_ns.load(Ordering::Relaxed) as f64 / 1000.0,
+        lock_count: locks,
+        total_lock_wait_us: lock_wait_us,
+        avg_lock_wait_us: lock_wait_us / locks.max(1) as f64,
+    };
+    log::info!("Capture profile: {:?}", report);
+    Ok(report)
+}
+
+// 音量归一化曲线
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+#[serde(tag = "curve", rename_all = "lowercase")]
+pub enum AmplitudeCurve {
+    Linear { gain: f32 },  // 线性放大（默认 5.0）
+    Log,                   // 按分贝映射 -60dB..0dB
+    Sqrt,                  // 平方根压缩，兼顾小音量与大音量
+}
+
+impl Default for AmplitudeCurve {
+    fn default() -> Self {
+        AmplitudeCurve::Linear { gain: 5.0 }
+    }
+}
+
+// 将平均振幅归一化到 0-1 范围，并增强效果
+pub fn normalize_amplitude(raw: f32, curve: &AmplitudeCurve) -> f32 {
+    let raw = raw.abs();
+    let normalized = match *curve {
+        AmplitudeCurve::Linear { gain } => raw * gain,
+        AmplitudeCurve::Log => {
+            if raw <= 0.0 {
+                0.0
+            } else {
+                (20.0 * raw.log10() + 60.0) / 60.0
+            }
+        }
+        AmplitudeCurve::Sqrt => raw.sqrt() * 2.0,
+    };
+    normalized.clamp(0.0, 1.0)
+}
+
+// 读取 WAV 文件，按与实时录音相同的 50ms 窗口计算归一化音量序列
+pub fn amplitude_series(path: &std::path::Path) -> Result<Vec<f32>, String> {
+    let mut reader = hound::WavReader::open(path)
+        .map_err(|e| format!("Failed to open WAV: {}", e))?;
+    let spec = reader.spec();
+    let curve: AmplitudeCurve = crate::get_setting("amplitude_curve");
+    let window = ((spec.sample_rate as u128 * spec.channels as u128 * AMPLITUDE_INTERVAL_MS) / 1000).max(1) as usize;
+
+    let samples: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Float => reader
+            .samples::<f32>()
+            .collect::<Result<_, _>>()
+            .map_err(|e| format!("Failed to read samples: {}", e))?,
+        hound::SampleFormat::Int => {
+            let max = (1i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|s| s.map(|s| s as f32 / max))
+                .collect::<Result<_, _>>()
+                .map_err(|e| format!("Failed to read samples: {}", e))?
+        }
+    };
+
+    Ok(samples
+        .chunks(window)
+        .map(|chunk| {
+            let sum: f32 = chunk.iter().map(|s| s.abs()).sum();
+            normalize_amplitude(sum / chunk.len() as f32, &curve)
+        })
+        .collect())
+}
+
+// 读取 WAV 文件时长（毫秒）
+pub fn wav_duration_ms(path: &std::path::Path) -> Result<u64, String> {
+    let reader = hound::WavReader::open(path)
+        .map_err(|e| format!("Failed to open WAV: {}", e))?;
+    let spec = reader.spec();
+    Ok(reader.duration() as u64 * 1000 / spec.sample_rate.max(1) as u64)
+}
+
+// 截取 WAV 中 [start_ms, end_ms) 的片段写入临时文件，保持原有的采样率、声道和样本格式；
+// 按帧（每帧包含所有声道）计算位置，避免截断到声道中间
+pub fn slice_wav(path: &std::path::Path, start_ms: u64, end_ms: u64) -> Result<PathBuf, String> {
+    let mut reader = hound::WavReader::open(path)
+        .map_err(|e| format!("Failed to open WAV: {}", e))?;
+    let spec = reader.spec();
+    let frames = reader.duration() as u64;
+    let duration_ms = frames * 1000 / spec.sample_rate.max(1) as u64;
+    if start_ms >= end_ms {
+        return Err(format!("Invalid range: start {} ms must be before end {} ms", start_ms, end_ms));
+    }
+    if end_ms > duration_ms {
+        return Err(format!("Invalid range: end {} ms exceeds recording duration {} ms", end_ms, duration_ms));
+    }
+
+    let start_frame = start_ms * spec.sample_rate as u64 / 1000;
+    let end_frame = (end_ms * spec.sample_rate as u64 / 1000).min(frames);
+    reader
+        .seek(start_frame as u32)
+        .map_err(|e| format!("Failed to seek WAV: {}", e))?;
+    let sample_count = ((end_frame - start_frame) * spec.channels as u64) as usize;
+
+    let temp_file = NamedTempFile::new()
+        .map_err(|e| format!("Failed to create temp file: {}", e))?;
+    let out_path = temp_file.path().with_extension("wav");
+    let mut writer = WavWriter::create(&out_path, spec)
+        .map_err(|e| format!("Failed to create WAV writer: {}", e))?;
+    match spec.sample_format {
+        hound::SampleFormat::Int => {
+            for sample in reader.samples::<i32>().take(sample_count) {
+                let sample = sample.map_err(|e| format!("Failed to read sample: {}", e))?;
+                writer.write_sample(sample).map_err(|e| format!("Failed to write sample: {}", e))?;
+            }
+        }
+        hound::SampleFormat::Float => {
+            for sample in reader.samples::<f32>().take(sample_count) {
+                let sample = sample.map_err(|e| format!("Failed to read sample: {}", e))?;
+                writer.write_sample(sample).map_err(|e| format!("Failed to write sample: {}", e))?;
+            }
+        }
+    }
+    writer
+        .finalize()
+        .map_err(|e| format!("Failed to finalize WAV: {}", e))?;
+
+    log::info!("Sliced {} ms - {} ms of {:?} to {:?}", start_ms, end_ms, path, out_path);
+    Ok(out_path)
+}
+
+// 音量累积器：只使用原子变量，音频回调中不会因加锁阻塞。
+// 振幅和以定点数（百万分之一）累加，发送间隔以流开始后的毫秒数记录
+struct AmplitudeAccumulator {
+    count: AtomicU64,
+    sum_micros: AtomicU64,
+    last_emit_ms: AtomicU64,
+    started: std::time::Instant,
+    interval_ms: u64,
+}
+
+impl AmplitudeAccumulator {
+    fn new(interval_ms: u64) -> Self {
+        AmplitudeAccumulator {
+            count: AtomicU64::new(0),
+            sum_micros: AtomicU64::new(0),
+            last_emit_ms: AtomicU64::new(0),
+            started: std::time::Instant::now(),
+            interval_ms,
+        }
+    }
+
+    // 累加一批样本的振幅绝对值之和；到达发送间隔时返回这段时间的平均振幅
+    fn accumulate(&self, sum_abs: f32, len: usize) -> Option<f32> {
+        self.count.fetch_add(len as u64, Ordering::Relaxed);
+        self.sum_micros.fetch_add((sum_abs as f64 * 1_000_000.0) as u64, Ordering::Relaxed);
+
+        let now = self.started.elapsed().as_millis() as u64;
+        let last = self.last_emit_ms.load(Ordering::Relaxed);
+        if now.saturating_sub(last) < self.interval_ms
+            || self.last_emit_ms.compare_exchange(last, now, Ordering::Relaxed, Ordering::Relaxed).is_err()
+        {
+            return None;
+        }
+
+        let count = self.count.swap(0, Ordering::Relaxed);
+        let sum = self.sum_micros.swap(0, Ordering::Relaxed);
+        (count > 0).then(|| (sum as f64 / 1_000_000.0 / count as f64) as f32)
+    }
+}
+
+// 归一化并发送到录音条窗口
+fn emit_amplitude(app_handle: Option<&AppHandle>, amplitude: f32, curve: &AmplitudeCurve) {
+    let Some(handle) = app_handle else {
+        return;
+    };
+    // 归一化到 0-1 范围，并增强效果
+    let normalized = normalize_amplitude(amplitude, curve);
+    if let Some(window) = handle.get_webview_window("recording-bar") {
+        let _ = window.emit("audio-amplitude", normalized);
+    }
+    log::debug!("Audio amplitude: {:.3}", normalized);
+}
+
 fn create_input_stream_with_amplitude(
+    device: &cpal::Device,
     samples: Arc<Mutex<Vec<f32>>>,
     app_handle: Option<AppHandle>,
-) -> Result<(cpal::Stream, u32), String> {
-    let host = cpal::default_host();
-    let device = host
-        .default_input_device()
-        .ok_or("No input device available")?;
-
-    let config = device
-        .default_input_config()
-        .map_err(|e| format!("Failed to get default input config: {}", e))?;
+    profile: Option<Arc<CaptureProfile>>,
+) -> Result<(cpal::Stream, u32, u16), String> {
+    let config = select_input_config(device)?;
 
     let sample_rate = config.sample_rate().0;
+    let stream_config = build_stream_config(&config);
     let err_fn = |err| log::error!("Audio stream error: {}", err);
 
-    // 用于计算音量的变量
-    let amplitude_counter = Arc::new(Mutex::new(0u64));
-    let amplitude_sum = Arc::new(Mutex::new(0.0f32));
-    let last_emit_time = Arc::new(Mutex::new(std::time::Instant::now()));
+    // 曲线和发送间隔在创建流时读取一次，回调中不再访问配置
+    let curve: AmplitudeCurve = crate::get_setting("amplitude_curve");
+    let emit_interval = amplitude_interval_ms();
+    // 使用电池且设置为关闭时不发送音量事件
+    let app_handle = app_handle.filter(|_| emit_interval.is_some());
+    let accumulator = Arc::new(AmplitudeAccumulator::new(
+        emit_interval.unwrap_or(AMPLITUDE_INTERVAL_MS) as u64,
+    ));
 
     let stream = match config.sample_format() {
         cpal::SampleFormat::F32 => {
             let samples_clone = Arc::clone(&samples);
-            let amp_counter_clone = Arc::clone(&amplitude_counter);
-            let amp_sum_clone = Arc::clone(&amplitude_sum);
-            let last_emit_clone = Arc::clone(&last_emit_time);
-            
+            let accumulator = Arc::clone(&accumulator);
+            let profile = profile.clone();
+
             device
                 .build_input_stream(
-                    &config.into(),
+                    &stream_config,
                     move |data: &[f32], _: &cpal::InputCallbackInfo| {
+                        let callback_started = profile.as_ref().map(|_| std::time::Instant::now());
                         // 存储样本
-                        if let Ok(mut s) = samples_clone.lock() {
+                        if let Ok(mut s) = lock_profiled(&samples_clone, profile.as_deref()) {
                             s.extend_from_slice(data);
                         }
-                        
-                        // 计算音量
-                        let mut sum = 0.0f32;
-                        for &sample in data {
-                            sum += sample.abs();
-                        }
-                        let avg = sum / data.len() as f32;
-                        
-                        // 累积音量数据
-                        if let Ok(mut counter) = amp_counter_clone.lock() {
-                            *counter += data.len() as u64;
-                        }
-                        if let Ok(mut sum_val) = amp_sum_clone.lock() {
-                            *sum_val += avg * data.len() as f32;
+
+                        // 计算音量并按间隔发送
+                        let sum: f32 = data.iter().map(|sample| sample.abs()).sum();
+                        if let Some(amplitude) = accumulator.accumulate(sum, data.len()) {
+                            emit_amplitude(app_handle.as_ref(), amplitude, &curve);
                         }
-                        
-                        // 每 50ms 发送一次音量数据
-                        if let Ok(mut last_time) = last_emit_clone.lock() {
-                            if last_time.elapsed().as_millis() >= 50 {
-                                if let (Ok(counter), Ok(sum_val)) = (amp_counter_clone.lock(), amp_sum_clone.lock()) {
-                                    if *counter > 0 {
-                                        let amplitude = *sum_val / *counter as f32;
-                                        // 归一化到 0-1 范围，并增强效果
-                                        let normalized = (amplitude * 5.0).min(1.0);
-                                        
-                                        if let Some(ref handle) = app_handle {
-                                            // 尝试发送到 recording-bar 窗口
-                                            if let Some(window) = handle.get_webview_window("recording-bar") {
-                                                let _ = window.emit("audio-amplitude", normalized);
-                                            }
-                                            log::debug!("Audio amplitude: {:.3}", normalized);
-                                        }
-                                    }
-                                }
-                                *last_time = std::time::Instant::now();
-                                if let Ok(mut c) = amp_counter_clone.lock() { *c = 0; }
-                                if let Ok(mut s) = amp_sum_clone.lock() { *s = 0.0; }
-                            }
+
+                        if let (Some(ref profile), Some(started)) = (&profile, callback_started) {
+                            profile.record_callback(started.elapsed());
                         }
                     },
                     err_fn,
@@ -179,55 +822,32 @@ fn create_input_stream_with_amplitude(
         }
         cpal::SampleFormat::I16 => {
             let samples_clone = Arc::clone(&samples);
-            let amp_counter_clone = Arc::clone(&amplitude_counter);
-            let amp_sum_clone = Arc::clone(&amplitude_sum);
-            let last_emit_clone = Arc::clone(&last_emit_time);
-            
+            let accumulator = Arc::clone(&accumulator);
+            let profile = profile.clone();
+
             device
                 .build_input_stream(
-                    &config.into(),
+                    &stream_config,
                     move |data: &[i16], _: &cpal::InputCallbackInfo| {
+                        let callback_started = profile.as_ref().map(|_| std::time::Instant::now());
+                        let floats: Vec<f32> = data
+                            .iter()
+                            .map(|&sample| sample as f32 / i16::MAX as f32)
+                            .collect();
+
+                        // 计算音量并按间隔发送
+                        let sum: f32 = floats.iter().map(|sample| sample.abs()).sum();
+                        if let Some(amplitude) = accumulator.accumulate(sum, floats.len()) {
+                            emit_amplitude(app_handle.as_ref(), amplitude, &curve);
+                        }
+
                         // 存储样本
-                        if let Ok(mut s) = samples_clone.lock() {
-                            let floats: Vec<f32> = data
-                                .iter()
-                                .map(|&sample| sample as f32 / i16::MAX as f32)
-                                .collect();
+                        if let Ok(mut s) = lock_profiled(&samples_clone, profile.as_deref()) {
                             s.extend(floats);
                         }
-                        
-                        // 计算音量
-                        let mut sum = 0.0f32;
-                        for &sample in data {
-                            sum += (sample as f32 / i16::MAX as f32).abs();
-                        }
-                        let avg = sum / data.len() as f32;
-                        
-                        if let Ok(mut counter) = amp_counter_clone.lock() {
-                            *counter += data.len() as u64;
-                        }
-                        if let Ok(mut sum_val) = amp_sum_clone.lock() {
-                            *sum_val += avg * data.len() as f32;
-                        }
-                        
-                        if let Ok(mut last_time) = last_emit_clone.lock() {
-                            if last_time.elapsed().as_millis() >= 50 {
-                                if let (Ok(counter), Ok(sum_val)) = (amp_counter_clone.lock(), amp_sum_clone.lock()) {
-                                    if *counter > 0 {
-                                        let amplitude = *sum_val / *counter as f32;
-                                        let normalized = (amplitude * 5.0).min(1.0);
-                                        
-                                        if let Some(ref handle) = app_handle {
-                                            if let Some(window) = handle.get_webview_window("recording-bar") {
-                                                let _ = window.emit("audio-amplitude", normalized);
-                                            }
-                                        }
-                                    }
-                                }
-                                *last_time = std::time::Instant::now();
-                                if let Ok(mut c) = amp_counter_clone.lock() { *c = 0; }
-                                if let Ok(mut s) = amp_sum_clone.lock() { *s = 0.0; }
-                            }
+
+                        if let (Some(ref profile), Some(started)) = (&profile, callback_started) {
+                            profile.record_callback(started.elapsed());
                         }
                     },
                     err_fn,
@@ -237,60 +857,35 @@ fn create_input_stream_with_amplitude(
         }
         cpal::SampleFormat::U16 => {
             let samples_clone = Arc::clone(&samples);
-            let amp_counter_clone = Arc::clone(&amplitude_counter);
-            let amp_sum_clone = Arc::clone(&amplitude_sum);
-            let last_emit_clone = Arc::clone(&last_emit_time);
-            
+            let accumulator = Arc::clone(&accumulator);
+            let profile = profile.clone();
+
             device
                 .build_input_stream(
-                    &config.into(),
+                    &stream_config,
                     move |data: &[u16], _: &cpal::InputCallbackInfo| {
+                        let callback_started = profile.as_ref().map(|_| std::time::Instant::now());
+                        let floats: Vec<f32> = data
+                            .iter()
+                            .map(|&sample| {
+                                (sample as f32 - u16::MAX as f32 / 2.0)
+                                    / (u16::MAX as f32 / 2.0)
+                            })
+                            .collect();
+
+                        // 计算音量并按间隔发送
+                        let sum: f32 = floats.iter().map(|sample| sample.abs()).sum();
+                        if let Some(amplitude) = accumulator.accumulate(sum, floats.len()) {
+                            emit_amplitude(app_handle.as_ref(), amplitude, &curve);
+                        }
+
                         // 存储样本
-                        if let Ok(mut s) = samples_clone.lock() {
-                            let floats: Vec<f32> = data
-                                .iter()
-                                .map(|&sample| {
-                                    (sample as f32 - u16::MAX as f32 / 2.0)
-                                        / (u16::MAX as f32 / 2.0)
-                                })
-                                .collect();
+                        if let Ok(mut s) = lock_profiled(&samples_clone, profile.as_deref()) {
                             s.extend(floats);
                         }
-                        
-                        // 计算音量
-                        let mut sum = 0.0f32;
-                        for &sample in data {
-                            let normalized = (sample as f32 - u16::MAX as f32 / 2.0) 
-                                / (u16::MAX as f32 / 2.0);
-                            sum += normalized.abs();
-                        }
-                        let avg = sum / data.len() as f32;
-                        
-                        if let Ok(mut counter) = amp_counter_clone.lock() {
-                            *counter += data.len() as u64;
-                        }
-                        if let Ok(mut sum_val) = amp_sum_clone.lock() {
-                            *sum_val += avg * data.len() as f32;
-                        }
-                        
-                        if let Ok(mut last_time) = last_emit_clone.lock() {
-                            if last_time.elapsed().as_millis() >= 50 {
-                                if let (Ok(counter), Ok(sum_val)) = (amp_counter_clone.lock(), amp_sum_clone.lock()) {
-                                    if *counter > 0 {
-                                        let amplitude = *sum_val / *counter as f32;
-                                        let normalized = (amplitude * 5.0).min(1.0);
-                                        
-                                        if let Some(ref handle) = app_handle {
-                                            if let Some(window) = handle.get_webview_window("recording-bar") {
-                                                let _ = window.emit("audio-amplitude", normalized);
-                                            }
-                                        }
-                                    }
-                                }
-                                *last_time = std::time::Instant::now();
-                                if let Ok(mut c) = amp_counter_clone.lock() { *c = 0; }
-                                if let Ok(mut s) = amp_sum_clone.lock() { *s = 0.0; }
-                            }
+
+                        if let (Some(ref profile), Some(started)) = (&profile, callback_started) {
+                            profile.record_callback(started.elapsed());
                         }
                     },
                     err_fn,
@@ -301,41 +896,49 @@ fn create_input_stream_with_amplitude(
         _ => return Err("Unsupported sample format".to_string()),
     };
 
-    Ok((stream, sample_rate))
+    Ok((stream, sample_rate, stream_config.channels))
 }
 
-fn save_samples_to_wav(
-    samples: &Arc<Mutex<Vec<f32>>>,
-    sample_rate: u32,
-) -> Result<PathBuf, String> {
-    let samples = {
-        let s = samples.lock().map_err(|e| e.to_string())?;
-        s.clone()
-    };
-
-    if samples.is_empty() {
-        return Err("No audio recorded".to_string());
-    }
-
-    log::info!("Recorded {} samples", samples.len());
-
+// 将样本写入临时 WAV 文件（16 位单声道）
+pub fn write_wav(samples: &[f32], sample_rate: u32) -> Result<PathBuf, String> {
     // Create temp file
     let temp_file = NamedTempFile::new()
         .map_err(|e| format!("Failed to create temp file: {}", e))?;
     let path = temp_file.path().with_extension("wav");
 
     // Write WAV file
-    let spec = WavSpec {
+    let writer = WavWriter::create(&path, wav_spec(sample_rate))
+        .map_err(|e| format!("Failed to create WAV writer: {}", e))?;
+    write_samples(writer, samples)?;
+
+    // Keep the temp file from being deleted
+    temp_file.keep().map_err(|e| format!("Failed to keep temp file: {}", e))?;
+
+    log::info!("Audio saved to: {:?}", path);
+
+    Ok(path)
+}
+
+// 在内存中编码 WAV（格式与 write_wav 相同），不创建任何文件
+pub fn encode_wav(samples: &[f32], sample_rate: u32) -> Result<Vec<u8>, String> {
+    let mut buffer = std::io::Cursor::new(Vec::new());
+    let writer = WavWriter::new(&mut buffer, wav_spec(sample_rate))
+        .map_err(|e| format!("Failed to create WAV writer: {}", e))?;
+    write_samples(writer, samples)?;
+    Ok(buffer.into_inner())
+}
+
+fn wav_spec(sample_rate: u32) -> WavSpec {
+    WavSpec {
         channels: 1,
         sample_rate,
         bits_per_sample: 16,
         sample_format: hound::SampleFormat::Int,
-    };
-
-    let mut writer = WavWriter::create(&path, spec)
-        .map_err(|e| format!("Failed to create WAV writer: {}", e))?;
+    }
+}
 
-    for sample in &samples {
+fn write_samples<W: std::io::Write + std::io::Seek>(mut writer: WavWriter<W>, samples: &[f32]) -> Result<(), String> {
+    for sample in samples {
         let amplitude = (sample * i16::MAX as f32) as i16;
         writer
             .write_sample(amplitude)
@@ -344,12 +947,85 @@ fn save_samples_to_wav(
 
     writer
         .finalize()
-        .map_err(|e| format!("Failed to finalize WAV: {}", e))?;
+        .map_err(|e| format!("Failed to finalize WAV: {}", e))
+}
 
-    // Keep the temp file from being deleted
-    temp_file.keep().map_err(|e| format!("Failed to keep temp file: {}", e))?;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    log::info!("Audio saved to: {:?}", path);
+    #[test]
+    fn take_samples_leaves_empty_buffer_for_next_recording() {
+        let samples = Mutex::new(vec![0.1, 0.2, 0.3]);
+        assert_eq!(take_samples(&samples).unwrap(), vec![0.1, 0.2, 0.3]);
+        assert!(samples.lock().unwrap().is_empty());
 
-    Ok(path)
+        samples.lock().unwrap().extend_from_slice(&[0.4, 0.5]);
+        assert_eq!(take_samples(&samples).unwrap(), vec![0.4, 0.5]);
+    }
+
+    #[test]
+    fn clear_samples_drops_leftovers_from_cancelled_recording() {
+        let samples = Mutex::new(vec![0.1, 0.2]);
+        clear_samples(&samples);
+        samples.lock().unwrap().push(0.9);
+        assert_eq!(take_samples(&samples).unwrap(), vec![0.9]);
+    }
+
+    #[test]
+    fn discard_warmup_drops_samples_for_duration() {
+        let mut samples = vec![0.0; 16_000];
+        discard_warmup(&mut samples, 16_000, 1, 250);
+        assert_eq!(samples.len(), 12_000);
+    }
+
+    #[test]
+    fn discard_warmup_drops_whole_interleaved_frames() {
+        let mut samples: Vec<f32> = (0..96_000).map(|i| i as f32).collect();
+        discard_warmup(&mut samples, 48_000, 2, 100);
+        assert_eq!(samples.len(), 96_000 - 9_600);
+        assert_eq!(samples[0], 9_600.0);
+    }
+
+    #[test]
+    fn discard_warmup_handles_zero_and_short_recordings() {
+        let mut samples = vec![0.0; 100];
+        discard_warmup(&mut samples, 16_000, 1, 0);
+        assert_eq!(samples.len(), 100);
+        discard_warmup(&mut samples, 16_000, 1, 1000);
+        assert!(samples.is_empty());
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 1e-4, "expected {}, got {}", expected, actual);
+    }
+
+    #[test]
+    fn linear_curve_scales_by_gain_and_clamps() {
+        let curve = AmplitudeCurve::Linear { gain: 5.0 };
+        assert_close(normalize_amplitude(0.0, &curve), 0.0);
+        assert_close(normalize_amplitude(0.1, &curve), 0.5);
+        assert_close(normalize_amplitude(-0.1, &curve), 0.5);
+        assert_close(normalize_amplitude(0.5, &curve), 1.0);
+    }
+
+    #[test]
+    fn log_curve_maps_minus_60_db_to_0_db() {
+        let curve = AmplitudeCurve::Log;
+        assert_close(normalize_amplitude(0.0, &curve), 0.0);
+        assert_close(normalize_amplitude(0.0001, &curve), 0.0);
+        assert_close(normalize_amplitude(0.001, &curve), 0.0);
+        assert_close(normalize_amplitude(0.1, &curve), 40.0 / 60.0);
+        assert_close(normalize_amplitude(1.0, &curve), 1.0);
+        assert_close(normalize_amplitude(2.0, &curve), 1.0);
+    }
+
+    #[test]
+    fn sqrt_curve_compresses_and_clamps() {
+        let curve = AmplitudeCurve::Sqrt;
+        assert_close(normalize_amplitude(0.0, &curve), 0.0);
+        assert_close(normalize_amplitude(0.04, &curve), 0.4);
+        assert_close(normalize_amplitude(0.25, &curve), 1.0);
+        assert_close(normalize_amplitude(1.0, &curve), 1.0);
+    }
 }
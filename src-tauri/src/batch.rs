@@ -0,0 +1,243 @@
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tauri::{AppHandle, Emitter, Manager};
+use tempfile::NamedTempFile;
+
+use crate::AppState;
+
+// 批量转录支持的音频扩展名（非 WAV 需先转换）
+const SUPPORTED_EXTENSIONS: &[&str] = &["wav", "m4a", "mp3", "aac", "caf", "aiff", "aif", "flac", "ogg"];
+
+// 根据文件头识别出的音频格式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AudioFormat {
+    Wav,
+    Mp3,
+    M4a,
+    Flac,
+    Ogg,
+    Aiff,
+    Caf,
+}
+
+// 根据文件头的 magic bytes 判断格式，无法识别时返回 None
+pub fn sniff_audio_format(header: &[u8]) -> Option<AudioFormat> {
+    match header {
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'A', b'V', b'E', ..] => Some(AudioFormat::Wav),
+        [b'F', b'O', b'R', b'M', _, _, _, _, b'A', b'I', b'F', b'F' | b'C', ..] => Some(AudioFormat::Aiff),
+        [_, _, _, _, b'f', b't', b'y', b'p', ..] => Some(AudioFormat::M4a),
+        [b'f', b'L', b'a', b'C', ..] => Some(AudioFormat::Flac),
+        [b'O', b'g', b'g', b'S', ..] => Some(AudioFormat::Ogg),
+        [b'c', b'a', b'f', b'f', ..] => Some(AudioFormat::Caf),
+        [b'I', b'D', b'3', ..] => Some(AudioFormat::Mp3),
+        // 无 ID3 标签的 MP3/AAC 以帧同步字开头
+        [0xFF, second, ..] if second & 0xE0 == 0xE0 => Some(AudioFormat::Mp3),
+        _ => None,
+    }
+}
+
+pub fn detect_audio_format(path: &Path) -> Option<AudioFormat> {
+    let mut header = [0u8; 12];
+    let mut file = std::fs::File::open(path).ok()?;
+    let len = file.read(&mut header).ok()?;
+    sniff_audio_format(&header[..len])
+}
+
+// 单个文件的转录结果
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct BatchResult {
+    pub path: String,
+    pub success: bool,
+    pub text: Option<String>,
+    pub error: Option<String>,
+    pub output_path: Option<String>,
+}
+
+// batch-progress 事件负载
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct BatchProgress {
+    pub done: usize,
+    pub total: usize,
+}
+
+fn is_supported(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| SUPPORTED_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+// 收集目录下支持的音频文件，按路径排序
+fn collect_audio_files(dir: &Path, recursive: bool, files: &mut Vec<PathBuf>) -> Result<(), String> {
+    let entries = std::fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read directory {:?}: {}", dir, e))?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if recursive {
+                if let Err(e) = collect_audio_files(&path, recursive, files) {
+                    log::warn!("{}", e);
+                }
+            }
+        } else if is_supported(&path) {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+// 非 WAV 文件转为 ASR 使用的 16kHz 单声道 WAV（优先 afconvert，失败时用 ffmpeg）
+fn convert_to_wav(path: &Path) -> Result<NamedTempFile, String> {
+    let temp_file = tempfile::Builder::new()
+        .suffix(".wav")
+        .tempfile()
+        .map_err(|e| format!("Failed to create temp file: {}", e))?;
+
+    let afconvert = Command::new("afconvert")
+        .args(["-f", "WAVE", "-d", "LEI16@16000", "-c", "1"])
+        .arg(path)
+        .arg(temp_file.path())
+        .output();
+    if matches!(afconvert, Ok(ref output) if output.status.success()) {
+        return Ok(temp_file);
+    }
+
+    // afconvert 不支持的格式（如 Ogg）或非 macOS 平台，尝试 ffmpeg
+    let output = Command::new("ffmpeg")
+        .args(["-y", "-loglevel", "error", "-i"])
+        .arg(path)
+        .args(["-ac", "1", "-ar", "16000"])
+        .arg(temp_file.path())
+        .output()
+        .map_err(|e| format!("Failed to convert audio (afconvert and ffmpeg unavailable): {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to convert audio: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(temp_file)
+}
+
+fn transcribe_file(app: &AppHandle, path: &Path, write_text: bool) -> Result<(String, Option<PathBuf>), String> {
+    std::fs::File::open(path).map_err(|e| format!("Failed to open audio file: {}", e))?;
+
+    // 按文件头而不是扩展名判断格式，未知格式直接拒绝
+    let format = detect_audio_format(path)
+        .ok_or_else(|| "Unsupported or unrecognized audio format".to_string())?;
+    let converted = if format == AudioFormat::Wav { None } else { Some(convert_to_wav(path)?) };
+    let audio_path = converted.as_ref().map(|f| f.path()).unwrap_or(path);
+
+    // 直接调用 sidecar：批量结果不写入历史、不输出到光标处；sidecar 锁保证与录音队列串行
+    let state = app.state::<AppState>();
+    let language = crate::resolve_language_hint(&state);
+    let transcript = {
+        let sidecar = state.sidecar_manager.lock().map_err(|e| e.to_string())?;
+        match *sidecar {
+            Some(ref manager) => manager.transcribe(audio_path, language.as_deref())?,
+            None => return Err("Sidecar not initialized".to_string()),
+        }
+    };
+
+    let output_path = if write_text {
+        let txt_path = path.with_extension("txt");
+        std::fs::write(&txt_path, &transcript.text)
+            .map_err(|e| format!("Failed to write transcript: {}", e))?;
+        Some(txt_path)
+    } else {
+        None
+    };
+
+    Ok((transcript.text, output_path))
+}
+
+// 按顺序转录目录中的所有音频文件，单个文件失败时记录错误并继续
+pub fn transcribe_folder(app: &AppHandle, dir: &Path, recursive: bool, write_text: bool) -> Result<Vec<BatchResult>, String> {
+    if !dir.is_dir() {
+        return Err(format!("Not a directory: {:?}", dir));
+    }
+
+    let mut files = Vec::new();
+    collect_audio_files(dir, recursive, &mut files)?;
+    files.sort();
+
+    let total = files.len();
+    log::info!("Batch transcription: {} files in {:?}", total, dir);
+    let _ = app.emit("batch-progress", BatchProgress { done: 0, total });
+
+    let mut results = Vec::with_capacity(total);
+    for (index, path) in files.iter().enumerate() {
+        let result = match transcribe_file(app, path, write_text) {
+            Ok((text, output_path)) => BatchResult {
+                path: path.to_string_lossy().to_string(),
+                success: true,
+                text: Some(text),
+                error: None,
+                output_path: output_path.map(|p| p.to_string_lossy().to_string()),
+            },
+            Err(e) => {
+                log::warn!("Batch transcription failed for {:?}: {}", path, e);
+                BatchResult {
+                    path: path.to_string_lossy().to_string(),
+                    success: false,
+                    text: None,
+                    error: Some(e),
+                    output_path: None,
+                }
+            }
+        };
+        results.push(result);
+
+        let _ = app.emit("batch-progress", BatchProgress { done: index + 1, total });
+    }
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sniffs_wav_and_aiff() {
+        assert_eq!(sniff_audio_format(b"RIFF\x24\x08\x00\x00WAVEfmt "), Some(AudioFormat::Wav));
+        assert_eq!(sniff_audio_format(b"FORM\x00\x00\x00\x00AIFF"), Some(AudioFormat::Aiff));
+        assert_eq!(sniff_audio_format(b"FORM\x00\x00\x00\x00AIFC"), Some(AudioFormat::Aiff));
+    }
+
+    #[test]
+    fn sniffs_mp3_with_id3_tag_or_frame_sync() {
+        assert_eq!(sniff_audio_format(b"ID3\x04\x00\x00\x00\x00\x00\x00\x00\x00"), Some(AudioFormat::Mp3));
+        assert_eq!(sniff_audio_format(&[0xFF, 0xFB, 0x90, 0x64]), Some(AudioFormat::Mp3));
+        assert_eq!(sniff_audio_format(&[0xFF, 0xF1, 0x50, 0x80]), Some(AudioFormat::Mp3));
+    }
+
+    #[test]
+    fn sniffs_flac_ogg_m4a_and_caf() {
+        assert_eq!(sniff_audio_format(b"fLaC\x00\x00\x00\x22"), Some(AudioFormat::Flac));
+        assert_eq!(sniff_audio_format(b"OggS\x00\x02\x00\x00"), Some(AudioFormat::Ogg));
+        assert_eq!(sniff_audio_format(b"\x00\x00\x00\x20ftypM4A "), Some(AudioFormat::M4a));
+        assert_eq!(sniff_audio_format(b"caff\x00\x01\x00\x00"), Some(AudioFormat::Caf));
+    }
+
+    #[test]
+    fn truncated_headers_are_not_recognized() {
+        assert_eq!(sniff_audio_format(b""), None);
+        assert_eq!(sniff_audio_format(b"RIFF\x24\x08\x00\x00WAV"), None);
+        assert_eq!(sniff_audio_format(b"\x00\x00\x00\x20fty"), None);
+        assert_eq!(sniff_audio_format(b"fLa"), None);
+        assert_eq!(sniff_audio_format(&[0xFF]), None);
+    }
+
+    #[test]
+    fn unknown_headers_are_not_recognized() {
+        assert_eq!(sniff_audio_format(b"RIFF\x24\x08\x00\x00AVI LIST"), None);
+        assert_eq!(sniff_audio_format(b"%PDF-1.7\n%\xE2\xE3"), None);
+        assert_eq!(sniff_audio_format(&[0xFF, 0x00, 0x00, 0x00]), None);
+    }
+}
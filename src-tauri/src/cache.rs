@@ -0,0 +1,100 @@
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::sidecar::TranscriptResult;
+
+// 缓存条目上限，超出后淘汰最久未使用的条目
+const MAX_ENTRIES: usize = 200;
+
+// 缓存文件读写串行化，避免队列和批量转录同时写入
+static CACHE_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+struct CacheEntry {
+    key: String,
+    result: TranscriptResult,
+    last_used: i64,
+}
+
+fn get_cache_path() -> PathBuf {
+    let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+    home.join(".mouth-high").join("transcription_cache.json")
+}
+
+pub fn is_enabled() -> bool {
+    crate::get_setting("transcription_cache")
+}
+
+fn load_entries() -> Vec<CacheEntry> {
+    fs::read_to_string(get_cache_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_entries(entries: &[CacheEntry]) -> Result<(), String> {
+    let content = serde_json::to_string(entries)
+        .map_err(|e| format!("Failed to serialize cache: {}", e))?;
+    // 每次写入和命中都会重写，中途崩溃不能留下截断的文件
+    crate::write_atomic(&get_cache_path(), content.as_bytes())
+}
+
+// 缓存键：音频内容 + 语言提示，语言提示不同时结果可能不同
+pub fn cache_key(audio_path: &Path, language: Option<&str>) -> Result<String, String> {
+    let bytes = fs::read(audio_path)
+        .map_err(|e| format!("Failed to read audio for cache: {}", e))?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(&bytes);
+    hasher.update([0u8]);
+    hasher.update(language.unwrap_or("").as_bytes());
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+// 命中时返回缓存的结果，并刷新其使用时间
+pub fn lookup(key: &str) -> Option<TranscriptResult> {
+    let _guard = CACHE_LOCK.lock().ok()?;
+    let mut entries = load_entries();
+    let entry = entries.iter_mut().find(|entry| entry.key == key)?;
+    entry.last_used = chrono::Local::now().timestamp_millis();
+    let result = entry.result.clone();
+
+    if let Err(e) = save_entries(&entries) {
+        log::warn!("Failed to update transcription cache: {}", e);
+    }
+    Some(result)
+}
+
+pub fn store(key: String, result: &TranscriptResult) -> Result<(), String> {
+    let _guard = CACHE_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut entries = load_entries();
+    entries.retain(|entry| entry.key != key);
+    entries.push(CacheEntry {
+        key,
+        result: result.clone(),
+        last_used: chrono::Local::now().timestamp_millis(),
+    });
+
+    // LRU 淘汰
+    if entries.len() > MAX_ENTRIES {
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.last_used));
+        entries.truncate(MAX_ENTRIES);
+    }
+
+    save_entries(&entries)
+}
+
+// 清空缓存，返回删除的条目数
+pub fn clear() -> Result<usize, String> {
+    let _guard = CACHE_LOCK.lock().map_err(|e| e.to_string())?;
+    let count = load_entries().len();
+
+    let path = get_cache_path();
+    if path.exists() {
+        fs::remove_file(&path)
+            .map_err(|e| format!("Failed to remove cache: {}", e))?;
+    }
+    Ok(count)
+}
@@ -0,0 +1,165 @@
+// 频谱降噪：先采集一段环境噪声的平均频谱，录音停止后从每帧频谱中减去该噪声频谱
+// 处理开销约为每秒音频数十次 512 点 FFT/逆 FFT，一分钟录音在普通笔记本上耗时几十毫秒
+use std::path::PathBuf;
+
+use realfft::num_complex::Complex;
+use realfft::RealFftPlanner;
+
+// 帧长和帧移（50% 重叠，配合 Hann 窗叠加后幅度恒定）
+const FRAME_SIZE: usize = 512;
+const HOP_SIZE: usize = FRAME_SIZE / 2;
+// 减去的噪声倍数，略大于 1 以压住噪声的波动
+const OVER_SUBTRACTION: f32 = 1.5;
+// 每个频点至少保留的原始幅度比例，避免减成 0 产生"音乐噪声"
+const SPECTRAL_FLOOR: f32 = 0.05;
+
+// 环境噪声的平均幅度谱，只适用于相同采样率的录音
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct NoiseProfile {
+    pub sample_rate: u32,
+    pub frame_size: usize,
+    pub magnitudes: Vec<f32>,
+}
+
+fn profile_path() -> PathBuf {
+    crate::get_config_path().with_file_name("noise_profile.json")
+}
+
+pub fn load_profile() -> Option<NoiseProfile> {
+    let content = std::fs::read_to_string(profile_path()).ok()?;
+    match serde_json::from_str::<NoiseProfile>(&content) {
+        Ok(profile) if profile.frame_size == FRAME_SIZE => Some(profile),
+        Ok(_) => {
+            log::warn!("Noise profile uses a different frame size, ignoring");
+            None
+        }
+        Err(e) => {
+            log::warn!("Failed to parse noise profile: {}", e);
+            None
+        }
+    }
+}
+
+pub fn save_profile(profile: &NoiseProfile) -> Result<(), String> {
+    let content = serde_json::to_string(profile)
+        .map_err(|e| format!("Failed to serialize noise profile: {}", e))?;
+    crate::write_atomic(&profile_path(), content.as_bytes())
+}
+
+// 周期 Hann 窗，50% 重叠时各帧窗函数之和为 1
+fn hann_window() -> Vec<f32> {
+    (0..FRAME_SIZE)
+        .map(|i| 0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / FRAME_SIZE as f32).cos())
+        .collect()
+}
+
+// 计算一段环境声的平均幅度谱
+pub fn compute_profile(samples: &[f32], sample_rate: u32) -> Result<NoiseProfile, String> {
+    if samples.len() < FRAME_SIZE {
+        return Err("Not enough audio to build a noise profile".to_string());
+    }
+
+    let fft = RealFftPlanner::<f32>::new().plan_fft_forward(FRAME_SIZE);
+    let window = hann_window();
+    let mut input = fft.make_input_vec();
+    let mut spectrum = fft.make_output_vec();
+    let mut magnitudes = vec![0.0f32; spectrum.len()];
+    let mut frames = 0usize;
+
+    for start in (0..=samples.len() - FRAME_SIZE).step_by(HOP_SIZE) {
+        for (i, value) in input.iter_mut().enumerate() {
+            *value = samples[start + i] * window[i];
+        }
+        fft.process(&mut input, &mut spectrum)
+            .map_err(|e| format!("FFT failed: {}", e))?;
+        for (sum, bin) in magnitudes.iter_mut().zip(&spectrum) {
+            *sum += bin.norm();
+        }
+        frames += 1;
+    }
+
+    magnitudes.iter_mut().for_each(|sum| *sum /= frames as f32);
+    Ok(NoiseProfile { sample_rate, frame_size: FRAME_SIZE, magnitudes })
+}
+
+// 谱减法降噪：逐帧减去噪声幅度并保留相位，再重叠相加还原
+pub fn spectral_subtract(samples: &[f32], profile: &NoiseProfile) -> Result<Vec<f32>, String> {
+    if samples.len() < FRAME_SIZE {
+        return Ok(samples.to_vec());
+    }
+
+    let mut planner = RealFftPlanner::<f32>::new();
+    let forward = planner.plan_fft_forward(FRAME_SIZE);
+    let inverse = planner.plan_fft_inverse(FRAME_SIZE);
+    let window = hann_window();
+    let mut input = forward.make_input_vec();
+    let mut spectrum = forward.make_output_vec();
+    let mut frame = inverse.make_output_vec();
+
+    // 末尾补零到整帧，保证最后的样本也被处理
+    let frame_count = (samples.len() - FRAME_SIZE).div_ceil(HOP_SIZE) + 1;
+    let mut output = vec![0.0f32; (frame_count - 1) * HOP_SIZE + FRAME_SIZE];
+
+    for index in 0..frame_count {
+        let start = index * HOP_SIZE;
+        for (i, value) in input.iter_mut().enumerate() {
+            *value = samples.get(start + i).copied().unwrap_or(0.0) * window[i];
+        }
+        forward.process(&mut input, &mut spectrum)
+            .map_err(|e| format!("FFT failed: {}", e))?;
+
+        for (bin, noise) in spectrum.iter_mut().zip(&profile.magnitudes) {
+            let magnitude = bin.norm();
+            if magnitude > 0.0 {
+                let cleaned = (magnitude - OVER_SUBTRACTION * noise).max(SPECTRAL_FLOOR * magnitude);
+                *bin *= cleaned / magnitude;
+            }
+        }
+        // 逆变换要求直流和奈奎斯特频点的虚部为 0
+        if let Some(first) = spectrum.first_mut() {
+            *first = Complex::new(first.re, 0.0);
+        }
+        if let Some(last) = spectrum.last_mut() {
+            *last = Complex::new(last.re, 0.0);
+        }
+
+        inverse.process(&mut spectrum, &mut frame)
+            .map_err(|e| format!("Inverse FFT failed: {}", e))?;
+        for (i, value) in frame.iter().enumerate() {
+            output[start + i] += value / FRAME_SIZE as f32;
+        }
+    }
+
+    output.truncate(samples.len());
+    Ok(output)
+}
+
+// 按 spectral_denoise 设置处理录音；没有噪声谱或采样率不一致时保持原样
+pub fn apply_configured(samples: Vec<f32>, sample_rate: u32) -> Vec<f32> {
+    if !crate::get_setting::<bool>("spectral_denoise") {
+        return samples;
+    }
+    let Some(profile) = load_profile() else {
+        log::warn!("Spectral denoise enabled but no noise profile captured");
+        return samples;
+    };
+    if profile.sample_rate != sample_rate {
+        log::warn!(
+            "Noise profile was captured at {} Hz, recording is {} Hz; skipping denoise",
+            profile.sample_rate, sample_rate
+        );
+        return samples;
+    }
+
+    let started = std::time::Instant::now();
+    match spectral_subtract(&samples, &profile) {
+        Ok(cleaned) => {
+            log::info!("Spectral denoise took {} ms", started.elapsed().as_millis());
+            cleaned
+        }
+        Err(e) => {
+            log::warn!("Spectral denoise failed: {}", e);
+            samples
+        }
+    }
+}
@@ -0,0 +1,75 @@
+// 按当前输入设备自动切换录音/输出模式（例如连接耳机时改用 Toggle 模式）
+use std::time::Duration;
+
+use tauri::AppHandle;
+
+use crate::{OutputMode, RecordingMode};
+
+// 检查输入设备变化的间隔
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+// 设备名包含 device_name_substring（不区分大小写）时应用的模式，未设置的模式保持不变
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize, PartialEq)]
+#[serde(default)]
+pub struct DeviceModeRule {
+    pub device_name_substring: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recording_mode: Option<RecordingMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_mode: Option<OutputMode>,
+}
+
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize, PartialEq)]
+#[serde(default)]
+pub struct DeviceModeRules {
+    pub enabled: bool,
+    pub rules: Vec<DeviceModeRule>,  // 按顺序匹配，使用第一条命中的规则
+}
+
+impl DeviceModeRules {
+    fn matching(&self, device_name: &str) -> Option<&DeviceModeRule> {
+        let device_name = device_name.to_lowercase();
+        self.rules
+            .iter()
+            .find(|rule| device_name.contains(&rule.device_name_substring.to_lowercase()))
+    }
+}
+
+// 后台轮询当前输入设备，设备变化时按规则切换模式
+pub fn start_watcher(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let mut last_device: Option<String> = None;
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+            let settings: DeviceModeRules = crate::get_setting("device_mode_rules");
+            if !settings.enabled {
+                last_device = None;
+                continue;
+            }
+
+            let device = crate::audio::input_device_name();
+            if device == last_device {
+                continue;
+            }
+            log::info!("Input device changed: {:?} -> {:?}", last_device, device);
+            last_device = device.clone();
+
+            let Some(rule) = device.as_deref().and_then(|name| settings.matching(name)) else {
+                continue;
+            };
+            if let Some(mode) = rule.recording_mode {
+                log::info!("Device rule \"{}\" switches recording mode to {:?}", rule.device_name_substring, mode);
+                if let Err(e) = crate::apply_recording_mode(&app, mode) {
+                    log::warn!("Failed to switch recording mode: {}", e);
+                }
+            }
+            if let Some(mode) = rule.output_mode {
+                log::info!("Device rule \"{}\" switches output mode to {:?}", rule.device_name_substring, mode);
+                if let Err(e) = crate::apply_output_mode(&app, mode) {
+                    log::warn!("Failed to switch output mode: {}", e);
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,81 @@
+// 命令返回给前端的结构化错误：界面按 code 区分处理，message 保持可读，便于直接显示和写入日志
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, serde::Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    InvalidInput,  // 参数或设置值不合法
+    NotFound,      // 历史记录、文件等不存在
+    NotReady,      // ASR 服务、录音器等尚未就绪
+    Unsupported,   // 当前平台或构建不支持
+    Io,            // 文件读写失败
+    Internal,
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct CommandError {
+    pub code: ErrorCode,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<serde_json::Value>,
+}
+
+pub type CommandResult<T> = Result<T, CommandError>;
+
+impl CommandError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        CommandError { code, message: message.into(), detail: None }
+    }
+
+    pub fn with_detail(mut self, detail: serde_json::Value) -> Self {
+        self.detail = Some(detail);
+        self
+    }
+
+    pub fn invalid_input(message: impl Into<String>) -> Self {
+        CommandError::new(ErrorCode::InvalidInput, message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        CommandError::new(ErrorCode::NotFound, message)
+    }
+
+    pub fn not_ready(message: impl Into<String>) -> Self {
+        CommandError::new(ErrorCode::NotReady, message)
+    }
+
+    pub fn unsupported(message: impl Into<String>) -> Self {
+        CommandError::new(ErrorCode::Unsupported, message)
+    }
+
+    pub fn io(message: impl Into<String>) -> Self {
+        CommandError::new(ErrorCode::Io, message)
+    }
+}
+
+// 内部函数返回的 String 错误没有类别信息，统一视为 Internal；
+// 需要前端区分处理的错误在出错位置用上面的构造函数明确指定类别
+impl From<String> for CommandError {
+    fn from(message: String) -> Self {
+        CommandError::new(ErrorCode::Internal, message)
+    }
+}
+
+impl From<&str> for CommandError {
+    fn from(message: &str) -> Self {
+        CommandError::from(message.to_string())
+    }
+}
+
+// 内部函数调用其他命令时仍可用 ? 取回错误消息
+impl From<CommandError> for String {
+    fn from(error: CommandError) -> Self {
+        error.message
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
@@ -0,0 +1,191 @@
+// 历史记录导出
+use std::path::Path;
+
+use crate::HistoryItem;
+
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Text,      // 纯文本，仅转录内容
+    Markdown,  // 按日期分组，每条带时间
+    Json,
+    Csv,
+}
+
+fn format_time(item: &HistoryItem) -> String {
+    chrono::DateTime::from_timestamp(item.timestamp, 0)
+        .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_else(|| item.date.clone())
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// 将历史记录按指定格式格式化，单条导出和批量导出共用
+pub fn format_items(items: &[HistoryItem], format: ExportFormat) -> Result<String, String> {
+    let content = match format {
+        ExportFormat::Text => items
+            .iter()
+            .map(|item| item.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+        ExportFormat::Markdown => {
+            let mut content = String::new();
+            let mut current_date: Option<&str> = None;
+            for item in items {
+                if current_date != Some(item.date.as_str()) {
+                    if current_date.is_some() {
+                        content.push('\n');
+                    }
+                    content.push_str(&format!("## {}\n\n", item.date));
+                    current_date = Some(item.date.as_str());
+                }
+                content.push_str(&format!("- **{}** {}\n", format_time(item), item.text));
+            }
+            content
+        }
+        ExportFormat::Json => serde_json::to_string_pretty(items)
+            .map_err(|e| format!("Failed to serialize history: {}", e))?,
+        ExportFormat::Csv => {
+            let mut content = String::from("id,time,char_count,text\n");
+            for item in items {
+                content.push_str(&format!(
+                    "{},{},{},{}\n",
+                    csv_field(&item.id),
+                    csv_field(&format_time(item)),
+                    item.char_count,
+                    csv_field(&item.text)
+                ));
+            }
+            content
+        }
+    };
+    Ok(content)
+}
+
+pub fn export_items(items: &[HistoryItem], path: &Path, format: ExportFormat) -> Result<(), String> {
+    let content = format_items(items, format)?;
+    crate::write_atomic(path, content.as_bytes())
+}
+
+// 字幕格式
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SubtitleFormat {
+    Srt,
+    Vtt,
+}
+
+// 字幕时间戳：SRT 为 00:00:01,500，VTT 为 00:00:01.500
+fn subtitle_time(seconds: f64, format: SubtitleFormat) -> String {
+    let total_ms = (seconds.max(0.0) * 1000.0).round() as u64;
+    let separator = match format {
+        SubtitleFormat::Srt => ',',
+        SubtitleFormat::Vtt => '.',
+    };
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        total_ms / 3_600_000,
+        total_ms / 60_000 % 60,
+        total_ms / 1000 % 60,
+        separator,
+        total_ms % 1000
+    )
+}
+
+// 生成字幕内容：有分段时间时每段一条，否则用录音时长生成覆盖整段的一条
+pub fn format_subtitles(item: &HistoryItem, duration_ms: Option<u64>, format: SubtitleFormat) -> Result<String, String> {
+    let cues: Vec<(f64, f64, &str)> = match item.segments {
+        Some(ref segments) if !segments.is_empty() => segments
+            .iter()
+            .filter(|segment| !segment.text.trim().is_empty())
+            .map(|segment| (segment.start, segment.end, segment.text.trim()))
+            .collect(),
+        _ => {
+            let duration_ms = duration_ms.ok_or_else(|| {
+                format!("No segment timing or recording duration for history item: {}", item.id)
+            })?;
+            vec![(0.0, duration_ms as f64 / 1000.0, item.text.trim())]
+        }
+    };
+
+    let mut content = String::new();
+    if format == SubtitleFormat::Vtt {
+        content.push_str("WEBVTT\n\n");
+    }
+    for (index, (start, end, text)) in cues.iter().enumerate() {
+        if format == SubtitleFormat::Srt {
+            content.push_str(&format!("{}\n", index + 1));
+        }
+        content.push_str(&format!(
+            "{} --> {}\n{}\n\n",
+            subtitle_time(*start, format),
+            subtitle_time(*end, format),
+            text
+        ));
+    }
+    Ok(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sidecar::Segment;
+
+    fn item_with_segments(segments: Vec<(f64, f64, &str)>) -> HistoryItem {
+        let mut item = HistoryItem::new("hello world");
+        item.segments = Some(
+            segments
+                .into_iter()
+                .map(|(start, end, text)| Segment { start, end, text: text.to_string() })
+                .collect(),
+        );
+        item
+    }
+
+    #[test]
+    fn srt_has_indices_and_comma_milliseconds() {
+        let item = item_with_segments(vec![(0.0, 1.5, "hello"), (61.25, 3723.004, "world")]);
+        let srt = format_subtitles(&item, None, SubtitleFormat::Srt).unwrap();
+        assert_eq!(
+            srt,
+            "1\n00:00:00,000 --> 00:00:01,500\nhello\n\n2\n00:01:01,250 --> 01:02:03,004\nworld\n\n"
+        );
+    }
+
+    #[test]
+    fn vtt_has_header_and_dot_milliseconds() {
+        let item = item_with_segments(vec![(0.0, 1.5, "hello")]);
+        let vtt = format_subtitles(&item, None, SubtitleFormat::Vtt).unwrap();
+        assert_eq!(vtt, "WEBVTT\n\n00:00:00.000 --> 00:00:01.500\nhello\n\n");
+    }
+
+    #[test]
+    fn empty_segments_are_skipped() {
+        let item = item_with_segments(vec![(0.0, 1.0, "one"), (1.0, 2.0, "  "), (2.0, 3.0, "two")]);
+        let srt = format_subtitles(&item, None, SubtitleFormat::Srt).unwrap();
+        assert_eq!(
+            srt,
+            "1\n00:00:00,000 --> 00:00:01,000\none\n\n2\n00:00:02,000 --> 00:00:03,000\ntwo\n\n"
+        );
+    }
+
+    #[test]
+    fn duration_only_produces_a_single_cue() {
+        let item = HistoryItem::new(" hello world ");
+        let srt = format_subtitles(&item, Some(2500), SubtitleFormat::Srt).unwrap();
+        assert_eq!(srt, "1\n00:00:00,000 --> 00:00:02,500\nhello world\n\n");
+    }
+
+    #[test]
+    fn missing_segments_and_duration_is_an_error() {
+        let item = HistoryItem::new("hello");
+        assert!(format_subtitles(&item, None, SubtitleFormat::Vtt).is_err());
+    }
+}
@@ -0,0 +1,124 @@
+// 录音开始/结束的提示音
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use std::time::Duration;
+
+const CHIME_DURATION_MS: u64 = 120;
+const START_FREQUENCY: f32 = 880.0;
+const STOP_FREQUENCY: f32 = 660.0;
+const CHIME_VOLUME: f32 = 0.2;
+
+pub fn is_enabled() -> bool {
+    crate::get_setting("feedback_sounds")
+}
+
+// 列出可用的输出设备名称
+pub fn list_output_devices() -> Result<Vec<String>, String> {
+    let devices = crate::audio::selected_host()
+        .output_devices()
+        .map_err(|e| format!("Failed to list output devices: {}", e))?;
+    Ok(devices.filter_map(|device| device.name().ok()).collect())
+}
+
+// 使用配置中选择的输出设备，找不到时退回默认输出设备
+fn output_device() -> Result<cpal::Device, String> {
+    let host = crate::audio::selected_host();
+    if let Some(name) = crate::get_setting::<Option<String>>("feedback_output_device") {
+        let found = host
+            .output_devices()
+            .ok()
+            .and_then(|mut devices| devices.find(|d| d.name().ok().as_deref() == Some(name.as_str())));
+        match found {
+            Some(device) => return Ok(device),
+            None => log::warn!("Feedback output device {} not found, using default", name),
+        }
+    }
+    host.default_output_device()
+        .ok_or_else(|| "No output device available".to_string())
+}
+
+pub fn play_start() {
+    play_chime(START_FREQUENCY);
+}
+
+pub fn play_stop() {
+    play_chime(STOP_FREQUENCY);
+}
+
+// 在后台线程播放一段短正弦音，不阻塞录音流程
+fn play_chime(frequency: f32) {
+    if !is_enabled() {
+        return;
+    }
+    std::thread::spawn(move || {
+        if let Err(e) = play_tone(frequency) {
+            log::warn!("Failed to play feedback sound: {}", e);
+        }
+    });
+}
+
+fn play_tone(frequency: f32) -> Result<(), String> {
+    let device = output_device()?;
+    let config = device
+        .default_output_config()
+        .map_err(|e| format!("Failed to get default output config: {}", e))?;
+
+    let sample_rate = config.sample_rate().0 as f32;
+    let channels = config.channels() as usize;
+    let total = (sample_rate * CHIME_DURATION_MS as f32 / 1000.0) as usize;
+    let mut index = 0usize;
+    // 首尾淡入淡出，避免爆音
+    let mut next_sample = move || {
+        let value = if index < total {
+            let t = index as f32 / sample_rate;
+            let envelope = (index.min(total - index) as f32 / (total as f32 * 0.2)).min(1.0);
+            (t * frequency * 2.0 * std::f32::consts::PI).sin() * CHIME_VOLUME * envelope
+        } else {
+            0.0
+        };
+        index += 1;
+        value
+    };
+
+    let err_fn = |err| log::error!("Feedback stream error: {}", err);
+    let stream = match config.sample_format() {
+        cpal::SampleFormat::F32 => device.build_output_stream(
+            &config.into(),
+            move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
+                for frame in data.chunks_mut(channels) {
+                    let value = next_sample();
+                    frame.iter_mut().for_each(|s| *s = value);
+                }
+            },
+            err_fn,
+            None,
+        ),
+        cpal::SampleFormat::I16 => device.build_output_stream(
+            &config.into(),
+            move |data: &mut [i16], _: &cpal::OutputCallbackInfo| {
+                for frame in data.chunks_mut(channels) {
+                    let value = (next_sample() * i16::MAX as f32) as i16;
+                    frame.iter_mut().for_each(|s| *s = value);
+                }
+            },
+            err_fn,
+            None,
+        ),
+        cpal::SampleFormat::U16 => device.build_output_stream(
+            &config.into(),
+            move |data: &mut [u16], _: &cpal::OutputCallbackInfo| {
+                for frame in data.chunks_mut(channels) {
+                    let value = ((next_sample() + 1.0) * 0.5 * u16::MAX as f32) as u16;
+                    frame.iter_mut().for_each(|s| *s = value);
+                }
+            },
+            err_fn,
+            None,
+        ),
+        format => return Err(format!("Unsupported sample format: {:?}", format)),
+    }
+    .map_err(|e| format!("Failed to build output stream: {}", e))?;
+
+    stream.play().map_err(|e| format!("Failed to play feedback sound: {}", e))?;
+    std::thread::sleep(Duration::from_millis(CHIME_DURATION_MS + 50));
+    Ok(())
+}
@@ -34,3 +34,178 @@ pub fn activate_app(bundle_id: &str) -> Result<(), String> {
         Err(String::from_utf8_lossy(&output.stderr).to_string())
     }
 }
+
+/// 激活标题包含指定文字的窗口（不区分大小写）
+#[cfg(target_os = "macos")]
+pub fn activate_window_by_title(title: &str) -> Result<(), String> {
+    let escaped = title.replace('\\', "\\\\").replace('"', "\\\"");
+    let script = format!(
+        r#"tell application "System Events"
+    repeat with p in (every process whose background only is false)
+        repeat with w in (every window of p)
+            ignoring case
+                set matched to (name of w contains "{}")
+            end ignoring
+            if matched then
+                set frontmost of p to true
+                perform action "AXRaise" of w
+                return "found"
+            end if
+        end repeat
+    end repeat
+end tell
+return """#,
+        escaped
+    );
+
+    let output = Command::new("osascript")
+        .args(["-e", &script])
+        .output()
+        .map_err(|e| e.to_string())?;
+
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).to_string());
+    }
+    if String::from_utf8_lossy(&output.stdout).trim() == "found" {
+        Ok(())
+    } else {
+        Err(format!("No window matching \"{}\"", title))
+    }
+}
+
+/// 激活标题包含指定文字的窗口（依赖 wmctrl，-a 按子串匹配且不区分大小写）
+#[cfg(target_os = "linux")]
+pub fn activate_window_by_title(title: &str) -> Result<(), String> {
+    let output = Command::new("wmctrl")
+        .args(["-a", title])
+        .output()
+        .map_err(|e| format!("Failed to run wmctrl: {}", e))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!("No window matching \"{}\"", title))
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+pub fn activate_window_by_title(_title: &str) -> Result<(), String> {
+    Err("Targeting windows by title is not supported on this platform".to_string())
+}
+
+/// 前台应用的窗口是否处于全屏状态（如演示幻灯片）
+#[cfg(target_os = "macos")]
+pub fn is_frontmost_fullscreen() -> bool {
+    let output = Command::new("osascript")
+        .args(["-e", r#"tell application "System Events" to get value of attribute "AXFullScreen" of front window of (first process whose frontmost is true)"#])
+        .output();
+    matches!(output, Ok(ref o) if o.status.success() && String::from_utf8_lossy(&o.stdout).trim() == "true")
+}
+
+/// 前台窗口是否处于全屏状态（依赖 xprop，读取 _NET_WM_STATE）
+#[cfg(target_os = "linux")]
+pub fn is_frontmost_fullscreen() -> bool {
+    let Ok(active) = Command::new("xprop").args(["-root", "_NET_ACTIVE_WINDOW"]).output() else {
+        return false;
+    };
+    // 输出形如 "_NET_ACTIVE_WINDOW(WINDOW): window id # 0x3a00007"
+    let active = String::from_utf8_lossy(&active.stdout);
+    let Some(window_id) = active.split_whitespace().last().filter(|id| id.starts_with("0x")) else {
+        return false;
+    };
+    Command::new("xprop")
+        .args(["-id", window_id, "_NET_WM_STATE"])
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).contains("_NET_WM_STATE_FULLSCREEN"))
+        .unwrap_or(false)
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+pub fn is_frontmost_fullscreen() -> bool {
+    false
+}
+
+/// 获取鼠标指针下窗口所属应用的 bundle identifier
+#[cfg(target_os = "macos")]
+pub fn get_app_under_pointer() -> Option<String> {
+    use cocoa::foundation::NSPoint;
+    use objc::runtime::Object;
+    use objc::{class, msg_send, sel, sel_impl};
+    use std::ffi::CStr;
+    use std::os::raw::c_char;
+
+    unsafe {
+        let location: NSPoint = msg_send![class!(NSEvent), mouseLocation];
+        let window_number: isize = msg_send![class!(NSWindow), windowNumberAtPoint: location belowWindowWithWindowNumber: 0isize];
+        if window_number <= 0 {
+            return None;
+        }
+
+        let pid = window_owner_pid(window_number as u32)?;
+        let app: *mut Object = msg_send![class!(NSRunningApplication), runningApplicationWithProcessIdentifier: pid];
+        if app.is_null() {
+            return None;
+        }
+
+        let bundle_id: *mut Object = msg_send![app, bundleIdentifier];
+        if bundle_id.is_null() {
+            return None;
+        }
+
+        let utf8: *const c_char = msg_send![bundle_id, UTF8String];
+        if utf8.is_null() {
+            return None;
+        }
+        Some(CStr::from_ptr(utf8).to_string_lossy().into_owned())
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn get_app_under_pointer() -> Option<String> {
+    None
+}
+
+#[cfg(target_os = "macos")]
+mod cg {
+    use std::ffi::c_void;
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        pub fn CGWindowListCopyWindowInfo(option: u32, relative_to_window: u32) -> *const c_void;
+        pub static kCGWindowOwnerPID: *const c_void;
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        pub fn CFArrayGetCount(array: *const c_void) -> isize;
+        pub fn CFArrayGetValueAtIndex(array: *const c_void, index: isize) -> *const c_void;
+        pub fn CFDictionaryGetValue(dict: *const c_void, key: *const c_void) -> *const c_void;
+        pub fn CFNumberGetValue(number: *const c_void, number_type: isize, value: *mut c_void) -> bool;
+        pub fn CFRelease(cf: *const c_void);
+    }
+
+    pub const LIST_OPTION_INCLUDING_WINDOW: u32 = 1 << 3;
+    pub const NUMBER_SINT32_TYPE: isize = 3;
+}
+
+/// 查询窗口所属进程的 pid
+#[cfg(target_os = "macos")]
+unsafe fn window_owner_pid(window_number: u32) -> Option<i32> {
+    use std::ffi::c_void;
+
+    let info = cg::CGWindowListCopyWindowInfo(cg::LIST_OPTION_INCLUDING_WINDOW, window_number);
+    if info.is_null() {
+        return None;
+    }
+
+    let mut pid: i32 = 0;
+    let found = cg::CFArrayGetCount(info) > 0 && {
+        let dict = cg::CFArrayGetValueAtIndex(info, 0);
+        let number = cg::CFDictionaryGetValue(dict, cg::kCGWindowOwnerPID);
+        !number.is_null()
+            && cg::CFNumberGetValue(number, cg::NUMBER_SINT32_TYPE, &mut pid as *mut i32 as *mut c_void)
+    };
+    cg::CFRelease(info);
+
+    found.then_some(pid)
+}
@@ -0,0 +1,159 @@
+// 历史记录存储：~/.mouth-high/history.jsonl，每行一条记录，按写入顺序排列（最旧在前）
+// 新增记录只追加一行；修改、删除和保留清理时整体重写（同时压缩掉无法解析的行）
+use std::fs::{self, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use crate::HistoryItem;
+
+pub fn history_path() -> PathBuf {
+    crate::get_config_path().with_file_name("history.jsonl")
+}
+
+// 解析后的历史记录缓存：本模块写入后立即清空；文件被外部修改时按修改时间和大小判断过期
+type HistoryStamp = (std::time::SystemTime, u64);
+static HISTORY_CACHE: Mutex<Option<(HistoryStamp, Arc<Vec<HistoryItem>>)>> = Mutex::new(None);
+
+// 写入锁：追加和"读取-修改-重写"都在锁内进行，避免重写时覆盖掉其他线程刚追加的记录
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+fn invalidate_cache() {
+    if let Ok(mut cache) = HISTORY_CACHE.lock() {
+        *cache = None;
+    }
+}
+
+// 读取全部历史记录，返回最新在前的列表；无法解析的行跳过
+pub fn load() -> Result<Arc<Vec<HistoryItem>>, String> {
+    let path = history_path();
+    let Ok(metadata) = fs::metadata(&path) else {
+        return Ok(Arc::new(Vec::new()));
+    };
+    let stamp = (
+        metadata.modified().map_err(|e| format!("Failed to read history metadata: {}", e))?,
+        metadata.len(),
+    );
+
+    let mut cache = HISTORY_CACHE.lock().map_err(|e| e.to_string())?;
+    if let Some((cached_stamp, ref history)) = *cache {
+        if cached_stamp == stamp {
+            return Ok(Arc::clone(history));
+        }
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read history: {}", e))?;
+    let mut items = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<HistoryItem>(line) {
+            Ok(item) => items.push(item),
+            Err(e) => log::warn!("Skipping invalid history line {}: {}", index + 1, e),
+        }
+    }
+    items.reverse();
+
+    let history = Arc::new(items);
+    *cache = Some((stamp, Arc::clone(&history)));
+    Ok(history)
+}
+
+pub fn load_owned() -> Result<Vec<HistoryItem>, String> {
+    Ok(load()?.as_ref().clone())
+}
+
+// 在写入锁内读取全部记录（最新在前）交给 f 修改，成功后整体重写；f 返回错误时不写入
+pub fn update<T, E: From<String>>(f: impl FnOnce(&mut Vec<HistoryItem>) -> Result<T, E>) -> Result<T, E> {
+    let _guard = WRITE_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut history = load_owned()?;
+    let result = f(&mut history)?;
+    rewrite(&history)?;
+    Ok(result)
+}
+
+// 追加一条记录，不读取也不重写已有内容
+pub fn append(item: &HistoryItem) -> Result<(), String> {
+    let _guard = WRITE_LOCK.lock().map_err(|e| e.to_string())?;
+    let path = history_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+
+    let mut line = serde_json::to_string(item)
+        .map_err(|e| format!("Failed to serialize history item: {}", e))?;
+    line.push('\n');
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open history: {}", e))?;
+
+    // 上次写入中断时最后一行可能不完整，先补换行，避免新记录接在残行后面
+    if file.seek(SeekFrom::End(0)).map_err(|e| e.to_string())? > 0 {
+        let mut last = [0u8; 1];
+        file.seek(SeekFrom::End(-1)).map_err(|e| e.to_string())?;
+        file.read_exact(&mut last).map_err(|e| format!("Failed to read history: {}", e))?;
+        if last[0] != b'\n' {
+            line.insert(0, '\n');
+        }
+    }
+
+    let written = file
+        .write_all(line.as_bytes())
+        .map_err(|e| format!("Failed to write history: {}", e));
+    invalidate_cache();
+    written
+}
+
+// 用给定的列表（最新在前）整体重写文件，调用方需持有写入锁
+fn rewrite(history: &[HistoryItem]) -> Result<(), String> {
+    let mut content = String::new();
+    for item in history.iter().rev() {
+        let line = serde_json::to_string(item)
+            .map_err(|e| format!("Failed to serialize history item: {}", e))?;
+        content.push_str(&line);
+        content.push('\n');
+    }
+    let written = crate::write_atomic(&history_path(), content.as_bytes());
+    invalidate_cache();
+    written
+}
+
+// 旧版本把历史记录存在 config.json 的 history 字段中：合并进 history.jsonl 后从配置中删除
+pub fn migrate_from_config(config: &mut serde_json::Value) -> Result<usize, String> {
+    let Some(value) = config.as_object_mut().and_then(|map| map.remove("history")) else {
+        return Ok(0);
+    };
+    let legacy: Vec<HistoryItem> = serde_json::from_value(value)
+        .map_err(|e| format!("Invalid history in config: {}", e))?;
+    let count = legacy.len();
+
+    update(|history| {
+        history.retain(|item| !legacy.iter().any(|old| old.id == item.id));
+        history.extend(legacy);
+        history.sort_by_key(|item| std::cmp::Reverse(item.timestamp));
+        Ok::<_, String>(())
+    })?;
+    Ok(count)
+}
+
+// 启动时把配置文件中的历史记录移出
+pub fn migrate_config_history() {
+    let mut config = crate::load_config();
+    if config.get("history").is_none() {
+        return;
+    }
+    match migrate_from_config(&mut config).and_then(|count| {
+        crate::save_config(&config)?;
+        Ok(count)
+    }) {
+        Ok(count) => log::info!("Moved {} history items to {:?}", count, history_path()),
+        Err(e) => log::warn!("Failed to move history out of config: {}", e),
+    }
+}
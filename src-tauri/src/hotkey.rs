@@ -1,4 +1,7 @@
-use crate::{audio::AudioRecorderHandle, AppState, HotkeyConfig};
+use crate::transcriber::{AudioHandoff, RecordedAudio, Transcriber};
+use crate::{audio::AudioRecorderHandle, sidecar::TranscriptResult, AppState, HotkeyConfig};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Mutex;
 use tauri::{AppHandle, Emitter, Manager};
 use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Modifiers, Shortcut, ShortcutState};
@@ -12,6 +15,103 @@ pub struct CurrentShortcut {
     pub shortcut: Mutex<Option<Shortcut>>,
 }
 
+// 已注册的录音快捷键（主快捷键和额外快捷键）及各自绑定的录音模式，按 Shortcut id 索引
+pub struct RecordingShortcuts {
+    pub modes: Mutex<HashMap<u32, Option<crate::RecordingMode>>>,
+}
+
+// 附加功能快捷键（录音快捷键之外的可选快捷键）
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HotkeyAction {
+    ResetRecording,   // 强制重置录音状态
+    CycleOutputMode,  // 循环切换输出模式
+    OpenSettings,     // 打开主窗口的设置页
+    OpenHistory,      // 打开主窗口的历史记录页
+}
+
+// 已注册的附加功能快捷键
+pub struct ActionShortcuts {
+    pub shortcuts: Mutex<HashMap<HotkeyAction, Shortcut>>,
+}
+
+// 录音条显示期间临时注册的 Escape 取消快捷键
+pub struct EscapeShortcut {
+    pub registered: Mutex<bool>,
+}
+
+// 可绑定的按键及其对应的 Code，快捷键转换和 get_supported_keys 共用
+const SUPPORTED_KEYS: &[(&str, Code)] = &[
+    ("a", Code::KeyA),
+    ("b", Code::KeyB),
+    ("c", Code::KeyC),
+    ("d", Code::KeyD),
+    ("e", Code::KeyE),
+    ("f", Code::KeyF),
+    ("g", Code::KeyG),
+    ("h", Code::KeyH),
+    ("i", Code::KeyI),
+    ("j", Code::KeyJ),
+    ("k", Code::KeyK),
+    ("l", Code::KeyL),
+    ("m", Code::KeyM),
+    ("n", Code::KeyN),
+    ("o", Code::KeyO),
+    ("p", Code::KeyP),
+    ("q", Code::KeyQ),
+    ("r", Code::KeyR),
+    ("s", Code::KeyS),
+    ("t", Code::KeyT),
+    ("u", Code::KeyU),
+    ("v", Code::KeyV),
+    ("w", Code::KeyW),
+    ("x", Code::KeyX),
+    ("y", Code::KeyY),
+    ("z", Code::KeyZ),
+    ("0", Code::Digit0),
+    ("1", Code::Digit1),
+    ("2", Code::Digit2),
+    ("3", Code::Digit3),
+    ("4", Code::Digit4),
+    ("5", Code::Digit5),
+    ("6", Code::Digit6),
+    ("7", Code::Digit7),
+    ("8", Code::Digit8),
+    ("9", Code::Digit9),
+    ("f1", Code::F1),
+    ("f2", Code::F2),
+    ("f3", Code::F3),
+    ("f4", Code::F4),
+    ("f5", Code::F5),
+    ("f6", Code::F6),
+    ("f7", Code::F7),
+    ("f8", Code::F8),
+    ("f9", Code::F9),
+    ("f10", Code::F10),
+    ("f11", Code::F11),
+    ("f12", Code::F12),
+    ("space", Code::Space),
+    ("enter", Code::Enter),
+    ("tab", Code::Tab),
+    ("escape", Code::Escape),
+    ("esc", Code::Escape),
+    ("backspace", Code::Backspace),
+    ("delete", Code::Delete),
+    ("home", Code::Home),
+    ("end", Code::End),
+    ("pageup", Code::PageUp),
+    ("pagedown", Code::PageDown),
+    ("up", Code::ArrowUp),
+    ("down", Code::ArrowDown),
+    ("left", Code::ArrowLeft),
+    ("right", Code::ArrowRight),
+];
+
+// 可绑定的按键名称（供设置界面使用）
+pub fn supported_keys() -> Vec<String> {
+    SUPPORTED_KEYS.iter().map(|(name, _)| name.to_string()).collect()
+}
+
 // 将配置转换为 Shortcut
 fn config_to_shortcut(config: &HotkeyConfig) -> Result<(Shortcut, String), String> {
     let mut modifiers = Modifiers::empty();
@@ -27,71 +127,12 @@ fn config_to_shortcut(config: &HotkeyConfig) -> Result<(Shortcut, String), Strin
     }
     
     // 将 key 字符串转换为 Code
-    let code = match config.key.to_lowercase().as_str() {
-        "a" => Code::KeyA,
-        "b" => Code::KeyB,
-        "c" => Code::KeyC,
-        "d" => Code::KeyD,
-        "e" => Code::KeyE,
-        "f" => Code::KeyF,
-        "g" => Code::KeyG,
-        "h" => Code::KeyH,
-        "i" => Code::KeyI,
-        "j" => Code::KeyJ,
-        "k" => Code::KeyK,
-        "l" => Code::KeyL,
-        "m" => Code::KeyM,
-        "n" => Code::KeyN,
-        "o" => Code::KeyO,
-        "p" => Code::KeyP,
-        "q" => Code::KeyQ,
-        "r" => Code::KeyR,
-        "s" => Code::KeyS,
-        "t" => Code::KeyT,
-        "u" => Code::KeyU,
-        "v" => Code::KeyV,
-        "w" => Code::KeyW,
-        "x" => Code::KeyX,
-        "y" => Code::KeyY,
-        "z" => Code::KeyZ,
-        "0" => Code::Digit0,
-        "1" => Code::Digit1,
-        "2" => Code::Digit2,
-        "3" => Code::Digit3,
-        "4" => Code::Digit4,
-        "5" => Code::Digit5,
-        "6" => Code::Digit6,
-        "7" => Code::Digit7,
-        "8" => Code::Digit8,
-        "9" => Code::Digit9,
-        "f1" => Code::F1,
-        "f2" => Code::F2,
-        "f3" => Code::F3,
-        "f4" => Code::F4,
-        "f5" => Code::F5,
-        "f6" => Code::F6,
-        "f7" => Code::F7,
-        "f8" => Code::F8,
-        "f9" => Code::F9,
-        "f10" => Code::F10,
-        "f11" => Code::F11,
-        "f12" => Code::F12,
-        "space" => Code::Space,
-        "enter" => Code::Enter,
-        "tab" => Code::Tab,
-        "escape" | "esc" => Code::Escape,
-        "backspace" => Code::Backspace,
-        "delete" => Code::Delete,
-        "home" => Code::Home,
-        "end" => Code::End,
-        "pageup" => Code::PageUp,
-        "pagedown" => Code::PageDown,
-        "up" => Code::ArrowUp,
-        "down" => Code::ArrowDown,
-        "left" => Code::ArrowLeft,
-        "right" => Code::ArrowRight,
-        _ => return Err(format!("Unsupported key: {}", config.key)),
-    };
+    let key = config.key.to_lowercase();
+    let code = SUPPORTED_KEYS
+        .iter()
+        .find(|(name, _)| *name == key)
+        .map(|(_, code)| *code)
+        .ok_or_else(|| format!("Unsupported key: {}", config.key))?;
     
     let shortcut = if modifiers.is_empty() {
         Shortcut::new(None, code)
@@ -116,11 +157,21 @@ pub fn setup_hotkey(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
     app.manage(CurrentShortcut {
         shortcut: Mutex::new(None),
     });
+    app.manage(RecordingShortcuts {
+        modes: Mutex::new(HashMap::new()),
+    });
+    app.manage(ActionShortcuts {
+        shortcuts: Mutex::new(HashMap::new()),
+    });
+    app.manage(EscapeShortcut {
+        registered: Mutex::new(false),
+    });
 
     // 尝试从配置读取快捷键
     let config = crate::get_hotkey_config().unwrap_or_else(|_| HotkeyConfig {
         modifiers: vec!["ctrl".to_string(), "shift".to_string()],
         key: "r".to_string(),
+        mode: None,
     });
 
     // 尝试注册配置的快捷键
@@ -129,17 +180,17 @@ pub fn setup_hotkey(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
         
         // 尝试默认快捷键列表
         let defaults = vec![
-            HotkeyConfig { modifiers: vec!["ctrl".to_string(), "shift".to_string()], key: "r".to_string() },
-            HotkeyConfig { modifiers: vec!["cmd".to_string(), "shift".to_string()], key: "r".to_string() },
-            HotkeyConfig { modifiers: vec!["alt".to_string(), "shift".to_string()], key: "r".to_string() },
-            HotkeyConfig { modifiers: vec![], key: "f5".to_string() },
-            HotkeyConfig { modifiers: vec!["ctrl".to_string()], key: "r".to_string() },
-            HotkeyConfig { modifiers: vec!["cmd".to_string()], key: "r".to_string() },
+            HotkeyConfig { modifiers: vec!["ctrl".to_string(), "shift".to_string()], key: "r".to_string(), mode: None },
+            HotkeyConfig { modifiers: vec!["cmd".to_string(), "shift".to_string()], key: "r".to_string(), mode: None },
+            HotkeyConfig { modifiers: vec!["alt".to_string(), "shift".to_string()], key: "r".to_string(), mode: None },
+            HotkeyConfig { modifiers: vec![], key: "f5".to_string(), mode: None },
+            HotkeyConfig { modifiers: vec!["ctrl".to_string()], key: "r".to_string(), mode: None },
+            HotkeyConfig { modifiers: vec!["cmd".to_string()], key: "r".to_string(), mode: None },
         ];
         
         let mut registered = false;
         for default_config in defaults {
-            if let Ok(_) = register_hotkey_with_config(app, &default_config) {
+            if register_hotkey_with_config(app, &default_config).is_ok() {
                 // 保存成功注册的默认配置
                 let _ = crate::set_hotkey_config(default_config);
                 registered = true;
@@ -149,13 +200,208 @@ pub fn setup_hotkey(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
         
         if !registered {
             log::error!("Could not register any global hotkey. Please grant Accessibility permissions in System Settings > Privacy & Security > Accessibility");
-            let _ = app.emit("error", "无法注册全局快捷键。请在 系统设置 > 隐私与安全性 > 辅助功能 中授予权限。".to_string());
+            crate::emit_error(app, "无法注册全局快捷键。请在 系统设置 > 隐私与安全性 > 辅助功能 中授予权限。".to_string());
+        }
+    }
+
+    register_extra_hotkeys(app);
+    register_action_hotkeys(app);
+
+    Ok(())
+}
+
+// 读取配置中的额外录音快捷键
+pub fn get_extra_hotkeys() -> Vec<HotkeyConfig> {
+    crate::get_setting("extra_hotkeys")
+}
+
+// 注册配置中的额外录音快捷键，失败时仅记录日志
+fn register_extra_hotkeys(app: &AppHandle) {
+    for config in get_extra_hotkeys() {
+        if let Err(e) = register_extra_hotkey(app, &config) {
+            log::warn!("Failed to register extra hotkey {}: {}", config.to_display_string(), e);
+        }
+    }
+}
+
+fn register_extra_hotkey(app: &AppHandle, config: &HotkeyConfig) -> Result<(), String> {
+    let (shortcut, name) = config_to_shortcut(config)?;
+
+    {
+        let recording = app.state::<RecordingShortcuts>();
+        let mut modes = recording.modes.lock().map_err(|e| e.to_string())?;
+        if modes.contains_key(&shortcut.id()) {
+            return Err(format!("{} is already used as a recording hotkey", name));
+        }
+        modes.insert(shortcut.id(), config.mode);
+    }
+
+    let handle = app.clone();
+    let result = app.global_shortcut()
+        .on_shortcut(shortcut, move |_app, fired, event| {
+            // 忽略已被注销的旧处理器
+            let Some(mode) = shortcut_mode(&handle, fired) else {
+                log::warn!("Stale extra hotkey handler triggered: {:?}", fired);
+                return;
+            };
+            handle_trigger(&handle, event.state, mode, fired.id());
+        })
+        .map_err(|e| format!("Failed to register hotkey: {:?}", e));
+
+    if let Err(e) = result {
+        let recording = app.state::<RecordingShortcuts>();
+        recording.modes.lock().map_err(|e| e.to_string())?.remove(&shortcut.id());
+        return Err(e);
+    }
+
+    log::info!("Extra hotkey registered: {} (mode: {:?})", name, config.mode);
+    Ok(())
+}
+
+// 设置额外录音快捷键：校验、保存并重新注册所有快捷键
+pub fn set_extra_hotkeys(app: &AppHandle, hotkeys: Vec<HotkeyConfig>) -> Result<(), String> {
+    for config in &hotkeys {
+        config_to_shortcut(config)?;
+    }
+    crate::set_setting("extra_hotkeys", &hotkeys)?;
+    log::info!("Extra hotkeys set: {}", hotkeys.len());
+    reregister_hotkey(app)
+}
+
+// 触发的录音快捷键绑定的模式；未注册时返回 None
+fn shortcut_mode(app: &AppHandle, shortcut: &Shortcut) -> Option<Option<crate::RecordingMode>> {
+    let recording = app.state::<RecordingShortcuts>();
+    let modes = recording.modes.lock().unwrap();
+    modes.get(&shortcut.id()).copied()
+}
+
+// 读取配置中的附加功能快捷键
+pub fn get_action_hotkeys() -> HashMap<HotkeyAction, HotkeyConfig> {
+    crate::get_setting("action_hotkeys")
+}
+
+// 注册配置中的所有附加功能快捷键，失败时仅记录日志
+fn register_action_hotkeys(app: &AppHandle) {
+    for (action, config) in get_action_hotkeys() {
+        if let Err(e) = register_action_hotkey(app, action, &config) {
+            log::warn!("Failed to register {:?} hotkey: {}", action, e);
+        }
+    }
+}
+
+fn register_action_hotkey(app: &AppHandle, action: HotkeyAction, config: &HotkeyConfig) -> Result<(), String> {
+    let (shortcut, name) = config_to_shortcut(config)?;
+
+    // 不能与录音快捷键或其他功能快捷键冲突
+    {
+        let current = app.state::<CurrentShortcut>();
+        let record_shortcut = *current.shortcut.lock().map_err(|e| e.to_string())?;
+        if record_shortcut == Some(shortcut) || shortcut_mode(app, &shortcut).is_some() {
+            return Err(format!("{} is already used as the recording hotkey", name));
+        }
+    }
+    {
+        let actions = app.state::<ActionShortcuts>();
+        let mut shortcuts = actions.shortcuts.lock().map_err(|e| e.to_string())?;
+        if shortcuts.iter().any(|(a, s)| *a != action && *s == shortcut) {
+            return Err(format!("{} is already used by another hotkey", name));
         }
+        shortcuts.insert(action, shortcut);
     }
 
+    let handle = app.clone();
+    let result = app.global_shortcut()
+        .on_shortcut(shortcut, move |_app, _shortcut, event| {
+            if !matches!(event.state, ShortcutState::Pressed) {
+                return;
+            }
+
+            // 忽略已被替换的旧处理器
+            let actions = handle.state::<ActionShortcuts>();
+            let registered = actions.shortcuts.lock().unwrap().get(&action).copied();
+            if registered != Some(shortcut) {
+                log::warn!("Stale {:?} handler triggered", action);
+                return;
+            }
+
+            run_action(&handle, action);
+        })
+        .map_err(|e| format!("Failed to register hotkey: {:?}", e));
+
+    if let Err(e) = result {
+        let actions = app.state::<ActionShortcuts>();
+        actions.shortcuts.lock().map_err(|e| e.to_string())?.remove(&action);
+        return Err(e);
+    }
+
+    log::info!("{:?} hotkey registered: {}", action, name);
     Ok(())
 }
 
+// 设置（或清除）某个附加功能快捷键，并保存到配置
+pub fn set_action_hotkey(app: &AppHandle, action: HotkeyAction, config: Option<HotkeyConfig>) -> Result<(), String> {
+    // 先注销旧的快捷键
+    let previous = {
+        let actions = app.state::<ActionShortcuts>();
+        let mut shortcuts = actions.shortcuts.lock().map_err(|e| e.to_string())?;
+        shortcuts.remove(&action)
+    };
+    if let Some(shortcut) = previous {
+        if let Err(e) = app.global_shortcut().unregister(shortcut) {
+            log::warn!("Failed to unregister {:?} hotkey: {:?}", action, e);
+        }
+    }
+
+    if let Some(ref config) = config {
+        register_action_hotkey(app, action, config)?;
+    }
+
+    let mut hotkeys = get_action_hotkeys();
+    match config {
+        Some(config) => hotkeys.insert(action, config),
+        None => hotkeys.remove(&action),
+    };
+    crate::set_setting("action_hotkeys", &hotkeys)
+}
+
+fn run_action(app: &AppHandle, action: HotkeyAction) {
+    log::info!("Hotkey action triggered: {:?}", action);
+    match action {
+        HotkeyAction::ResetRecording => force_reset_recording_state(app),
+        HotkeyAction::CycleOutputMode => cycle_output_mode(app),
+        HotkeyAction::OpenSettings => open_main_window(app, "settings"),
+        HotkeyAction::OpenHistory => open_main_window(app, "history"),
+    }
+}
+
+// 显示并聚焦主窗口，通过 navigate 事件让前端切换到指定页面
+fn open_main_window(app: &AppHandle, page: &str) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+    let _ = app.emit("navigate", page);
+}
+
+// 切换到下一个输出模式，并用系统通知提示新模式
+fn cycle_output_mode(app: &AppHandle) {
+    let current = *app.state::<AppState>().output_mode.lock().unwrap();
+    let next = current.next();
+    if let Err(e) = crate::apply_output_mode(app, next) {
+        log::warn!("Failed to cycle output mode: {}", e);
+        return;
+    }
+
+    let label = match next {
+        crate::OutputMode::Keyboard => "Keyboard",
+        crate::OutputMode::Clipboard => "Clipboard",
+        crate::OutputMode::Accessibility => "Accessibility",
+    };
+    if let Err(e) = crate::notify::show_notification("Mouth High", &format!("Output mode: {}", label)) {
+        log::warn!("Failed to show notification: {}", e);
+    }
+}
+
 // 使用配置注册快捷键
 fn register_hotkey_with_config(app: &AppHandle, config: &HotkeyConfig) -> Result<(), String> {
     let (shortcut, name) = config_to_shortcut(config)?;
@@ -166,11 +412,15 @@ fn register_hotkey_with_config(app: &AppHandle, config: &HotkeyConfig) -> Result
     {
         let current = app.state::<CurrentShortcut>();
         let mut current_shortcut = current.shortcut.lock().map_err(|e| e.to_string())?;
-        *current_shortcut = Some(shortcut.clone());
+        *current_shortcut = Some(shortcut);
+    }
+    {
+        let recording = app.state::<RecordingShortcuts>();
+        recording.modes.lock().map_err(|e| e.to_string())?.insert(shortcut.id(), config.mode);
     }
 
     let handle = app.clone();
-    let shortcut_for_handler = shortcut.clone();
+    let shortcut_for_handler = shortcut;
 
     // on_shortcut() 同时完成：注册快捷键 + 绑定处理器
     app.global_shortcut()
@@ -189,57 +439,352 @@ fn register_hotkey_with_config(app: &AppHandle, config: &HotkeyConfig) -> Result
                 return;
             }
             
-            let recording_mode = {
-                let state = handle.state::<crate::AppState>();
-                let mode = *state.recording_mode.lock().unwrap();
-                mode
-            };
+            drop(current_shortcut);
 
-            match recording_mode {
-                crate::RecordingMode::Hold => {
-                    // Hold 模式：按住开始，松开停止
-                    match event.state {
-                        ShortcutState::Pressed => {
-                            log::info!("Hotkey pressed (Hold mode) - starting recording");
-                            start_recording(&handle);
-                        }
-                        ShortcutState::Released => {
-                            log::info!("Hotkey released (Hold mode) - stopping recording");
-                            stop_recording_and_process(&handle);
+            let mode = shortcut_mode(&handle, &shortcut_for_handler).flatten();
+            handle_trigger(&handle, event.state, mode, shortcut_for_handler.id());
+        })
+        .map_err(|e| format!("Failed to register hotkey: {:?}", e))?;
+
+    log::info!("Global hotkey registered: {}", name);
+    let _ = app.emit("hotkey-registered", name);
+    Ok(())
+}
+
+// Hold 模式下触发键是否处于按下状态，用于识别系统按键重复和缺失的 Pressed
+static TRIGGER_HELD: AtomicBool = AtomicBool::new(false);
+// 本次按住期间被忽略的重复 Pressed 次数，松开时汇总记录一次日志
+static REPEATED_PRESSES: AtomicUsize = AtomicUsize::new(0);
+// 开始本次 Hold 录音的触发键（Shortcut id，鼠标按键为 0），其他键的 Released 不会停止录音
+static HELD_TRIGGER: AtomicU32 = AtomicU32::new(0);
+// 松开防抖：等待期间有待执行的停止，再次按下会取消它
+static PENDING_RELEASE: AtomicBool = AtomicBool::new(false);
+static RELEASE_GENERATION: AtomicU64 = AtomicU64::new(0);
+// 按住快捷键时再按其他键，系统会对同一快捷键误报 Released，随后按键重复又发送 Pressed；
+// 默认防抖覆盖这段间隔，真正松开时停止录音会相应延后
+pub const DEFAULT_HOLD_RELEASE_DEBOUNCE_MS: u64 = 200;
+
+pub const DEFAULT_TOGGLE_COOLDOWN_MS: u64 = 300;
+
+// Toggle 模式的按键冷却：记录上一次生效的按下时间，冷却时间内的再次按下（按键抖动）被忽略
+struct ToggleCooldown {
+    last_accepted: Option<std::time::Instant>,
+}
+
+impl ToggleCooldown {
+    const fn new() -> Self {
+        ToggleCooldown { last_accepted: None }
+    }
+
+    // 只有冷却时间外的 Pressed 生效并记录时间；Released 和被忽略的按下不改变状态
+    fn accept(&mut self, event: ShortcutState, now: std::time::Instant, cooldown: std::time::Duration) -> bool {
+        if event != ShortcutState::Pressed {
+            return false;
+        }
+        if self.last_accepted.is_some_and(|previous| now.duration_since(previous) < cooldown) {
+            return false;
+        }
+        self.last_accepted = Some(now);
+        true
+    }
+}
+
+static TOGGLE_COOLDOWN: Mutex<ToggleCooldown> = Mutex::new(ToggleCooldown::new());
+
+fn accept_toggle_press(event: ShortcutState) -> bool {
+    let cooldown_ms = crate::get_setting::<Option<u64>>("toggle_cooldown_ms").unwrap_or(DEFAULT_TOGGLE_COOLDOWN_MS);
+    TOGGLE_COOLDOWN.lock().unwrap().accept(
+        event,
+        std::time::Instant::now(),
+        std::time::Duration::from_millis(cooldown_ms),
+    )
+}
+
+fn is_recording(app: &AppHandle) -> bool {
+    let state = app.state::<crate::AppState>();
+    let is_rec = *state.is_recording.lock().unwrap();
+    is_rec
+}
+
+// 当前录音使用的模式（由触发开始的快捷键决定）
+static SESSION_MODE: Mutex<Option<crate::RecordingMode>> = Mutex::new(None);
+
+// 当前录音会话的模式；尚未有录音时使用全局录音模式
+fn session_mode(app: &AppHandle) -> crate::RecordingMode {
+    if let Some(mode) = *SESSION_MODE.lock().unwrap() {
+        return mode;
+    }
+    let state = app.state::<crate::AppState>();
+    let mode = *state.recording_mode.lock().unwrap();
+    mode
+}
+
+// 处理录音触发（快捷键或鼠标按键），按触发键绑定的模式（或全局录音模式）开始/停止；
+// 录音进行中时沿用开始录音时的模式，避免不同模式的快捷键交替触发导致状态错乱。
+// trigger_id 标识触发键（Shortcut id，鼠标按键为 0）
+pub fn handle_trigger(app: &AppHandle, state: ShortcutState, mode: Option<crate::RecordingMode>, trigger_id: u32) {
+    let recording_mode = if is_recording(app) || TRIGGER_HELD.load(Ordering::SeqCst) {
+        session_mode(app)
+    } else {
+        mode.unwrap_or_else(|| {
+            let state = app.state::<crate::AppState>();
+            let mode = *state.recording_mode.lock().unwrap();
+            mode
+        })
+    };
+
+    match recording_mode {
+        crate::RecordingMode::Hold => {
+            // Hold 模式：按住开始，松开停止
+            match state {
+                ShortcutState::Pressed => {
+                    // 松开后很快又按下（按其他键时系统误报的 Released），取消待执行的停止
+                    if PENDING_RELEASE.swap(false, Ordering::SeqCst) {
+                        RELEASE_GENERATION.fetch_add(1, Ordering::SeqCst);
+                        TRIGGER_HELD.store(true, Ordering::SeqCst);
+                        log::info!("Hotkey re-pressed within release debounce, continuing recording");
+                        return;
+                    }
+                    // 系统按键重复会在按住期间不断发送 Pressed，只在首次按下时开始录音
+                    if TRIGGER_HELD.swap(true, Ordering::SeqCst) || is_recording(app) {
+                        if REPEATED_PRESSES.fetch_add(1, Ordering::SeqCst) == 0 {
+                            log::info!("Ignoring repeated hotkey press while recording");
                         }
+                        return;
                     }
+                    log::info!("Hotkey pressed (Hold mode) - starting recording");
+                    HELD_TRIGGER.store(trigger_id, Ordering::SeqCst);
+                    start_recording(app, recording_mode);
                 }
-                crate::RecordingMode::Toggle => {
-                    // Toggle 模式：按一下切换录音状态
-                    if matches!(event.state, ShortcutState::Pressed) {
-                        let is_recording = {
-                            let state = handle.state::<crate::AppState>();
-                            let is_rec = *state.is_recording.lock().unwrap();
-                            is_rec
-                        };
-                        
-                        if is_recording {
-                            log::info!("Hotkey pressed (Toggle mode) - stopping recording");
-                            stop_recording_and_process(&handle);
-                        } else {
-                            log::info!("Hotkey pressed (Toggle mode) - starting recording");
-                            start_recording(&handle);
+                ShortcutState::Released => {
+                    // 多个快捷键时，其他快捷键的 Released 不会停止录音；
+                    // 同一快捷键的误报 Released 由 release_hold 的防抖处理
+                    if TRIGGER_HELD.load(Ordering::SeqCst) && HELD_TRIGGER.load(Ordering::SeqCst) != trigger_id {
+                        log::info!("Ignoring release of a different hotkey while holding");
+                        return;
+                    }
+                    let was_held = TRIGGER_HELD.swap(false, Ordering::SeqCst);
+                    let repeats = REPEATED_PRESSES.swap(0, Ordering::SeqCst);
+                    if repeats > 1 {
+                        log::info!("Ignored {} repeated hotkey presses", repeats);
+                    }
+                    if !was_held {
+                        // 没有对应的 Pressed（可能被系统合并），仍在录音时照常停止以免状态不同步
+                        if !is_recording(app) {
+                            log::warn!("Hotkey released without a prior press, ignoring");
+                            return;
                         }
+                        log::warn!("Hotkey released without a prior press while recording - stopping");
                     }
+                    release_hold(app);
                 }
             }
+        }
+        crate::RecordingMode::Toggle => {
+            // Toggle 模式：只有 Pressed 切换录音状态，Released 一律忽略（按住不放也不会停止）
+            if matches!(state, ShortcutState::Pressed) {
+                // 按下-松开-按下的抖动或按住时的系统按键重复会在极短时间内再次触发，避免刚开始就停止
+                if !accept_toggle_press(state) {
+                    log::info!("Ignoring hotkey press within toggle cooldown");
+                    return;
+                }
+                if is_recording(app) {
+                    log::info!("Hotkey pressed (Toggle mode) - stopping recording");
+                    stop_recording_and_process(app);
+                } else {
+                    log::info!("Hotkey pressed (Toggle mode) - starting recording");
+                    start_recording(app, recording_mode);
+                }
+            }
+        }
+    }
+}
+
+// Hold 模式松开后停止录音；设置了防抖时间时延迟停止，期间再次按下会取消
+fn release_hold(app: &AppHandle) {
+    let debounce_ms = crate::get_setting::<Option<u64>>("hold_release_debounce_ms").unwrap_or(DEFAULT_HOLD_RELEASE_DEBOUNCE_MS);
+    if debounce_ms == 0 {
+        log::info!("Hotkey released (Hold mode) - stopping recording");
+        stop_recording_and_process(app);
+        return;
+    }
+
+    let generation = RELEASE_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    PENDING_RELEASE.store(true, Ordering::SeqCst);
+    let handle = app.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(debounce_ms));
+        if RELEASE_GENERATION.load(Ordering::SeqCst) == generation && PENDING_RELEASE.swap(false, Ordering::SeqCst) {
+            log::info!("Hotkey released (Hold mode) - stopping recording");
+            stop_recording_and_process(&handle);
+        }
+    });
+}
+
+// 显示录音条窗口，并按设置临时注册 Escape 取消；
+// take_focus 为 false 时窗口不可获得焦点，避免抢走 Hold 模式下键盘输入的目标
+fn show_recording_bar(app: &AppHandle, take_focus: bool) {
+    if let Some(window) = app.get_webview_window("recording-bar") {
+        // 按设置调整大小和背景透明度
+        let bar = crate::get_setting::<crate::RecordingBarSettings>("recording_bar").clamped();
+        if let Err(e) = window.set_size(tauri::LogicalSize::new(bar.width, bar.height)) {
+            log::warn!("Failed to resize recording bar: {}", e);
+        }
+        let _ = window.eval(format!(
+            "document.documentElement.style.setProperty('--recording-bar-opacity', '{}')",
+            bar.opacity
+        ));
+        let _ = window.set_always_on_top(bar.always_on_top);
+        let _ = window.set_visible_on_all_workspaces(bar.always_on_top);
+        if let Err(e) = window.set_ignore_cursor_events(bar.click_through) {
+            log::warn!("Failed to set recording bar click-through: {}", e);
+        }
+        let _ = window.set_focusable(take_focus);
+        center_on_monitor(app, &window);
+        let _ = window.show();
+        if take_focus {
+            let _ = window.set_focus();
+        }
+    }
+
+    if crate::get_setting::<bool>("escape_cancels_recording") {
+        if let Err(e) = register_escape_shortcut(app) {
+            log::warn!("Failed to register Escape cancel: {}", e);
+        }
+    }
+}
+
+// 录音条所在的显示器：设置指定的显示器 > 鼠标所在的显示器 > 主显示器
+fn recording_bar_monitor(app: &AppHandle) -> Option<tauri::Monitor> {
+    if let Some(name) = crate::get_setting::<Option<String>>("recording_bar_monitor") {
+        let found = app
+            .available_monitors()
+            .ok()
+            .and_then(|monitors| monitors.into_iter().find(|m| m.name() == Some(&name)));
+        match found {
+            Some(monitor) => return Some(monitor),
+            None => log::warn!("Recording bar monitor {} not connected, using cursor monitor", name),
+        }
+    }
+
+    app.cursor_position()
+        .ok()
+        .and_then(|cursor| app.monitor_from_point(cursor.x, cursor.y).ok().flatten())
+        .or_else(|| app.primary_monitor().ok().flatten())
+}
+
+// window.center() 总是以 Tauri 认定的主显示器为准，这里按显示器的物理坐标自行居中
+fn center_on_monitor(app: &AppHandle, window: &tauri::WebviewWindow) {
+    let (Some(monitor), Ok(size)) = (recording_bar_monitor(app), window.outer_size()) else {
+        let _ = window.center();
+        return;
+    };
+    let origin = monitor.position();
+    let area = monitor.size();
+    let x = origin.x + (area.width.saturating_sub(size.width) / 2) as i32;
+    let y = origin.y + (area.height.saturating_sub(size.height) / 2) as i32;
+    if let Err(e) = window.set_position(tauri::PhysicalPosition::new(x, y)) {
+        log::warn!("Failed to position recording bar: {}", e);
+        let _ = window.center();
+    }
+}
+
+// 隐藏录音条窗口，并注销临时的 Escape 快捷键，避免影响其他应用
+fn hide_recording_bar(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("recording-bar") {
+        let _ = window.hide();
+    }
+
+    unregister_escape_shortcut(app);
+}
+
+fn register_escape_shortcut(app: &AppHandle) -> Result<(), String> {
+    let escape = app.state::<EscapeShortcut>();
+    let mut registered = escape.registered.lock().map_err(|e| e.to_string())?;
+    if *registered {
+        return Ok(());
+    }
+
+    // 录音快捷键本身就是 Escape 时不再重复注册
+    let shortcut = Shortcut::new(None, Code::Escape);
+    {
+        let current = app.state::<CurrentShortcut>();
+        if *current.shortcut.lock().map_err(|e| e.to_string())? == Some(shortcut) || shortcut_mode(app, &shortcut).is_some() {
+            return Ok(());
+        }
+    }
+
+    let handle = app.clone();
+    app.global_shortcut()
+        .on_shortcut(shortcut, move |_app, _shortcut, event| {
+            if !matches!(event.state, ShortcutState::Pressed) {
+                return;
+            }
+
+            log::info!("Escape pressed while recording bar is shown - cancelling");
+            // 在新线程中取消，避免在快捷键回调内注销自身
+            let handle = handle.clone();
+            std::thread::spawn(move || stop_recording_and_discard(&handle));
         })
         .map_err(|e| format!("Failed to register hotkey: {:?}", e))?;
 
-    log::info!("Global hotkey registered: {}", name);
-    let _ = app.emit("hotkey-registered", name);
+    *registered = true;
     Ok(())
 }
 
+fn unregister_escape_shortcut(app: &AppHandle) {
+    let Some(escape) = app.try_state::<EscapeShortcut>() else {
+        return;
+    };
+    let mut registered = escape.registered.lock().unwrap();
+    if !*registered {
+        return;
+    }
+
+    if let Err(e) = app.global_shortcut().unregister(Shortcut::new(None, Code::Escape)) {
+        log::warn!("Failed to unregister Escape cancel: {:?}", e);
+    }
+    *registered = false;
+}
+
 // 更新快捷键（供前端调用）
 pub fn update_hotkey(app: &AppHandle, config: &HotkeyConfig) -> Result<(), String> {
     log::info!("Updating hotkey to: {:?}", config);
-    
+
+    register_all(app, config)?;
+
+    // 保存配置
+    crate::set_hotkey_config(config.clone())?;
+
+    log::info!("Hotkey successfully updated to: {}", config.to_display_string());
+    Ok(())
+}
+
+// hotkey-register-failed 事件负载
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct HotkeyRegisterError {
+    pub hotkey: String,
+    pub error: String,
+}
+
+// 按当前配置重新注册快捷键（不修改配置），用于辅助功能权限被撤销又重新授予后恢复
+pub fn reregister_hotkey(app: &AppHandle) -> Result<(), String> {
+    let config = crate::get_hotkey_config()?;
+    log::info!("Re-registering hotkey: {:?}", config);
+
+    if let Err(e) = register_all(app, &config) {
+        log::error!("Failed to re-register hotkey: {}", e);
+        let _ = app.emit("hotkey-register-failed", HotkeyRegisterError {
+            hotkey: config.to_display_string(),
+            error: e.clone(),
+        });
+        return Err(e);
+    }
+    Ok(())
+}
+
+// 注销所有快捷键后，按给定配置重新注册录音快捷键和附加功能快捷键
+fn register_all(app: &AppHandle, config: &HotkeyConfig) -> Result<(), String> {
     // 先获取并清空当前快捷键状态
     {
         let current = app.state::<CurrentShortcut>();
@@ -259,30 +804,77 @@ pub fn update_hotkey(app: &AppHandle, config: &HotkeyConfig) -> Result<(), Strin
     // 增加延迟确保系统完全释放快捷键
     std::thread::sleep(std::time::Duration::from_millis(300));
 
+    {
+        let actions = app.state::<ActionShortcuts>();
+        actions.shortcuts.lock().map_err(|e| e.to_string())?.clear();
+    }
+    {
+        let recording = app.state::<RecordingShortcuts>();
+        recording.modes.lock().map_err(|e| e.to_string())?.clear();
+    }
+    *app.state::<EscapeShortcut>().registered.lock().map_err(|e| e.to_string())? = false;
+
     // 重新注册
     log::info!("Registering new shortcut...");
     register_hotkey_with_config(app, config)?;
-
-    // 保存配置
-    crate::set_hotkey_config(config.clone())?;
-
-    log::info!("Hotkey successfully updated to: {}", config.to_display_string());
+    register_extra_hotkeys(app);
+    register_action_hotkeys(app);
     Ok(())
 }
 
-fn start_recording(app: &AppHandle) {
+fn start_recording(app: &AppHandle, mode: crate::RecordingMode) {
     let state = app.state::<AppState>();
     let recorder_state = app.state::<RecorderState>();
 
     // Check if already recording
+    let is_recording = *state.is_recording.lock().unwrap();
+    if is_recording {
+        match crate::get_setting::<crate::AlreadyRecordingPolicy>("already_recording_policy") {
+            crate::AlreadyRecordingPolicy::Ignore => {
+                log::warn!("Already recording");
+                return;
+            }
+            crate::AlreadyRecordingPolicy::RestartFresh => {
+                log::info!("Already recording - discarding and starting fresh");
+                stop_recording_and_discard(app);
+            }
+            crate::AlreadyRecordingPolicy::ForceStop => {
+                log::info!("Already recording - treating start as stop");
+                stop_recording_and_process(app);
+                return;
+            }
+        }
+    }
+
+    // ASR 服务尚未就绪时按策略拒绝录音，避免说完才发现无法转录
+    if !crate::sidecar::is_ready()
+        && crate::get_setting::<crate::SidecarNotReadyPolicy>("sidecar_not_ready_policy")
+            == crate::SidecarNotReadyPolicy::RejectImmediately
     {
-        let is_recording = state.is_recording.lock().unwrap();
-        if *is_recording {
-            log::warn!("Already recording");
-            return;
+        log::warn!("ASR service not ready, refusing to record");
+        crate::emit_error(app, "ASR service is still starting, please try again in a moment");
+        return;
+    }
+
+    // 当前应用在禁用列表中时不录音（如密码管理器）
+    let disabled_apps: Vec<String> = crate::get_setting("disabled_apps");
+    if !disabled_apps.is_empty() {
+        if let Some(bundle_id) = crate::focus::get_frontmost_app() {
+            if disabled_apps.contains(&bundle_id) {
+                log::info!("Dictation blocked in {}", bundle_id);
+                let _ = app.emit("dictation-blocked", bundle_id);
+                return;
+            }
         }
     }
 
+    // 前台应用全屏（如正在演示）时不录音，避免误按快捷键把文字输出到幻灯片上
+    if crate::get_setting::<bool>("suppress_in_fullscreen") && crate::focus::is_frontmost_fullscreen() {
+        log::info!("Dictation suppressed while a full-screen app is frontmost");
+        let _ = app.emit("suppressed-fullscreen", ());
+        return;
+    }
+
     // Start recording with app_handle for amplitude monitoring
     let result = {
         let recorder = recorder_state.recorder.lock().unwrap();
@@ -308,13 +900,11 @@ fn start_recording(app: &AppHandle) {
                 *is_recording = true;
             }
 
-            log::info!("Recording started (session {})", session_id);
+            log::info!("Recording started (session {}, {:?} mode)", session_id, mode);
 
-            // 获取录音模式
-            let recording_mode = {
-                let mode = *state.recording_mode.lock().unwrap();
-                mode
-            };
+            // 记录本次录音的模式，停止和输出时沿用
+            *SESSION_MODE.lock().unwrap() = Some(mode);
+            let recording_mode = mode;
 
             // Toggle 模式下，保存当前焦点应用，然后显示浮动波纹条窗口
             if recording_mode == crate::RecordingMode::Toggle {
@@ -325,19 +915,20 @@ fn start_recording(app: &AppHandle) {
                     log::info!("Saved previous app: {}", bundle_id);
                 }
 
-                if let Some(window) = app.get_webview_window("recording-bar") {
-                    let _ = window.center();
-                    let _ = window.show();
-                    let _ = window.set_focus();
-                }
+                show_recording_bar(app, true);
+            } else if crate::get_setting::<bool>("show_bar_in_hold") {
+                // Hold 模式下仅作为录音提示显示，不获取焦点
+                show_recording_bar(app, false);
             }
 
+            crate::tray::set_recording(app, true);
+            crate::feedback::play_start();
             let _ = app.emit("recording-started", ());
             log::info!("Recording started");
         }
         Err(e) => {
             log::error!("Failed to start recording: {}", e);
-            let _ = app.emit("error", format!("Failed to start recording: {}", e));
+            crate::emit_error(app, format!("Failed to start recording: {}", e));
         }
     }
 }
@@ -357,15 +948,28 @@ fn stop_recording_and_process(app: &AppHandle) {
         }
     }
 
-    // Stop recording and get audio file path
-    let audio_path = {
+    // 按当前转录后端的需要取得 WAV 文件或样本；仅内存模式下始终交付样本，录音不落盘
+    let handoff = if crate::get_setting::<bool>("memory_only") {
+        AudioHandoff::Samples
+    } else {
+        state
+            .sidecar_manager
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|manager| manager.handoff())
+            .unwrap_or(AudioHandoff::Wav)
+    };
+
+    // Stop recording and get audio
+    let recorded = {
         let recorder = recorder_state.recorder.lock().unwrap();
         if let Some(ref rec) = *recorder {
-            match rec.stop_recording() {
-                Ok(path) => Some(path),
+            match rec.stop_recording(handoff) {
+                Ok(audio) => Some(audio),
                 Err(e) => {
                     log::error!("Failed to stop recording: {}", e);
-                    let _ = app.emit("error", format!("Failed to stop recording: {}", e));
+                    crate::emit_error(app, format!("Failed to stop recording: {}", e));
                     None
                 }
             }
@@ -379,22 +983,29 @@ fn stop_recording_and_process(app: &AppHandle) {
         let mut is_recording = state.is_recording.lock().unwrap();
         *is_recording = false;
     }
+    crate::tray::set_recording(app, false);
 
-    // 获取录音模式
-    let recording_mode = {
-        let mode = *state.recording_mode.lock().unwrap();
-        mode
-    };
+    // 获取本次录音的模式
+    let recording_mode = session_mode(app);
 
     // Toggle 模式下：先隐藏录音条窗口，再恢复焦点
     if recording_mode == crate::RecordingMode::Toggle {
         // 1. 先隐藏录音条窗口（避免它干扰焦点）
-        if let Some(window) = app.get_webview_window("recording-bar") {
-            let _ = window.hide();
-        }
+        hide_recording_bar(app);
         
         // 2. 给系统时间处理隐藏窗口
         std::thread::sleep(std::time::Duration::from_millis(50));
+
+        // ByPointer：改为输出到鼠标指针下的应用，获取失败时沿用录音开始时的应用
+        if crate::get_setting::<crate::OutputTarget>("output_target") == crate::OutputTarget::ByPointer {
+            match crate::focus::get_app_under_pointer() {
+                Some(bundle_id) => {
+                    log::info!("Output target under pointer: {}", bundle_id);
+                    *state.previous_app.lock().unwrap() = Some(bundle_id);
+                }
+                None => log::warn!("No app under pointer, falling back to previous app"),
+            }
+        }
         
         // 3. 恢复焦点到之前的应用
         let prev = state.previous_app.lock().unwrap();
@@ -408,13 +1019,11 @@ fn stop_recording_and_process(app: &AppHandle) {
         }
     } else {
         // Hold 模式下只需隐藏窗口
-        if let Some(window) = app.get_webview_window("recording-bar") {
-            let _ = window.hide();
-        }
+        hide_recording_bar(app);
     }
 
     // Process audio if we have it
-    if let Some(path) = audio_path {
+    if let Some(audio) = recorded {
         // If this session was cancelled, discard and do not transcribe/output.
         let cancelled = {
             let cancelled = state.cancelled_sessions.lock().unwrap();
@@ -422,24 +1031,59 @@ fn stop_recording_and_process(app: &AppHandle) {
         };
         if cancelled {
             log::info!("Skip processing cancelled session {}", session_id);
-            if let Err(e) = std::fs::remove_file(&path) {
-                log::warn!("Failed to remove temp audio file: {}", e);
-            }
+            audio.discard();
             let _ = app.emit("recording-cancelled", ());
             return;
         }
 
+        crate::feedback::play_stop();
         let _ = app.emit("processing-started", ());
-        log::info!("Processing audio: {:?}", path);
+        if let Some(path) = audio.path() {
+            log::info!("Processing audio: {:?}", path);
+        }
 
-        let handle = app.clone();
-        std::thread::spawn(move || {
-            process_audio(&handle, path, session_id);
-        });
+        // 交给转录队列处理，录音线程不必等待 ASR
+        if let Err(e) = crate::queue::enqueue(app, audio, session_id) {
+            log::error!("Failed to queue transcription: {}", e);
+            crate::emit_error(app, e);
+        }
     }
 }
 
-fn process_audio(app: &AppHandle, audio_path: std::path::PathBuf, session_id: u64) {
+// 处理超时的默认值（秒），与 ASR 请求本身的超时无关
+pub const DEFAULT_PROCESSING_TIMEOUT_SECS: u64 = 60;
+
+// 处理录音并在所有退出路径上发送 processing-finished；超时未完成时发送 processing-timeout 供界面恢复
+pub fn process_audio(app: &AppHandle, audio: RecordedAudio, session_id: u64) {
+    let timeout = crate::get_setting::<Option<u64>>("processing_timeout_secs")
+        .unwrap_or(DEFAULT_PROCESSING_TIMEOUT_SECS);
+    let (done_tx, done_rx) = std::sync::mpsc::channel::<()>();
+    let handle = app.clone();
+    std::thread::spawn(move || {
+        if let Err(std::sync::mpsc::RecvTimeoutError::Timeout) =
+            done_rx.recv_timeout(std::time::Duration::from_secs(timeout))
+        {
+            log::warn!("Processing session {} exceeded {}s", session_id, timeout);
+            let _ = handle.emit("processing-timeout", session_id);
+        }
+    });
+
+    process_audio_inner(app, audio, session_id);
+
+    let _ = done_tx.send(());
+    let _ = app.emit("processing-finished", session_id);
+}
+
+// 模拟一次转录：不经过录音和 ASR，直接走转录后的完整流程（仅供界面开发和集成测试）
+#[cfg(debug_assertions)]
+pub fn inject_transcript(app: &AppHandle, text: String, output: bool) {
+    let _ = app.emit("processing-started", ());
+    let transcript = TranscriptResult { text, ..Default::default() };
+    handle_transcript(app, transcript, None, output);
+    let _ = app.emit("processing-finished", 0u64);
+}
+
+fn process_audio_inner(app: &AppHandle, audio: RecordedAudio, session_id: u64) {
     let state = app.state::<AppState>();
 
     // If user cancelled, skip all side-effects (ASR, stats, history, output).
@@ -447,18 +1091,20 @@ fn process_audio(app: &AppHandle, audio_path: std::path::PathBuf, session_id: u6
         let cancelled = state.cancelled_sessions.lock().unwrap();
         if cancelled.contains(&session_id) {
             log::info!("Drop cancelled session {} before ASR", session_id);
-            if let Err(e) = std::fs::remove_file(&audio_path) {
-                log::warn!("Failed to remove temp audio file: {}", e);
-            }
+            audio.discard();
             return;
         }
     }
 
     // Send to sidecar for ASR
+    let language = crate::resolve_language_hint(&state);
     let result = {
         let sidecar = state.sidecar_manager.lock().unwrap();
         if let Some(ref manager) = *sidecar {
-            manager.transcribe(&audio_path)
+            crate::sidecar::set_active_session(Some(session_id));
+            let result = manager.transcribe_audio(&audio, language.as_deref());
+            crate::sidecar::set_active_session(None);
+            result
         } else {
             Err("Sidecar not initialized".to_string())
         }
@@ -466,66 +1112,359 @@ fn process_audio(app: &AppHandle, audio_path: std::path::PathBuf, session_id: u6
 
     match result {
         Ok(transcript) => {
+            if let Some(ms) = transcript.elapsed_ms {
+                let _ = app.emit("transcription-timing", serde_json::json!({ "ms": ms }));
+            }
+
             // If user cancelled while ASR was running, drop the result.
             {
                 let cancelled = state.cancelled_sessions.lock().unwrap();
                 if cancelled.contains(&session_id) {
                     log::info!("Drop cancelled session {} after ASR", session_id);
-                    if let Err(e) = std::fs::remove_file(&audio_path) {
-                        log::warn!("Failed to remove temp audio file: {}", e);
-                    }
+                    audio.discard();
                     return;
                 }
             }
 
-            log::info!("Transcription: {}", transcript.text);
-
-            // Update usage stats
-            let char_count = transcript.text.chars().count();
-            if let Err(e) = crate::update_usage_stats(char_count) {
-                log::warn!("Failed to update usage stats: {}", e);
+            handle_transcript(app, transcript, audio.path(), true);
+        }
+        Err(e) => {
+            // 取消时 sidecar 以错误响应被打断的请求，不当作失败处理
+            if state.cancelled_sessions.lock().unwrap().contains(&session_id) {
+                log::info!("Drop cancelled session {} after ASR: {}", session_id, e);
+                audio.discard();
+                return;
             }
 
-            // Save to history
-            if let Err(e) = crate::add_history_item(&transcript.text) {
-                log::warn!("Failed to add history item: {}", e);
-            }
+            log::error!("Transcription failed: {}", e);
+            crate::emit_error(app, format!("Transcription failed: {}", e));
 
-            // 获取录音模式，如果是 Toggle 模式，再次确保焦点正确
-            let recording_mode = *state.recording_mode.lock().unwrap();
-            if recording_mode == crate::RecordingMode::Toggle {
-                // 再次恢复焦点到之前的应用（ASR 处理期间焦点可能改变）
-                let prev = state.previous_app.lock().unwrap();
-                if let Some(ref bundle_id) = *prev {
-                    log::info!("Re-restoring focus to: {}", bundle_id);
-                    let _ = crate::focus::activate_app(bundle_id);
-                    std::thread::sleep(std::time::Duration::from_millis(150));
+            // 保留录音和占位记录，稍后可通过 retranscribe 重试
+            if let Some(path) = audio.path().filter(|_| crate::get_setting::<bool>("on_error_keep_audio")) {
+                match crate::keep_failed_recording(path) {
+                    Ok(saved) => {
+                        let _ = app.emit("transcription-failed", saved.to_string_lossy().to_string());
+                    }
+                    Err(e) => log::warn!("Failed to keep failed recording: {}", e),
                 }
             }
+        }
+    }
 
-            // Output the text
-            let output_mode = {
-                let mode = state.output_mode.lock().unwrap();
-                *mode
-            };
+    // Clean up audio file (kept recordings have already been moved away)
+    audio.discard();
+}
+
+// 连续重复转录的默认判定窗口（秒）
+pub const DEFAULT_DEDUPE_WINDOW_SECS: u64 = 10;
+
+// 记录本次转录，开启 dedupe_consecutive 时判断是否与窗口内的上一次相同
+fn is_duplicate_transcript(state: &AppState, text: &str) -> bool {
+    let mut last = state.last_transcript.lock().unwrap();
+    let window = std::time::Duration::from_secs(
+        crate::get_setting::<Option<u64>>("dedupe_window_secs").unwrap_or(DEFAULT_DEDUPE_WINDOW_SECS),
+    );
+    let duplicate = crate::get_setting::<bool>("dedupe_consecutive")
+        && !text.is_empty()
+        && matches!(*last, Some((ref previous, at)) if previous == text && at.elapsed() < window);
+
+    if !duplicate {
+        *last = Some((text.to_string(), std::time::Instant::now()));
+    }
+    duplicate
+}
+
+// 录音时所在的应用：Toggle 模式下前台已是录音条，使用录音开始时保存的应用
+fn source_app(app: &AppHandle) -> Option<String> {
+    if session_mode(app) == crate::RecordingMode::Toggle {
+        app.state::<AppState>().previous_app.lock().unwrap().clone()
+    } else {
+        crate::focus::get_frontmost_app()
+    }
+}
+
+// 转录完成后的处理：文本处理、统计、历史、输出和事件；audio_path 为 None 时跳过时长和保留录音
+// 临时音频文件由调用方清理
+fn handle_transcript(app: &AppHandle, mut transcript: TranscriptResult, audio_path: Option<&std::path::Path>, output: bool) {
+    let state = app.state::<AppState>();
+
+    log::info!("Transcription: {}", transcript.text);
+
+    // 记住识别出的语言，供 Sticky 模式下一次录音使用；ASR 未返回时按文字构成推断
+    let detected = transcript
+        .language
+        .clone()
+        .or_else(|| crate::text::detect_script_language(&transcript.text));
+    if detected.is_some() {
+        *state.last_language.lock().unwrap() = detected.clone();
+    }
+
+    // ASR 服务不支持翻译时改用配置的翻译接口
+    if transcript.translation.is_none() {
+        transcript.translation = crate::translate::translate_via_endpoint(&transcript.text);
+    }
 
-            if let Err(e) = crate::input::output_text(&transcript.text, output_mode) {
-                log::error!("Failed to output text: {}", e);
-                let _ = app.emit("error", format!("Failed to output text: {}", e));
+    // 有译文时输出译文，原文按设置保存在历史记录中
+    let original_text = match transcript.translation.take().filter(|t| !t.trim().is_empty()) {
+        Some(translation) => Some(std::mem::replace(&mut transcript.text, translation)),
+        None => None,
+    };
+
+    let processed = crate::text::process_transcript(&transcript.text, detected.as_deref());
+    // 删除规则无效时报错，文字按未删除的内容继续处理
+    if let Some(e) = processed.strip_error {
+        log::warn!("Failed to apply strip patterns: {}", e);
+        crate::emit_error(app, format!("Failed to apply strip patterns: {}", e));
+    }
+    match processed.outcome {
+        // 只有语气词（清嗓子等被识别为 "um"）时不输出、不记录历史和统计
+        crate::text::TranscriptOutcome::FillerOnly => {
+            log::info!("Transcript contains only filler words, skipping");
+            let _ = app.emit("filler-only", &transcript.text);
+            return;
+        }
+        // 命令模式：匹配到命令短语时执行动作，不输出文字、不记录历史
+        crate::text::TranscriptOutcome::Command(action) => {
+            log::info!("Voice command: {:?}", action);
+            let last_output = state.last_output.lock().unwrap().take();
+            if let Err(e) = crate::voice_command::run_action(action, last_output.as_deref()) {
+                log::warn!("Failed to run voice command: {}", e);
+            }
+            let _ = app.emit("voice-command", action);
+            return;
+        }
+        crate::text::TranscriptOutcome::Text(text) => transcript.text = text,
+    }
+
+    // 与上一次转录相同且在时间窗口内时视为重复触发，不输出、不记录
+    if is_duplicate_transcript(&state, &transcript.text) {
+        log::info!("Skipping duplicate transcript");
+        let _ = app.emit("duplicate-skipped", &transcript.text);
+        return;
+    }
+
+    // Update usage stats
+    let char_count = transcript.text.chars().count();
+    if let Err(e) = crate::update_usage_stats(char_count) {
+        log::warn!("Failed to update usage stats: {}", e);
+    }
+    crate::check_quota(app, char_count);
+
+    // 续写模式：追加到最近一条历史记录，只输出新增部分
+    let continue_last = std::mem::take(&mut *state.continue_last.lock().unwrap());
+
+    // Markdown 辅助只在配置的应用中生效，按录音时所在的应用判断
+    let markdown = crate::get_setting::<crate::text::MarkdownAssist>("markdown_assist");
+    let markdown_enabled = markdown.enabled
+        && !markdown.apps.is_empty()
+        && source_app(app).is_some_and(|id| markdown.apps.contains(&id));
+
+    // 文本处理：大小写转换、Markdown 辅助和输出模板，历史记录按设置保存原文或处理结果
+    let crate::text::ProcessedText { output: processed, history: history_text } =
+        crate::text::format_transcript(&transcript.text, continue_last, markdown_enabled.then_some(&markdown));
+
+    let joiner = if continue_last {
+        match crate::append_to_last_history(&history_text) {
+            Ok(Some(joiner)) => Some(joiner),
+            Ok(None) => {
+                log::info!("No recent history item to continue, adding a new one");
+                None
             }
+            Err(e) => {
+                log::warn!("Failed to append to last history item: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
 
+    // Save to history
+    if joiner.is_none() {
+        let mut item = crate::HistoryItem::new(&history_text);
+        item.language = detected;
+        item.duration_ms = audio_path.and_then(|path| crate::audio::wav_duration_ms(path).ok());
+        item.segments = transcript.segments.clone();
+        if crate::get_setting::<bool>("auto_tag_app") {
+            let source_app = source_app(app);
+            if let Some(ref bundle_id) = source_app {
+                item.tags.push(bundle_id.clone());
+            }
+            item.source_app = source_app;
+        }
+        if crate::get_setting::<crate::TranslationSettings>("translation").keep_original {
+            item.original_text = original_text;
+        }
+        if let Some(path) = audio_path.filter(|_| crate::get_setting::<bool>("keep_recordings")) {
+            match crate::keep_recording(path, &item.id) {
+                Ok(kept) => item.audio_path = Some(kept.to_string_lossy().to_string()),
+                Err(e) => log::warn!("Failed to keep recording: {}", e),
+            }
+        }
+        if let Err(e) = crate::add_history_item(item) {
+            log::warn!("Failed to add history item: {}", e);
+        }
+    }
+
+    if crate::get_setting::<bool>("notify_on_transcript") {
+        crate::notify::notify_transcript(&processed);
+    }
+
+    // 不输出时（如模拟转录）只通知界面
+    if !output {
+        let _ = app.emit("transcript", &transcript);
+        return;
+    }
+
+    // 输出目标为指定窗口时先激活它，不再恢复录音前的应用
+    let window_targeted = activate_target_window(app);
+
+    // 获取录音模式，如果是 Toggle 模式，再次确保焦点正确
+    let recording_mode = session_mode(app);
+    if !window_targeted && recording_mode == crate::RecordingMode::Toggle {
+        // 再次恢复焦点到之前的应用（ASR 处理期间焦点可能改变）
+        let prev = state.previous_app.lock().unwrap();
+        if let Some(ref bundle_id) = *prev {
+            log::info!("Re-restoring focus to: {}", bundle_id);
+            let _ = crate::focus::activate_app(bundle_id);
+            std::thread::sleep(std::time::Duration::from_millis(150));
+        }
+    }
+
+    // Output the text
+    let output_mode = {
+        let mode = state.output_mode.lock().unwrap();
+        *mode
+    };
+
+    // Toggle 模式下粘贴前需确认焦点已回到录音前的应用
+    let expected_app = if window_targeted {
+        crate::focus::get_frontmost_app()
+    } else if recording_mode == crate::RecordingMode::Toggle {
+        state.previous_app.lock().unwrap().clone()
+    } else {
+        None
+    };
+
+    let output = format!("{}{}", joiner.unwrap_or_default(), processed);
+
+    // 焦点停留在本应用窗口时不输出，避免把文字打进设置页或录音条；文字仍复制到剪贴板
+    if is_self_focused(app) {
+        log::warn!("Own window is frontmost, refusing to type");
+        if let Err(e) = crate::input::copy_to_clipboard(&output) {
+            log::warn!("Failed to copy text: {}", e);
+        }
+        let _ = app.emit("self-focus-guard", &output);
+        let _ = app.emit("transcript", &transcript);
+        return;
+    }
+
+    // 目标应用在确认列表中时先发出 confirm-output，等待 approve_output / reject_output
+    let confirm_apps: Vec<String> = crate::get_setting("confirm_in_apps");
+    if !confirm_apps.is_empty() {
+        let target = expected_app.clone().or_else(crate::focus::get_frontmost_app);
+        if let Some(bundle_id) = target.filter(|id| confirm_apps.contains(id)) {
+            log::info!("Waiting for confirmation before output to {}", bundle_id);
+            let pending = PendingOutput { text: output, mode: output_mode, app: bundle_id };
+            let _ = app.emit("confirm-output", &pending);
+            if let Some(previous) = state.pending_output.lock().unwrap().replace(pending) {
+                log::warn!("Discarding unconfirmed output for {}", previous.app);
+            }
             let _ = app.emit("transcript", &transcript);
+            return;
+        }
+    }
+
+    deliver_output(app, &output, output_mode, expected_app);
+
+    let _ = app.emit("transcript", &transcript);
+}
+
+// 输出目标为 ByWindowTitle 时激活匹配的窗口；找不到时报错并按默认方式输出
+fn activate_target_window(app: &AppHandle) -> bool {
+    let crate::OutputTarget::ByWindowTitle(title) = crate::get_setting::<crate::OutputTarget>("output_target") else {
+        return false;
+    };
+
+    match crate::focus::activate_window_by_title(&title) {
+        Ok(()) => {
+            log::info!("Activated output window matching: {}", title);
+            std::thread::sleep(std::time::Duration::from_millis(150));
+            true
         }
         Err(e) => {
-            log::error!("Transcription failed: {}", e);
-            let _ = app.emit("error", format!("Transcription failed: {}", e));
+            log::warn!("Failed to activate output window: {}", e);
+            crate::emit_error(app, format!("Failed to activate output window: {}", e));
+            false
+        }
+    }
+}
+
+// 等待用户确认的输出
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct PendingOutput {
+    pub text: String,
+    #[serde(skip)]
+    pub mode: crate::OutputMode,
+    pub app: String,
+}
+
+fn deliver_output(app: &AppHandle, output: &str, mode: crate::OutputMode, expected_app: Option<String>) {
+    let state = app.state::<AppState>();
+    match crate::input::output_text(output, mode, expected_app.as_deref()) {
+        Ok(crate::input::OutputOutcome::Delivered) => {
+            *state.last_output.lock().unwrap() = Some(output.to_string());
+        }
+        Ok(crate::input::OutputOutcome::CopiedOnly) => {
+            let _ = app.emit("paste-skipped", expected_app);
+        }
+        Err(e) => {
+            log::error!("Failed to output text: {}", e);
+            crate::emit_error(app, format!("Failed to output text: {}", e));
         }
     }
+}
+
+// 确认输出：切回目标应用后输出等待中的文字
+pub fn approve_output(app: &AppHandle) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let pending = state
+        .pending_output
+        .lock()
+        .map_err(|e| e.to_string())?
+        .take()
+        .ok_or("No output waiting for confirmation")?;
+
+    log::info!("Output approved for {}", pending.app);
+    if let Err(e) = crate::focus::activate_app(&pending.app) {
+        log::warn!("Failed to restore focus: {}", e);
+    }
+    std::thread::sleep(std::time::Duration::from_millis(150));
+
+    deliver_output(app, &pending.text, pending.mode, Some(pending.app));
+    Ok(())
+}
+
+// 拒绝输出：丢弃等待中的文字（历史记录中仍保留）
+pub fn reject_output(app: &AppHandle) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let pending = state
+        .pending_output
+        .lock()
+        .map_err(|e| e.to_string())?
+        .take()
+        .ok_or("No output waiting for confirmation")?;
+
+    log::info!("Output rejected for {}", pending.app);
+    Ok(())
+}
 
-    // Clean up audio file
-    if let Err(e) = std::fs::remove_file(&audio_path) {
-        log::warn!("Failed to remove temp audio file: {}", e);
+// 当前前台应用是否为本应用（可通过 self_focus_guard 设置关闭该检查）
+fn is_self_focused(app: &AppHandle) -> bool {
+    if !crate::get_setting::<Option<bool>>("self_focus_guard").unwrap_or(true) {
+        return false;
     }
+    crate::focus::get_frontmost_app().as_deref() == Some(app.config().identifier.as_str())
 }
 
 // 公共函数：停止录音（供前端调用）
@@ -562,9 +1501,7 @@ fn stop_recording_and_discard(app: &AppHandle) {
             // Marking this session as cancelled ensures `process_audio()` will drop results.
             log::info!("Cancel requested while not recording (session {})", session_id);
 
-            if let Some(window) = app.get_webview_window("recording-bar") {
-                let _ = window.hide();
-            }
+            hide_recording_bar(app);
 
             std::thread::sleep(std::time::Duration::from_millis(50));
 
@@ -580,15 +1517,15 @@ fn stop_recording_and_discard(app: &AppHandle) {
         }
     }
 
-    // Stop recording and get audio file path
-    let audio_path = {
+    // 丢弃的录音不需要写入文件
+    let recorded = {
         let recorder = recorder_state.recorder.lock().unwrap();
         if let Some(ref rec) = *recorder {
-            match rec.stop_recording() {
-                Ok(path) => Some(path),
+            match rec.stop_recording(AudioHandoff::Samples) {
+                Ok(audio) => Some(audio),
                 Err(e) => {
                     log::error!("Failed to stop recording: {}", e);
-                    let _ = app.emit("error", format!("Failed to stop recording: {}", e));
+                    crate::emit_error(app, format!("Failed to stop recording: {}", e));
                     None
                 }
             }
@@ -602,19 +1539,15 @@ fn stop_recording_and_discard(app: &AppHandle) {
         let mut is_recording = state.is_recording.lock().unwrap();
         *is_recording = false;
     }
+    crate::tray::set_recording(app, false);
 
-    // 获取录音模式
-    let recording_mode = {
-        let mode = *state.recording_mode.lock().unwrap();
-        mode
-    };
+    // 获取本次录音的模式
+    let recording_mode = session_mode(app);
 
     // Toggle 模式下：先隐藏录音条窗口，再恢复焦点
     if recording_mode == crate::RecordingMode::Toggle {
         // 1. 先隐藏录音条窗口（避免它干扰焦点）
-        if let Some(window) = app.get_webview_window("recording-bar") {
-            let _ = window.hide();
-        }
+        hide_recording_bar(app);
 
         // 2. 给系统时间处理隐藏窗口
         std::thread::sleep(std::time::Duration::from_millis(50));
@@ -630,24 +1563,151 @@ fn stop_recording_and_discard(app: &AppHandle) {
         }
     } else {
         // Hold 模式下只需隐藏窗口
-        if let Some(window) = app.get_webview_window("recording-bar") {
-            let _ = window.hide();
-        }
+        hide_recording_bar(app);
     }
 
-    // Clean up audio file (discard)
-    if let Some(path) = audio_path {
-        if let Err(e) = std::fs::remove_file(&path) {
-            log::warn!("Failed to remove temp audio file: {}", e);
-        }
+    // 宽限期内保留录音，可通过 undo_cancel 恢复；宽限期为 0 时直接丢弃
+    if let Some(audio) = recorded {
+        hold_cancelled_audio(audio, session_id);
     }
 
     let _ = app.emit("recording-cancelled", ());
     log::info!("Recording cancelled (discarded, session {})", session_id);
 }
 
+// 取消后保留录音的默认宽限期（毫秒）
+pub const DEFAULT_CANCEL_GRACE_MS: u64 = 5000;
+
+// 最近一次取消的录音（会话 id 和样本），宽限期结束后释放
+static CANCELLED_AUDIO: Mutex<Option<(u64, RecordedAudio)>> = Mutex::new(None);
+
+fn hold_cancelled_audio(audio: RecordedAudio, session_id: u64) {
+    let grace_ms = crate::get_setting::<Option<u64>>("cancel_grace_ms").unwrap_or(DEFAULT_CANCEL_GRACE_MS);
+    if grace_ms == 0 {
+        audio.discard();
+        return;
+    }
+
+    if let Some((_, previous)) = CANCELLED_AUDIO.lock().unwrap().replace((session_id, audio)) {
+        previous.discard();
+    }
+
+    std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(grace_ms));
+        let mut held = CANCELLED_AUDIO.lock().unwrap();
+        if matches!(*held, Some((id, _)) if id == session_id) {
+            if let Some((_, audio)) = held.take() {
+                audio.discard();
+                log::info!("Cancel grace period expired (session {})", session_id);
+            }
+        }
+    });
+}
+
+// 恢复宽限期内被取消的录音，并照常转录和输出
+pub fn undo_cancel(app: &AppHandle) -> Result<(), String> {
+    let (session_id, audio) = CANCELLED_AUDIO
+        .lock()
+        .map_err(|e| e.to_string())?
+        .take()
+        .ok_or_else(|| "No cancelled recording to restore".to_string())?;
+
+    let state = app.state::<AppState>();
+    state.cancelled_sessions.lock().map_err(|e| e.to_string())?.remove(&session_id);
+    log::info!("Restoring cancelled recording (session {})", session_id);
+
+    crate::feedback::play_stop();
+    let _ = app.emit("processing-started", ());
+    crate::queue::enqueue(app, audio, session_id).map(|_| ())
+}
+
 // 公共函数：取消录音（供前端调用，不转录/不输出）
 pub fn cancel_recording_manually(app: &AppHandle) -> Result<(), String> {
     stop_recording_and_discard(app);
     Ok(())
 }
+
+// 强制重置录音状态：用于流出错等导致 is_recording 卡住的情况
+pub fn force_reset_recording_state(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let recorder_state = app.state::<RecorderState>();
+
+    log::warn!("Force resetting recording state");
+    TRIGGER_HELD.store(false, Ordering::SeqCst);
+    REPEATED_PRESSES.store(0, Ordering::SeqCst);
+    PENDING_RELEASE.store(false, Ordering::SeqCst);
+
+    // 丢弃当前会话，避免残留的转录结果被输出
+    {
+        let session_id = *state.recording_session.lock().unwrap();
+        state.cancelled_sessions.lock().unwrap().insert(session_id);
+    }
+
+    // 停止可能仍在运行的音频流
+    {
+        let recorder = recorder_state.recorder.lock().unwrap();
+        if let Some(ref rec) = *recorder {
+            if let Ok(audio) = rec.stop_recording(AudioHandoff::Samples) {
+                audio.discard();
+            }
+        }
+    }
+
+    *state.is_recording.lock().unwrap() = false;
+    *state.previous_app.lock().unwrap() = None;
+    crate::tray::set_recording(app, false);
+
+    hide_recording_bar(app);
+
+    let _ = app.emit("recording-reset", ());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    const COOLDOWN: Duration = Duration::from_millis(300);
+
+    fn at(start: Instant, ms: u64) -> Instant {
+        start + Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn bounce_within_cooldown_is_ignored() {
+        let start = Instant::now();
+        let mut cooldown = ToggleCooldown::new();
+        assert!(cooldown.accept(ShortcutState::Pressed, start, COOLDOWN));
+        assert!(!cooldown.accept(ShortcutState::Released, at(start, 40), COOLDOWN));
+        assert!(!cooldown.accept(ShortcutState::Pressed, at(start, 80), COOLDOWN));
+    }
+
+    #[test]
+    fn press_after_cooldown_is_accepted() {
+        let start = Instant::now();
+        let mut cooldown = ToggleCooldown::new();
+        assert!(cooldown.accept(ShortcutState::Pressed, start, COOLDOWN));
+        assert!(!cooldown.accept(ShortcutState::Released, at(start, 100), COOLDOWN));
+        assert!(cooldown.accept(ShortcutState::Pressed, at(start, 400), COOLDOWN));
+        assert!(!cooldown.accept(ShortcutState::Released, at(start, 450), COOLDOWN));
+        assert!(!cooldown.accept(ShortcutState::Pressed, at(start, 500), COOLDOWN));
+    }
+
+    #[test]
+    fn ignored_presses_do_not_extend_cooldown() {
+        let start = Instant::now();
+        let mut cooldown = ToggleCooldown::new();
+        assert!(cooldown.accept(ShortcutState::Pressed, start, COOLDOWN));
+        assert!(!cooldown.accept(ShortcutState::Pressed, at(start, 250), COOLDOWN));
+        assert!(cooldown.accept(ShortcutState::Pressed, at(start, 300), COOLDOWN));
+    }
+
+    #[test]
+    fn zero_cooldown_accepts_every_press() {
+        let start = Instant::now();
+        let mut cooldown = ToggleCooldown::new();
+        assert!(cooldown.accept(ShortcutState::Pressed, start, Duration::ZERO));
+        assert!(cooldown.accept(ShortcutState::Pressed, start, Duration::ZERO));
+        assert!(!cooldown.accept(ShortcutState::Released, start, Duration::ZERO));
+    }
+}
@@ -4,35 +4,338 @@ use enigo::{Enigo, Keyboard, Settings};
 use std::thread;
 use std::time::Duration;
 
-pub fn output_text(text: &str, mode: OutputMode) -> Result<(), String> {
+// 等待目标应用获得焦点的最长时间
+const FOCUS_WAIT_TIMEOUT: Duration = Duration::from_millis(1500);
+const FOCUS_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// 剪贴板模式粘贴前的固定等待
+const PASTE_DELAY: Duration = Duration::from_millis(100);
+// 读回剪贴板确认写入的最长时间和轮询间隔
+const CLIPBOARD_VERIFY_TIMEOUT: Duration = Duration::from_millis(500);
+const CLIPBOARD_VERIFY_INTERVAL: Duration = Duration::from_millis(5);
+
+// 剪贴板模式下写入剪贴板后何时发送粘贴按键
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PasteTiming {
+    #[default]
+    Delay,   // 固定等待 100ms
+    Verify,  // 读回剪贴板，内容一致后立即粘贴
+}
+
+// 剪贴板模式复制后是否自动粘贴，按操作系统分别覆盖默认值；
+// 配置同步到其他系统时各自生效，不需要切换机器后重新设置
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq, Default)]
+#[serde(default)]
+pub struct AutoPasteOverrides {
+    pub macos: Option<bool>,
+    pub windows: Option<bool>,
+    pub linux: Option<bool>,
+}
+
+impl AutoPasteOverrides {
+    fn for_current_os(&self) -> Option<bool> {
+        if cfg!(target_os = "macos") {
+            self.macos
+        } else if cfg!(target_os = "windows") {
+            self.windows
+        } else {
+            self.linux
+        }
+    }
+}
+
+// Linux 上 Wayland/X11 的模拟 Ctrl+V 不稳定，默认只复制
+pub fn default_auto_paste() -> bool {
+    !cfg!(target_os = "linux")
+}
+
+// 当前系统实际生效的值：用户覆盖优先，否则使用系统默认
+pub fn auto_paste_enabled() -> bool {
+    crate::get_setting::<AutoPasteOverrides>("clipboard_auto_paste")
+        .for_current_os()
+        .unwrap_or_else(default_auto_paste)
+}
+
+// 输出结果
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputOutcome {
+    Delivered,   // 已输入/粘贴到目标应用
+    CopiedOnly,  // 焦点未恢复，仅复制到剪贴板，未粘贴
+}
+
+// expected_app: 粘贴前需要处于前台的应用（Toggle 模式下为录音前的应用）
+pub fn output_text(text: &str, mode: OutputMode, expected_app: Option<&str>) -> Result<OutputOutcome, String> {
     match mode {
-        OutputMode::Keyboard => simulate_keyboard_input(text),
-        OutputMode::Clipboard => copy_to_clipboard_and_paste(text),
+        OutputMode::Keyboard if exceeds_keyboard_limit(text) => {
+            // 长文本逐字输入又慢又容易出错，改用剪贴板粘贴
+            log::info!("Text longer than keyboard_max_length, pasting via clipboard instead");
+            if crate::get_setting::<bool>("keyboard_fallback_notify") {
+                if let Err(e) = crate::notify::show_notification("Mouth High", "Long text pasted via clipboard") {
+                    log::warn!("Failed to show notification: {}", e);
+                }
+            }
+            copy_to_clipboard_and_paste(text, expected_app)
+        }
+        OutputMode::Keyboard => simulate_keyboard_input(text).map(|_| OutputOutcome::Delivered),
+        OutputMode::Clipboard => copy_to_clipboard_and_paste(text, expected_app),
+        OutputMode::Accessibility => match insert_via_accessibility(text) {
+            Ok(()) => Ok(OutputOutcome::Delivered),
+            Err(e) => {
+                // 焦点元素不支持直接写入时退回键盘模拟
+                log::warn!("Accessibility insert failed, falling back to keyboard: {}", e);
+                simulate_keyboard_input(text).map(|_| OutputOutcome::Delivered)
+            }
+        },
+    }
+}
+
+// 键盘模式下的最大字符数，未设置时不限制
+fn exceeds_keyboard_limit(text: &str) -> bool {
+    crate::get_setting::<Option<usize>>("keyboard_max_length")
+        .is_some_and(|max| text.chars().count() > max)
+}
+
+// 轮询前台应用，直到与期望的应用一致或超时
+fn wait_for_focus(bundle_id: &str) -> bool {
+    let start = std::time::Instant::now();
+    loop {
+        if crate::focus::get_frontmost_app().as_deref() == Some(bundle_id) {
+            return true;
+        }
+        if start.elapsed() >= FOCUS_WAIT_TIMEOUT {
+            return false;
+        }
+        thread::sleep(FOCUS_POLL_INTERVAL);
+    }
+}
+
+// 键盘模拟输入的实现方式
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyboardBackend {
+    #[default]
+    Enigo,   // Enigo text()
+    Native,  // macOS CGEvent / Windows SendInput 直接发送 Unicode 字符串
+}
+
+// 将文本输入到当前焦点应用
+pub trait TextInjector {
+    fn inject(&self, text: &str) -> Result<(), String>;
+}
+
+pub struct EnigoInjector;
+
+impl TextInjector for EnigoInjector {
+    fn inject(&self, text: &str) -> Result<(), String> {
+        let mut enigo = Enigo::new(&Settings::default())
+            .map_err(|e| format!("Failed to create Enigo instance: {}", e))?;
+
+        // 短暂等待 Enigo 准备好
+        thread::sleep(Duration::from_millis(50));
+
+        enigo
+            .text(text)
+            .map_err(|e| format!("Failed to type text: {}", e))
+    }
+}
+
+pub struct NativeInjector;
+
+#[cfg(target_os = "macos")]
+impl TextInjector for NativeInjector {
+    fn inject(&self, text: &str) -> Result<(), String> {
+        use std::ffi::c_void;
+
+        #[link(name = "CoreGraphics", kind = "framework")]
+        extern "C" {
+            fn CGEventCreateKeyboardEvent(source: *const c_void, keycode: u16, key_down: bool) -> *mut c_void;
+            fn CGEventKeyboardSetUnicodeString(event: *mut c_void, length: usize, string: *const u16);
+            fn CGEventPost(tap: u32, event: *mut c_void);
+        }
+        #[link(name = "CoreFoundation", kind = "framework")]
+        extern "C" {
+            fn CFRelease(cf: *const c_void);
+        }
+        const HID_EVENT_TAP: u32 = 0;
+        // 单个事件携带的 UTF-16 长度有限，分块发送
+        const CHUNK_LEN: usize = 20;
+
+        let utf16: Vec<u16> = text.encode_utf16().collect();
+        for chunk in utf16.chunks(CHUNK_LEN) {
+            for key_down in [true, false] {
+                unsafe {
+                    let event = CGEventCreateKeyboardEvent(std::ptr::null(), 0, key_down);
+                    if event.is_null() {
+                        return Err("Failed to create keyboard event".to_string());
+                    }
+                    CGEventKeyboardSetUnicodeString(event, chunk.len(), chunk.as_ptr());
+                    CGEventPost(HID_EVENT_TAP, event);
+                    CFRelease(event);
+                }
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl TextInjector for NativeInjector {
+    fn inject(&self, text: &str) -> Result<(), String> {
+        #[repr(C)]
+        struct KeybdInput {
+            vk: u16,
+            scan: u16,
+            flags: u32,
+            time: u32,
+            extra_info: usize,
+        }
+        // INPUT 结构：联合体按最大成员 MOUSEINPUT 的大小补齐
+        #[repr(C)]
+        struct Input {
+            kind: u32,
+            ki: KeybdInput,
+            _pad: [u8; 8],
+        }
+        #[link(name = "user32")]
+        extern "system" {
+            fn SendInput(count: u32, inputs: *const Input, size: i32) -> u32;
+        }
+        const INPUT_KEYBOARD: u32 = 1;
+        const KEYEVENTF_KEYUP: u32 = 0x0002;
+        const KEYEVENTF_UNICODE: u32 = 0x0004;
+
+        let inputs: Vec<Input> = text
+            .encode_utf16()
+            .flat_map(|unit| {
+                [0, KEYEVENTF_KEYUP].map(|up| Input {
+                    kind: INPUT_KEYBOARD,
+                    ki: KeybdInput { vk: 0, scan: unit, flags: KEYEVENTF_UNICODE | up, time: 0, extra_info: 0 },
+                    _pad: [0; 8],
+                })
+            })
+            .collect();
+
+        let sent = unsafe { SendInput(inputs.len() as u32, inputs.as_ptr(), std::mem::size_of::<Input>() as i32) };
+        if sent as usize != inputs.len() {
+            return Err(format!("SendInput sent {} of {} events", sent, inputs.len()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+impl TextInjector for NativeInjector {
+    fn inject(&self, text: &str) -> Result<(), String> {
+        log::warn!("Native keyboard backend not supported on this platform, using Enigo");
+        EnigoInjector.inject(text)
+    }
+}
+
+pub fn injector(backend: KeyboardBackend) -> Box<dyn TextInjector> {
+    match backend {
+        KeyboardBackend::Enigo => Box::new(EnigoInjector),
+        KeyboardBackend::Native => Box::new(NativeInjector),
+    }
+}
+
+// 通过辅助功能 API 将文字写入焦点元素的选中区域（无选中时即插入到光标处），不发送按键
+#[cfg(target_os = "macos")]
+fn insert_via_accessibility(text: &str) -> Result<(), String> {
+    use cocoa::base::{id, nil};
+    use cocoa::foundation::NSString;
+    use std::ffi::c_void;
+
+    #[link(name = "ApplicationServices", kind = "framework")]
+    extern "C" {
+        fn AXUIElementCreateSystemWide() -> *mut c_void;
+        fn AXUIElementCopyAttributeValue(element: *mut c_void, attribute: *const c_void, value: *mut *mut c_void) -> i32;
+        fn AXUIElementIsAttributeSettable(element: *mut c_void, attribute: *const c_void, settable: *mut u8) -> i32;
+        fn AXUIElementSetAttributeValue(element: *mut c_void, attribute: *const c_void, value: *const c_void) -> i32;
+    }
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        fn CFRelease(cf: *const c_void);
+    }
+    const AX_SUCCESS: i32 = 0;
+
+    unsafe {
+        // NSString 与 CFString 可直接互换
+        let focused_attr: id = NSString::alloc(nil).init_str("AXFocusedUIElement");
+        let selected_attr: id = NSString::alloc(nil).init_str("AXSelectedText");
+        let value: id = NSString::alloc(nil).init_str(text);
+        let system = AXUIElementCreateSystemWide();
+
+        let mut focused: *mut c_void = std::ptr::null_mut();
+        let result = if AXUIElementCopyAttributeValue(system, focused_attr as *const c_void, &mut focused) != AX_SUCCESS
+            || focused.is_null()
+        {
+            Err("No focused element".to_string())
+        } else {
+            let mut settable: u8 = 0;
+            let result = if AXUIElementIsAttributeSettable(focused, selected_attr as *const c_void, &mut settable) != AX_SUCCESS
+                || settable == 0
+            {
+                Err("Focused element does not support text insertion".to_string())
+            } else {
+                match AXUIElementSetAttributeValue(focused, selected_attr as *const c_void, value as *const c_void) {
+                    AX_SUCCESS => Ok(()),
+                    code => Err(format!("Failed to set focused element text (AXError {})", code)),
+                }
+            };
+            CFRelease(focused);
+            result
+        };
+
+        CFRelease(system);
+        CFRelease(value as *const c_void);
+        CFRelease(selected_attr as *const c_void);
+        CFRelease(focused_attr as *const c_void);
+
+        if result.is_ok() {
+            log::info!("Inserted {} characters via accessibility API", text.chars().count());
+        }
+        result
     }
 }
 
+#[cfg(not(target_os = "macos"))]
+fn insert_via_accessibility(_text: &str) -> Result<(), String> {
+    Err("Accessibility output is only supported on macOS".to_string())
+}
+
 fn simulate_keyboard_input(text: &str) -> Result<(), String> {
     // 已经通过 focus::activate_app 恢复了焦点，只需要短暂等待系统响应
     thread::sleep(Duration::from_millis(100));
-    
-    let mut enigo = Enigo::new(&Settings::default())
-        .map_err(|e| format!("Failed to create Enigo instance: {}", e))?;
 
-    // 短暂等待 Enigo 准备好
-    thread::sleep(Duration::from_millis(50));
+    let backend: KeyboardBackend = crate::get_setting("keyboard_backend");
+    injector(backend).inject(text)?;
 
-    // Type the text
-    enigo
-        .text(text)
-        .map_err(|e| format!("Failed to type text: {}", e))?;
+    log::info!("Typed {} characters via keyboard simulation ({:?})", text.len(), backend);
 
-    log::info!("Typed {} characters via keyboard simulation", text.len());
+    Ok(())
+}
 
+// 连续按下某个按键若干次（命令模式使用）
+pub fn press_key(key: enigo::Key, times: usize) -> Result<(), String> {
+    thread::sleep(Duration::from_millis(100));
+
+    let mut enigo = Enigo::new(&Settings::default())
+        .map_err(|e| format!("Failed to create Enigo instance: {}", e))?;
+
+    for _ in 0..times {
+        enigo
+            .key(key, enigo::Direction::Click)
+            .map_err(|e| format!("Failed to press {:?}: {}", key, e))?;
+    }
+
+    log::info!("Pressed {:?} {} times", key, times);
     Ok(())
 }
 
-fn copy_to_clipboard_and_paste(text: &str) -> Result<(), String> {
-    // Copy to clipboard
+// 仅复制到剪贴板，不发送粘贴按键
+pub fn copy_to_clipboard(text: &str) -> Result<(), String> {
     let mut clipboard =
         Clipboard::new().map_err(|e| format!("Failed to access clipboard: {}", e))?;
 
@@ -41,26 +344,134 @@ fn copy_to_clipboard_and_paste(text: &str) -> Result<(), String> {
         .map_err(|e| format!("Failed to set clipboard text: {}", e))?;
 
     log::info!("Copied {} characters to clipboard", text.len());
+    Ok(())
+}
+
+// 轮询读回剪贴板，直到内容与写入的文本一致或超时
+fn wait_for_clipboard(text: &str) -> bool {
+    let Ok(mut clipboard) = Clipboard::new() else {
+        return false;
+    };
+    let start = std::time::Instant::now();
+    loop {
+        if clipboard.get_text().is_ok_and(|current| current == text) {
+            return true;
+        }
+        if start.elapsed() >= CLIPBOARD_VERIFY_TIMEOUT {
+            return false;
+        }
+        thread::sleep(CLIPBOARD_VERIFY_INTERVAL);
+    }
+}
+
+fn copy_to_clipboard_and_paste(text: &str, expected_app: Option<&str>) -> Result<OutputOutcome, String> {
+    // Copy to clipboard
+    copy_to_clipboard(text)?;
+
+    // "复制 + 输入"不发送粘贴按键，用户开启后优先于自动粘贴开关
+    let type_fallback = crate::get_setting::<bool>("clipboard_paste_fallback");
+    if !type_fallback && !auto_paste_enabled() {
+        log::info!("Auto-paste disabled on this OS, text left on clipboard");
+        return Ok(OutputOutcome::CopiedOnly);
+    }
+
+    // 焦点未回到目标应用时不粘贴，避免粘贴到本应用窗口或其他位置
+    if let Some(bundle_id) = expected_app {
+        if !wait_for_focus(bundle_id) {
+            log::warn!("Focus did not return to {}, skipping paste", bundle_id);
+            return Ok(OutputOutcome::CopiedOnly);
+        }
+    }
+
+    // 部分应用会拦截模拟的粘贴按键：开启后复制的同时改为逐字输入，剪贴板里仍保留文本
+    if type_fallback {
+        simulate_keyboard_input(text)?;
+        log::info!("Copied to clipboard and typed text (clipboard_paste_fallback)");
+        return Ok(OutputOutcome::Delivered);
+    }
 
     // Optionally paste (Cmd+V on macOS)
     let mut enigo = Enigo::new(&Settings::default())
         .map_err(|e| format!("Failed to create Enigo instance: {}", e))?;
 
-    // Small delay
-    thread::sleep(Duration::from_millis(100));
+    match crate::get_setting::<PasteTiming>("paste_timing") {
+        PasteTiming::Delay => thread::sleep(PASTE_DELAY),
+        PasteTiming::Verify => {
+            if !wait_for_clipboard(text) {
+                log::warn!("Clipboard readback did not match within {:?}, pasting anyway", CLIPBOARD_VERIFY_TIMEOUT);
+            }
+        }
+    }
 
     // Press Cmd+V
-    enigo
-        .key(enigo::Key::Meta, enigo::Direction::Press)
-        .map_err(|e| format!("Failed to press Meta key: {}", e))?;
-    enigo
-        .key(enigo::Key::Unicode('v'), enigo::Direction::Click)
-        .map_err(|e| format!("Failed to press V key: {}", e))?;
-    enigo
-        .key(enigo::Key::Meta, enigo::Direction::Release)
-        .map_err(|e| format!("Failed to release Meta key: {}", e))?;
+    send_paste_shortcut(&mut enigo)?;
 
     log::info!("Pasted from clipboard");
 
-    Ok(())
+    Ok(OutputOutcome::Delivered)
+}
+
+// 按键发送接口，粘贴快捷键的按下/释放顺序与具体的键盘模拟实现分开
+pub trait KeySender {
+    fn send_key(&mut self, key: enigo::Key, direction: enigo::Direction) -> Result<(), String>;
+}
+
+impl KeySender for Enigo {
+    fn send_key(&mut self, key: enigo::Key, direction: enigo::Direction) -> Result<(), String> {
+        self.key(key, direction).map_err(|e| e.to_string())
+    }
+}
+
+// 发送 Cmd+V：按下 Meta 之后无论 V 是否成功都释放 Meta，避免修饰键卡在按下状态
+fn send_paste_shortcut(keys: &mut impl KeySender) -> Result<(), String> {
+    keys.send_key(enigo::Key::Meta, enigo::Direction::Press)
+        .map_err(|e| format!("Failed to press Meta key: {}", e))?;
+    let clicked = keys
+        .send_key(enigo::Key::Unicode('v'), enigo::Direction::Click)
+        .map_err(|e| format!("Failed to press V key: {}", e));
+    let released = keys
+        .send_key(enigo::Key::Meta, enigo::Direction::Release)
+        .map_err(|e| format!("Failed to release Meta key: {}", e));
+    if let Err(ref e) = released {
+        log::warn!("{}", e);
+    }
+    clicked.and(released)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use enigo::{Direction, Key};
+
+    // 记录按键调用，对 Click 返回错误
+    #[derive(Default)]
+    struct FailingClick {
+        calls: Vec<(Key, Direction)>,
+    }
+
+    impl KeySender for FailingClick {
+        fn send_key(&mut self, key: Key, direction: Direction) -> Result<(), String> {
+            self.calls.push((key, direction));
+            if direction == Direction::Click {
+                return Err("click failed".to_string());
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn meta_is_released_when_v_click_fails() {
+        let mut keys = FailingClick::default();
+        let result = send_paste_shortcut(&mut keys);
+
+        assert_eq!(result, Err("Failed to press V key: click failed".to_string()));
+        assert_eq!(
+            keys.calls,
+            vec![
+                (Key::Meta, Direction::Press),
+                (Key::Unicode('v'), Direction::Click),
+                (Key::Meta, Direction::Release),
+            ]
+        );
+    }
 }
@@ -1,17 +1,37 @@
 mod audio;
+mod batch;
+mod cache;
+mod denoise;
+mod device_rules;
+mod error;
+mod export;
+mod feedback;
 mod focus;
+mod history;
 mod hotkey;
 mod input;
+mod migrate;
+#[cfg(feature = "mouse-hotkey")]
+mod mouse;
+mod notify;
+mod power;
+mod queue;
 mod sidecar;
+mod text;
+mod transcriber;
+mod translate;
 mod tray;
+mod voice_command;
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Mutex;
 use std::path::PathBuf;
 use std::fs;
 use serde_json::json;
 use chrono::Local;
-use tauri::Emitter;
+use tauri::{Emitter, Manager};
+
+use error::{CommandError, CommandResult};
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, Default)]
 pub struct UsageStats {
@@ -19,6 +39,35 @@ pub struct UsageStats {
     pub total_transcriptions: u64,
     pub today_characters: u64,
     pub today_date: String,
+    // 当前计费周期（从 period_start 开始）累计的字数，用于估算额度
+    #[serde(default)]
+    pub period_characters: u64,
+    #[serde(default)]
+    pub period_start: String,
+}
+
+// 额度估算设置：每月重置日和周期内的字数预算
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+#[serde(default)]
+pub struct QuotaSettings {
+    pub reset_day: u32,                   // 每月第几天开始新周期（1-28）
+    pub budget_characters: Option<u64>,   // 周期预算，None 表示不提醒
+    pub warn_ratio: f64,                  // 用量达到预算的该比例时发送 quota-warning
+}
+
+impl Default for QuotaSettings {
+    fn default() -> Self {
+        QuotaSettings { reset_day: 1, budget_characters: None, warn_ratio: 0.8 }
+    }
+}
+
+// 额度估算结果
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct QuotaEstimate {
+    pub period_start: String,
+    pub used_characters: u64,
+    pub budget_characters: Option<u64>,
+    pub remaining_characters: Option<u64>,
 }
 
 // 历史记录项
@@ -29,10 +78,95 @@ pub struct HistoryItem {
     pub timestamp: i64,  // Unix timestamp in seconds
     pub date: String,    // YYYY-MM-DD format for grouping
     pub char_count: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio_path: Option<String>,  // 保留的录音文件路径
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,    // 识别出的语言
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,    // 录音时长
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub failed: bool,                // 转录失败的占位记录，可重新转录
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_app: Option<String>,  // 听写时的前台应用（开启自动标签时记录）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_text: Option<String>,  // 翻译前的原文（开启 keep_original 时记录）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub segments: Option<Vec<sidecar::Segment>>,  // sidecar 返回的分段时间，用于导出字幕
 }
 
-// 历史记录保留设置
+impl HistoryItem {
+    pub fn new(text: &str) -> Self {
+        let now = Local::now();
+        HistoryItem {
+            id: format!("{}", now.timestamp_millis()),
+            text: text.to_string(),
+            timestamp: now.timestamp(),
+            date: now.format("%Y-%m-%d").to_string(),
+            char_count: text.chars().count(),
+            audio_path: None,
+            language: None,
+            duration_ms: None,
+            failed: false,
+            tags: Vec::new(),
+            source_app: None,
+            original_text: None,
+            segments: None,
+        }
+    }
+}
+
+// 录音条窗口外观
 #[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+#[serde(default)]
+pub struct RecordingBarSettings {
+    pub width: f64,
+    pub height: f64,
+    pub opacity: f64,
+    pub always_on_top: bool,  // 置顶并在全屏应用上方显示
+    pub click_through: bool,  // 忽略鼠标事件，避免误点抢走焦点
+}
+
+impl Default for RecordingBarSettings {
+    fn default() -> Self {
+        RecordingBarSettings {
+            width: 200.0,
+            height: 52.0,
+            opacity: 0.98,
+            always_on_top: true,
+            click_through: false,
+        }
+    }
+}
+
+impl RecordingBarSettings {
+    pub const WIDTH_RANGE: (f64, f64) = (120.0, 1200.0);
+    pub const HEIGHT_RANGE: (f64, f64) = (32.0, 300.0);
+    pub const OPACITY_RANGE: (f64, f64) = (0.2, 1.0);
+
+    // 读取配置时将值限制在合理范围内
+    pub fn clamped(self) -> Self {
+        RecordingBarSettings {
+            width: self.width.clamp(Self::WIDTH_RANGE.0, Self::WIDTH_RANGE.1),
+            height: self.height.clamp(Self::HEIGHT_RANGE.0, Self::HEIGHT_RANGE.1),
+            opacity: self.opacity.clamp(Self::OPACITY_RANGE.0, Self::OPACITY_RANGE.1),
+            ..self
+        }
+    }
+}
+
+// 存储空间占用
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, Default)]
+pub struct StorageUsage {
+    pub config_bytes: u64,
+    pub history_bytes: u64,
+    pub recordings_bytes: u64,
+    pub recordings_count: usize,
+}
+
+// 历史记录保留设置
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq, Default)]
 pub enum HistoryRetention {
     #[serde(rename = "7days")]
     SevenDays,
@@ -41,55 +175,87 @@ pub enum HistoryRetention {
     #[serde(rename = "90days")]
     NinetyDays,
     #[serde(rename = "forever")]
+    #[default]
     Forever,
 }
 
-impl Default for HistoryRetention {
-    fn default() -> Self {
-        HistoryRetention::Forever
-    }
-}
-
-#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq, Default)]
 pub enum OutputMode {
     #[serde(rename = "keyboard")]
+    #[default]
     Keyboard,
     #[serde(rename = "clipboard")]
     Clipboard,
+    // 实验性：通过 macOS 辅助功能 API 直接写入焦点文本框，不支持时退回键盘模式
+    #[serde(rename = "accessibility")]
+    Accessibility,
 }
 
-impl Default for OutputMode {
-    fn default() -> Self {
-        OutputMode::Keyboard
-    }
+// 转录结果的输出目标
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputTarget {
+    #[default]
+    PreviousApp,            // 开始录音时的前台应用（Toggle 模式）
+    ByPointer,              // 停止录音时鼠标指针下的应用（Toggle 模式）
+    ByWindowTitle(String),  // 标题包含指定文字的窗口，任何录音模式下都会先激活它
 }
 
 // 录音模式
-#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq, Default)]
 pub enum RecordingMode {
     #[serde(rename = "hold")]
+    #[default]
     Hold,    // 按住录音，松开停止
     #[serde(rename = "toggle")]
     Toggle,  // 按一下开始，再按一下停止
 }
 
-impl Default for RecordingMode {
-    fn default() -> Self {
-        RecordingMode::Hold
-    }
-}
-
-#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum StopAction {
+    #[default]
     Confirm,
     Cancel,
 }
 
-impl Default for StopAction {
-    fn default() -> Self {
-        StopAction::Confirm
-    }
+// 已在录音时再次触发开始的处理策略
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AlreadyRecordingPolicy {
+    #[default]
+    Ignore,        // 忽略（默认）
+    RestartFresh,  // 丢弃当前录音并重新开始
+    ForceStop,     // 视为停止录音
+}
+
+// 按下录音键时 ASR 服务尚未就绪的处理策略
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SidecarNotReadyPolicy {
+    BlockUntilReady,    // 照常录音，转录等服务就绪后再进行
+    RejectImmediately,  // 拒绝开始录音并提示
+    #[default]
+    RecordAnyway,       // 照常录音和转录（可能失败）
+}
+
+// 语言提示模式
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Default)]
+#[serde(tag = "mode", content = "language", rename_all = "lowercase")]
+pub enum LanguageMode {
+    Fixed(String),  // 始终使用指定语言
+    #[default]
+    Auto,           // 不发送提示，由 ASR 自行识别
+    Sticky,         // 沿用上一次识别出的语言
+}
+
+// 转录后翻译：由 sidecar 调用翻译模型，或转发到自定义的翻译接口
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, Default)]
+#[serde(default)]
+pub struct TranslationSettings {
+    pub translate_to: Option<String>,  // 目标语言（如 "en"），None 表示不翻译
+    pub endpoint: Option<String>,      // 自定义翻译接口，POST {text, target} 返回 {text}
+    pub keep_original: bool,           // 历史记录中同时保存原文
 }
 
 pub struct AppState {
@@ -100,13 +266,80 @@ pub struct AppState {
     pub cancelled_sessions: Mutex<HashSet<u64>>,
     pub sidecar_manager: Mutex<Option<sidecar::SidecarManager>>,
     pub previous_app: Mutex<Option<String>>,
+    pub continue_last: Mutex<bool>,
+    pub last_language: Mutex<Option<String>>,
+    pub last_output: Mutex<Option<String>>,  // 上一次输出的文字，供命令模式撤销
+    pub last_transcript: Mutex<Option<(String, std::time::Instant)>>,  // 上一次转录及时间，用于去重
+    pub pending_output: Mutex<Option<hotkey::PendingOutput>>,  // 等待确认的输出
+    pub recent_errors: Mutex<VecDeque<RecentError>>,  // 最近的错误，供晚打开的窗口补看
+}
+
+// 保留的最近错误条数
+const RECENT_ERRORS_LIMIT: usize = 20;
+
+// 超过该时间（秒）的错误不再补发，窗口重新挂载时不会看到早已处理过的错误
+const ERROR_REPLAY_MAX_AGE_SECS: i64 = 60;
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct RecentError {
+    pub message: String,
+    pub timestamp: i64,
+    #[serde(skip)]
+    pub replayed: bool,  // 已补发过一次，不再重复补发
+}
+
+// 发送 error 事件并记录，窗口在错误发生后才打开（如启动时快捷键注册失败）也能取回
+pub fn emit_error(app: &tauri::AppHandle, message: impl Into<String>) {
+    let message = message.into();
+    if let Ok(mut errors) = app.state::<AppState>().recent_errors.lock() {
+        if errors.len() >= RECENT_ERRORS_LIMIT {
+            errors.pop_front();
+        }
+        errors.push_back(RecentError {
+            message: message.clone(),
+            timestamp: Local::now().timestamp(),
+            replayed: false,
+        });
+    }
+    let _ = app.emit("error", message);
+}
+
+// 获取最近的错误，最新在前
+#[tauri::command]
+fn get_recent_errors(state: tauri::State<'_, AppState>, limit: usize) -> CommandResult<Vec<RecentError>> {
+    let errors = state.recent_errors.lock().map_err(|e| e.to_string())?;
+    Ok(errors.iter().rev().take(limit).cloned().collect())
 }
 
+// 重新发送最近一次错误，供刚订阅 error 事件的窗口调用；每条错误只补发一次，过旧的不补发
+#[tauri::command]
+fn request_error_replay(app_handle: tauri::AppHandle, state: tauri::State<'_, AppState>) -> CommandResult<bool> {
+    let message = {
+        let mut errors = state.recent_errors.lock().map_err(|e| e.to_string())?;
+        let now = Local::now().timestamp();
+        match errors.back_mut() {
+            Some(error) if !error.replayed && now - error.timestamp <= ERROR_REPLAY_MAX_AGE_SECS => {
+                error.replayed = true;
+                error.message.clone()
+            }
+            _ => return Ok(false),
+        }
+    };
+    let _ = app_handle.emit("error", message);
+    Ok(true)
+}
+
+// 续写窗口：超过该时间后不再追加到上一条历史记录，避免意外合并
+const CONTINUATION_WINDOW_SECS: i64 = 120;
+
 // 快捷键配置
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, Default)]
 pub struct HotkeyConfig {
     pub modifiers: Vec<String>,  // ["ctrl", "shift", "alt", "cmd"]
     pub key: String,             // "r", "f5", "space", etc.
+    // 该快捷键使用的录音模式，None 表示跟随全局录音模式
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<RecordingMode>,
 }
 
 impl HotkeyConfig {
@@ -126,209 +359,1507 @@ impl HotkeyConfig {
     }
 }
 
-#[tauri::command]
-fn set_output_mode(state: tauri::State<'_, AppState>, mode: OutputMode) -> Result<(), String> {
-    let mut output_mode = state.output_mode.lock().map_err(|e| e.to_string())?;
-    *output_mode = mode;
+impl OutputMode {
+    // 循环切换只包含稳定的模式
+    pub const ALL: [OutputMode; 2] = [OutputMode::Keyboard, OutputMode::Clipboard];
+
+    // 循环切换到下一个输出模式
+    pub fn next(self) -> OutputMode {
+        let index = Self::ALL.iter().position(|m| *m == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+// 更新输出模式并保存到配置
+pub fn apply_output_mode(app: &tauri::AppHandle, mode: OutputMode) -> Result<(), String> {
+    {
+        let state = app.state::<AppState>();
+        let mut output_mode = state.output_mode.lock().map_err(|e| e.to_string())?;
+        *output_mode = mode;
+    }
+    set_setting("output_mode", &mode)?;
     log::info!("Output mode set to: {:?}", mode);
+    let _ = app.emit("output-mode-changed", mode);
+    Ok(())
+}
+
+#[tauri::command]
+fn set_output_mode(app_handle: tauri::AppHandle, mode: OutputMode) -> CommandResult<()> {
+    if mode == OutputMode::Accessibility && !get_setting::<bool>("experimental_accessibility_output") {
+        return Err(CommandError::invalid_input("Accessibility output is experimental, enable it first"));
+    }
+    apply_output_mode(&app_handle, mode).map_err(Into::into)
+}
+
+// 实验性功能开关：允许选择辅助功能输出模式
+#[tauri::command]
+fn get_experimental_accessibility_output() -> bool {
+    get_setting("experimental_accessibility_output")
+}
+
+#[tauri::command]
+fn set_experimental_accessibility_output(app_handle: tauri::AppHandle, enabled: bool) -> CommandResult<()> {
+    set_setting("experimental_accessibility_output", &enabled)?;
+    log::info!("Experimental accessibility output set to: {}", enabled);
+
+    // 关闭时若正在使用该模式，切回键盘模式
+    let current = *app_handle.state::<AppState>().output_mode.lock().map_err(|e| e.to_string())?;
+    if !enabled && current == OutputMode::Accessibility {
+        apply_output_mode(&app_handle, OutputMode::Keyboard)?;
+    }
     Ok(())
 }
 
 #[tauri::command]
-fn get_output_mode(state: tauri::State<'_, AppState>) -> Result<OutputMode, String> {
+fn get_output_mode(state: tauri::State<'_, AppState>) -> CommandResult<OutputMode> {
     let output_mode = state.output_mode.lock().map_err(|e| e.to_string())?;
     Ok(*output_mode)
 }
 
 #[tauri::command]
-fn get_recording_mode(state: tauri::State<'_, AppState>) -> Result<RecordingMode, String> {
+fn get_output_target() -> OutputTarget {
+    get_setting("output_target")
+}
+
+#[tauri::command]
+fn set_output_target(target: OutputTarget) -> CommandResult<()> {
+    if matches!(target, OutputTarget::ByWindowTitle(ref title) if title.trim().is_empty()) {
+        return Err(CommandError::invalid_input("Window title cannot be empty"));
+    }
+    set_setting("output_target", &target)?;
+    log::info!("Output target set to: {:?}", target);
+    Ok(())
+}
+
+#[tauri::command]
+fn get_recording_mode(state: tauri::State<'_, AppState>) -> CommandResult<RecordingMode> {
     let recording_mode = state.recording_mode.lock().map_err(|e| e.to_string())?;
     Ok(*recording_mode)
 }
 
-#[tauri::command]
-fn set_recording_mode(app_handle: tauri::AppHandle, state: tauri::State<'_, AppState>, mode: RecordingMode) -> Result<(), String> {
-    let mut recording_mode = state.recording_mode.lock().map_err(|e| e.to_string())?;
-    *recording_mode = mode;
+pub fn apply_recording_mode(app: &tauri::AppHandle, mode: RecordingMode) -> Result<(), String> {
+    {
+        let state = app.state::<AppState>();
+        let mut recording_mode = state.recording_mode.lock().map_err(|e| e.to_string())?;
+        *recording_mode = mode;
+    }
     log::info!("Recording mode set to: {:?}", mode);
     
     // 通知前端录音模式已更改
-    let _ = app_handle.emit("recording-mode-changed", mode);
+    let _ = app.emit("recording-mode-changed", mode);
     
     Ok(())
 }
 
 #[tauri::command]
-fn stop_recording(app_handle: tauri::AppHandle, action: Option<StopAction>) -> Result<(), String> {
-    let action = action.unwrap_or_default();
-    log::info!("stop_recording called with action: {:?}", action);
-    match action {
-        StopAction::Confirm => hotkey::stop_recording_manually(&app_handle),
-        StopAction::Cancel => hotkey::cancel_recording_manually(&app_handle),
-    }
+fn set_recording_mode(app_handle: tauri::AppHandle, mode: RecordingMode) -> CommandResult<()> {
+    apply_recording_mode(&app_handle, mode).map_err(Into::into)
 }
 
+// 按输入设备自动切换模式的规则
 #[tauri::command]
-fn cancel_recording(app_handle: tauri::AppHandle) -> Result<(), String> {
-    hotkey::cancel_recording_manually(&app_handle)
-}
-
-fn get_config_path() -> PathBuf {
-    let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
-    home.join(".mouth-high").join("config.json")
+fn get_device_mode_rules() -> device_rules::DeviceModeRules {
+    get_setting("device_mode_rules")
 }
 
 #[tauri::command]
-fn get_api_key() -> Result<Option<String>, String> {
-    let config_path = get_config_path();
-    if !config_path.exists() {
-        return Ok(None);
+fn set_device_mode_rules(rules: device_rules::DeviceModeRules) -> CommandResult<()> {
+    if rules.rules.iter().any(|rule| rule.device_name_substring.trim().is_empty()) {
+        return Err(CommandError::invalid_input("Device name cannot be empty"));
     }
+    set_setting("device_mode_rules", &rules)?;
+    log::info!("Device mode rules set: enabled={}, {} rules", rules.enabled, rules.rules.len());
+    Ok(())
+}
 
-    let content = fs::read_to_string(&config_path)
-        .map_err(|e| format!("Failed to read config: {}", e))?;
-
-    let config: serde_json::Value = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse config: {}", e))?;
-
-    Ok(config.get("dashscope_api_key")
-        .and_then(|v| v.as_str())
-        .map(|s| s.to_string()))
+#[tauri::command]
+fn get_recording_bar_settings() -> CommandResult<RecordingBarSettings> {
+    Ok(get_setting::<RecordingBarSettings>("recording_bar").clamped())
 }
 
 #[tauri::command]
-fn set_api_key(api_key: String) -> Result<(), String> {
-    let config_path = get_config_path();
+fn set_recording_bar_settings(app: tauri::AppHandle, settings: RecordingBarSettings) -> CommandResult<()> {
+    let in_range = |value: f64, (min, max): (f64, f64)| value.is_finite() && value >= min && value <= max;
+    if !in_range(settings.width, RecordingBarSettings::WIDTH_RANGE)
+        || !in_range(settings.height, RecordingBarSettings::HEIGHT_RANGE)
+    {
+        return Err(CommandError::invalid_input(format!("Invalid recording bar size: {}x{}", settings.width, settings.height)));
+    }
+    if !in_range(settings.opacity, RecordingBarSettings::OPACITY_RANGE) {
+        return Err(CommandError::invalid_input(format!("Invalid recording bar opacity: {}", settings.opacity)));
+    }
 
-    // Create directory if needed
-    if let Some(parent) = config_path.parent() {
-        fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    // 不能超出当前显示器
+    if let Some(window) = app.get_webview_window("recording-bar") {
+        if let Ok(Some(monitor)) = window.current_monitor() {
+            let size = monitor.size().to_logical::<f64>(monitor.scale_factor());
+            if settings.width > size.width || settings.height > size.height {
+                return Err(CommandError::invalid_input(format!(
+                    "Recording bar size {}x{} exceeds the display ({}x{})",
+                    settings.width, settings.height, size.width, size.height
+                )));
+            }
+        }
     }
 
-    // Read existing config or create new
-    let mut config: serde_json::Value = if config_path.exists() {
-        let content = fs::read_to_string(&config_path).unwrap_or_else(|_| "{}".to_string());
-        serde_json::from_str(&content).unwrap_or_else(|_| json!({}))
-    } else {
-        json!({})
-    };
+    set_setting("recording_bar", &settings)?;
+    log::info!("Recording bar settings updated: {:?}", settings);
+    Ok(())
+}
 
-    // Update API key
-    config["dashscope_api_key"] = json!(api_key);
+// 已连接的显示器，供设置录音条显示位置
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct MonitorInfo {
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    pub primary: bool,
+}
 
-    // Write back
-    let content = serde_json::to_string_pretty(&config)
-        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+#[tauri::command]
+fn list_monitors(app: tauri::AppHandle) -> CommandResult<Vec<MonitorInfo>> {
+    let primary = app
+        .primary_monitor()
+        .map_err(|e| format!("Failed to query primary monitor: {}", e))?
+        .and_then(|monitor| monitor.name().cloned());
+    let monitors = app
+        .available_monitors()
+        .map_err(|e| format!("Failed to list monitors: {}", e))?;
+    Ok(monitors
+        .into_iter()
+        .filter_map(|monitor| {
+            let name = monitor.name()?.clone();
+            Some(MonitorInfo {
+                primary: primary.as_ref() == Some(&name),
+                width: monitor.size().width,
+                height: monitor.size().height,
+                name,
+            })
+        })
+        .collect())
+}
 
-    fs::write(&config_path, content)
-        .map_err(|e| format!("Failed to write config: {}", e))?;
+// 录音条固定显示的显示器名称，None 表示跟随鼠标所在的显示器
+#[tauri::command]
+fn get_recording_bar_monitor() -> Option<String> {
+    get_setting("recording_bar_monitor")
+}
 
-    log::info!("API key saved to {:?}", config_path);
+#[tauri::command]
+fn set_recording_bar_monitor(app: tauri::AppHandle, name: Option<String>) -> CommandResult<()> {
+    if let Some(ref name) = name {
+        let connected = app
+            .available_monitors()
+            .map_err(|e| format!("Failed to list monitors: {}", e))?
+            .iter()
+            .any(|monitor| monitor.name() == Some(name));
+        if !connected {
+            return Err(CommandError::not_found(format!("Monitor not found: {}", name)));
+        }
+    }
+    set_setting("recording_bar_monitor", &name)?;
+    log::info!("Recording bar monitor set to: {:?}", name);
     Ok(())
 }
 
+// 处理超时（秒），超时后发送 processing-timeout
 #[tauri::command]
-fn is_api_key_configured() -> bool {
-    match get_api_key() {
-        Ok(Some(key)) => !key.is_empty(),
-        _ => false,
-    }
+fn get_processing_timeout_secs() -> u64 {
+    get_setting::<Option<u64>>("processing_timeout_secs").unwrap_or(hotkey::DEFAULT_PROCESSING_TIMEOUT_SECS)
 }
 
-// 获取快捷键配置
 #[tauri::command]
-fn get_hotkey_config() -> Result<HotkeyConfig, String> {
-    let config_path = get_config_path();
-    if !config_path.exists() {
-        // 返回默认配置
-        return Ok(HotkeyConfig {
-            modifiers: vec!["ctrl".to_string(), "shift".to_string()],
-            key: "r".to_string(),
-        });
+fn set_processing_timeout_secs(secs: u64) -> CommandResult<()> {
+    if !(5..=600).contains(&secs) {
+        return Err(CommandError::invalid_input("Processing timeout must be between 5 and 600 seconds"));
     }
+    set_setting("processing_timeout_secs", &secs)?;
+    log::info!("Processing timeout set to: {}s", secs);
+    Ok(())
+}
 
-    let content = fs::read_to_string(&config_path)
-        .map_err(|e| format!("Failed to read config: {}", e))?;
-
-    let config: serde_json::Value = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse config: {}", e))?;
+// Hold 模式下也显示录音条（不获取焦点）
+#[tauri::command]
+fn get_show_bar_in_hold() -> bool {
+    get_setting("show_bar_in_hold")
+}
 
-    let hotkey_config: HotkeyConfig = config.get("hotkey")
-        .and_then(|v| serde_json::from_value(v.clone()).ok())
-        .unwrap_or_else(|| HotkeyConfig {
-            modifiers: vec!["ctrl".to_string(), "shift".to_string()],
-            key: "r".to_string(),
-        });
+#[tauri::command]
+fn set_show_bar_in_hold(enabled: bool) -> CommandResult<()> {
+    set_setting("show_bar_in_hold", &enabled)?;
+    log::info!("Show recording bar in Hold mode set to: {}", enabled);
+    Ok(())
+}
 
-    Ok(hotkey_config)
+// 录音条显示时按 Escape 取消录音（不转录）
+#[tauri::command]
+fn get_escape_cancels_recording() -> CommandResult<bool> {
+    Ok(get_setting("escape_cancels_recording"))
 }
 
-// 设置快捷键配置
 #[tauri::command]
-fn set_hotkey_config(config: HotkeyConfig) -> Result<(), String> {
-    let config_path = get_config_path();
+fn set_escape_cancels_recording(enabled: bool) -> CommandResult<()> {
+    set_setting("escape_cancels_recording", &enabled)?;
+    log::info!("Escape cancels recording set to: {}", enabled);
+    Ok(())
+}
 
-    // Create directory if needed
-    if let Some(parent) = config_path.parent() {
-        fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed to create config directory: {}", e))?;
-    }
+// 设置下一次转录是否追加到最近一条历史记录（仅生效一次）
+#[tauri::command]
+fn set_continue_last(state: tauri::State<'_, AppState>, enabled: bool) -> CommandResult<()> {
+    let mut continue_last = state.continue_last.lock().map_err(|e| e.to_string())?;
+    *continue_last = enabled;
+    log::info!("Continue last history item: {}", enabled);
+    Ok(())
+}
 
-    // Read existing config or create new
-    let mut full_config: serde_json::Value = if config_path.exists() {
-        let content = fs::read_to_string(&config_path).unwrap_or_else(|_| "{}".to_string());
-        serde_json::from_str(&content).unwrap_or_else(|_| json!({}))
-    } else {
-        json!({})
-    };
+#[tauri::command]
+fn get_continue_last(state: tauri::State<'_, AppState>) -> CommandResult<bool> {
+    let continue_last = state.continue_last.lock().map_err(|e| e.to_string())?;
+    Ok(*continue_last)
+}
 
-    // Update hotkey config
-    full_config["hotkey"] = serde_json::to_value(&config)
-        .map_err(|e| format!("Failed to serialize hotkey config: {}", e))?;
+// Markdown 辅助
+#[tauri::command]
+fn get_markdown_assist() -> text::MarkdownAssist {
+    get_setting("markdown_assist")
+}
 
-    // Write back
-    let content = serde_json::to_string_pretty(&full_config)
-        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+#[tauri::command]
+fn set_markdown_assist(settings: text::MarkdownAssist) -> CommandResult<()> {
+    set_setting("markdown_assist", &settings)?;
+    log::info!("Markdown assist set (enabled: {}, {} apps)", settings.enabled, settings.apps.len());
+    Ok(())
+}
 
-    fs::write(&config_path, content)
-        .map_err(|e| format!("Failed to write config: {}", e))?;
+// 语气词过滤
+#[tauri::command]
+fn get_filler_word_filter() -> text::FillerWordFilter {
+    get_setting("filler_word_filter")
+}
 
-    log::info!("Hotkey config saved: {}", config.to_display_string());
+#[tauri::command]
+fn set_filler_word_filter(filter: text::FillerWordFilter) -> CommandResult<()> {
+    set_setting("filler_word_filter", &filter)?;
+    log::info!("Filler word filter set (enabled: {})", filter.enabled);
     Ok(())
 }
 
-// 更新快捷键并重新注册
+// 口述标点
 #[tauri::command]
-fn update_hotkey(app_handle: tauri::AppHandle, config: HotkeyConfig) -> Result<(), String> {
-    hotkey::update_hotkey(&app_handle, &config)
+fn get_spoken_punctuation() -> text::SpokenPunctuation {
+    get_setting("spoken_punctuation")
 }
 
 #[tauri::command]
-fn get_usage_stats() -> Result<UsageStats, String> {
-    let config_path = get_config_path();
-    if !config_path.exists() {
-        return Ok(UsageStats::default());
-    }
+fn set_spoken_punctuation(settings: text::SpokenPunctuation) -> CommandResult<()> {
+    set_setting("spoken_punctuation", &settings)?;
+    log::info!("Spoken punctuation enabled: {}", settings.enabled);
+    Ok(())
+}
 
-    let content = fs::read_to_string(&config_path)
-        .map_err(|e| format!("Failed to read config: {}", e))?;
+#[tauri::command]
+fn get_text_transform() -> text::TransformSettings {
+    get_setting("text_transform")
+}
 
-    let config: serde_json::Value = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse config: {}", e))?;
+#[tauri::command]
+fn set_text_transform(settings: text::TransformSettings) -> CommandResult<()> {
+    set_setting("text_transform", &settings)?;
+    log::info!("Text transform set to: {:?}", settings);
+    Ok(())
+}
 
-    let mut stats: UsageStats = config.get("stats")
-        .and_then(|v| serde_json::from_value(v.clone()).ok())
-        .unwrap_or_default();
+#[tauri::command]
+fn get_output_template() -> text::OutputTemplate {
+    get_setting("output_template")
+}
 
-    // Check if we need to reset today's stats
-    let today = Local::now().format("%Y-%m-%d").to_string();
-    if stats.today_date != today {
-        stats.today_characters = 0;
-        stats.today_date = today;
+#[tauri::command]
+fn set_output_template(template: text::OutputTemplate) -> CommandResult<()> {
+    if let Some(ref t) = template.template {
+        text::validate_template(t)?;
     }
+    set_setting("output_template", &template)?;
+    log::info!("Output template set to: {:?}", template);
+    Ok(())
+}
 
-    Ok(stats)
+// 使用电池时的音量事件节流，下次开始录音时生效
+#[tauri::command]
+fn get_amplitude_throttle() -> audio::AmplitudeThrottle {
+    get_setting("amplitude_throttle")
+}
+
+#[tauri::command]
+fn set_amplitude_throttle(throttle: audio::AmplitudeThrottle) -> CommandResult<()> {
+    if !(audio::AMPLITUDE_INTERVAL_MS as u64..=1000).contains(&throttle.battery_interval_ms) {
+        return Err(CommandError::invalid_input(format!(
+            "Battery amplitude interval must be between {} and 1000 ms",
+            audio::AMPLITUDE_INTERVAL_MS
+        )));
+    }
+    set_setting("amplitude_throttle", &throttle)?;
+    log::info!("Amplitude throttle set to: {:?}", throttle);
+    Ok(())
+}
+
+#[tauri::command]
+fn get_amplitude_curve() -> audio::AmplitudeCurve {
+    get_setting("amplitude_curve")
+}
+
+#[tauri::command]
+fn set_amplitude_curve(curve: audio::AmplitudeCurve) -> CommandResult<()> {
+    if let audio::AmplitudeCurve::Linear { gain } = curve {
+        if !gain.is_finite() || gain <= 0.0 {
+            return Err(CommandError::invalid_input(format!("Invalid amplitude gain: {}", gain)));
+        }
+    }
+    set_setting("amplitude_curve", &curve)?;
+    log::info!("Amplitude curve set to: {:?}", curve);
+    Ok(())
+}
+
+#[tauri::command]
+fn get_already_recording_policy() -> AlreadyRecordingPolicy {
+    get_setting("already_recording_policy")
+}
+
+#[tauri::command]
+fn set_already_recording_policy(policy: AlreadyRecordingPolicy) -> CommandResult<()> {
+    set_setting("already_recording_policy", &policy)?;
+    log::info!("Already-recording policy set to: {:?}", policy);
+    Ok(())
+}
+
+#[tauri::command]
+fn get_sidecar_not_ready_policy() -> SidecarNotReadyPolicy {
+    get_setting("sidecar_not_ready_policy")
+}
+
+#[tauri::command]
+fn set_sidecar_not_ready_policy(app: tauri::AppHandle, policy: SidecarNotReadyPolicy) -> CommandResult<()> {
+    set_setting("sidecar_not_ready_policy", &policy)?;
+    log::info!("Sidecar-not-ready policy set to: {:?}", policy);
+    // 切换策略后让等待中的任务重新检查
+    queue::wake(&app);
+    Ok(())
+}
+
+#[tauri::command]
+fn is_sidecar_ready() -> bool {
+    sidecar::is_ready()
+}
+
+// ASR 服务握手时声明支持的功能
+#[tauri::command]
+fn get_sidecar_capabilities() -> Vec<String> {
+    sidecar::capabilities()
+}
+
+// ASR 服务路径覆盖（为空时自动查找），下次启动生效
+#[tauri::command]
+fn get_sidecar_paths() -> (Option<String>, Option<String>) {
+    (get_setting("python_path"), get_setting("asr_script_path"))
+}
+
+#[tauri::command]
+fn set_sidecar_paths(python_path: Option<String>, asr_script_path: Option<String>) -> CommandResult<()> {
+    let normalize = |path: Option<String>| path.map(|p| p.trim().to_string()).filter(|p| !p.is_empty());
+    set_setting("python_path", &normalize(python_path))?;
+    set_setting("asr_script_path", &normalize(asr_script_path))?;
+    log::info!("Sidecar path overrides updated");
+    Ok(())
+}
+
+#[tauri::command]
+fn validate_sidecar_paths() -> CommandResult<String> {
+    sidecar::validate_paths().map_err(Into::into)
+}
+
+// 转录结果缓存（默认关闭）
+#[tauri::command]
+fn get_transcription_cache() -> bool {
+    cache::is_enabled()
+}
+
+#[tauri::command]
+fn set_transcription_cache(enabled: bool) -> CommandResult<()> {
+    set_setting("transcription_cache", &enabled)?;
+    log::info!("Transcription cache set to: {}", enabled);
+    Ok(())
+}
+
+#[tauri::command]
+fn clear_transcription_cache() -> CommandResult<usize> {
+    let count = cache::clear()?;
+    log::info!("Cleared {} transcription cache entries", count);
+    Ok(count)
+}
+
+// 转录队列：并发上限、溢出策略和当前深度
+#[tauri::command]
+fn get_max_concurrent_transcriptions() -> usize {
+    queue::max_concurrent()
+}
+
+#[tauri::command]
+fn set_max_concurrent_transcriptions(app_handle: tauri::AppHandle, max: usize) -> CommandResult<()> {
+    if !(1..=queue::MAX_WORKERS).contains(&max) {
+        return Err(CommandError::invalid_input(format!("Concurrency must be between 1 and {}", queue::MAX_WORKERS)));
+    }
+    set_setting("max_concurrent_transcriptions", &max)?;
+    log::info!("Max concurrent transcriptions set to: {}", max);
+    queue::wake(&app_handle);
+    Ok(())
+}
+
+#[tauri::command]
+fn get_queue_overflow_policy() -> queue::QueueOverflowPolicy {
+    get_setting("queue_overflow_policy")
+}
+
+#[tauri::command]
+fn set_queue_overflow_policy(policy: queue::QueueOverflowPolicy) -> CommandResult<()> {
+    set_setting("queue_overflow_policy", &policy)?;
+    log::info!("Queue overflow policy set to: {:?}", policy);
+    Ok(())
+}
+
+// 转录耗时统计（最近若干次的滚动平均）
+#[tauri::command]
+fn get_transcription_timing_stats() -> sidecar::TimingStats {
+    sidecar::timing_stats()
+}
+
+// 取消排队中或正在处理的转录任务
+#[tauri::command]
+fn cancel_transcription(app_handle: tauri::AppHandle, job_id: String) -> CommandResult<()> {
+    queue::cancel(&app_handle, &job_id)
+}
+
+#[tauri::command]
+fn get_queue_depth(app_handle: tauri::AppHandle) -> queue::QueueDepth {
+    queue::depth(&app_handle)
+}
+
+// 防止输出到本应用窗口（默认开启）
+#[tauri::command]
+fn get_self_focus_guard() -> bool {
+    get_setting::<Option<bool>>("self_focus_guard").unwrap_or(true)
+}
+
+#[tauri::command]
+fn set_self_focus_guard(enabled: bool) -> CommandResult<()> {
+    set_setting("self_focus_guard", &enabled)?;
+    log::info!("Self focus guard set to: {}", enabled);
+    Ok(())
+}
+
+// 键盘模拟输入方式
+#[tauri::command]
+fn get_keyboard_backend() -> input::KeyboardBackend {
+    get_setting("keyboard_backend")
+}
+
+#[tauri::command]
+fn set_keyboard_backend(backend: input::KeyboardBackend) -> CommandResult<()> {
+    set_setting("keyboard_backend", &backend)?;
+    log::info!("Keyboard backend set to: {:?}", backend);
+    Ok(())
+}
+
+// 仅内存模式：录音不写入任何文件，直接以内存中的数据交给 sidecar
+#[tauri::command]
+fn get_memory_only() -> bool {
+    get_setting("memory_only")
+}
+
+#[tauri::command]
+fn set_memory_only(enabled: bool) -> CommandResult<()> {
+    if enabled && sidecar::is_ready() && !sidecar::has_capability("audio_base64") {
+        return Err(CommandError::unsupported("ASR service does not support in-memory audio"));
+    }
+    set_setting("memory_only", &enabled)?;
+    log::info!("Memory-only recording set to: {}", enabled);
+    Ok(())
+}
+
+// 剪贴板模式的粘贴时机：固定等待或读回确认后立即粘贴
+#[tauri::command]
+fn get_paste_timing() -> input::PasteTiming {
+    get_setting("paste_timing")
+}
+
+#[tauri::command]
+fn set_paste_timing(timing: input::PasteTiming) -> CommandResult<()> {
+    set_setting("paste_timing", &timing)?;
+    log::info!("Paste timing set to: {:?}", timing);
+    Ok(())
+}
+
+// 剪贴板模式自动粘贴：各系统的覆盖值、系统默认值和当前生效的值
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct AutoPasteSettings {
+    pub overrides: input::AutoPasteOverrides,
+    pub os_default: bool,
+    pub effective: bool,
+}
+
+#[tauri::command]
+fn get_auto_paste_settings() -> AutoPasteSettings {
+    AutoPasteSettings {
+        overrides: get_setting("clipboard_auto_paste"),
+        os_default: input::default_auto_paste(),
+        effective: input::auto_paste_enabled(),
+    }
+}
+
+#[tauri::command]
+fn set_auto_paste_overrides(overrides: input::AutoPasteOverrides) -> CommandResult<()> {
+    set_setting("clipboard_auto_paste", &overrides)?;
+    log::info!("Auto-paste overrides set to: {:?} (effective: {})", overrides, input::auto_paste_enabled());
+    Ok(())
+}
+
+// 剪贴板模式的"复制 + 输入"：复制到剪贴板后用键盘模拟输入文本，不发送粘贴按键
+#[tauri::command]
+fn get_clipboard_paste_fallback() -> bool {
+    get_setting("clipboard_paste_fallback")
+}
+
+#[tauri::command]
+fn set_clipboard_paste_fallback(enabled: bool) -> CommandResult<()> {
+    set_setting("clipboard_paste_fallback", &enabled)?;
+    log::info!("Clipboard paste fallback set to: {}", enabled);
+    Ok(())
+}
+
+// 预览文本处理规则对示例文本的效果，与实际转录走同一处理流程，但不输出、不记录历史；
+// language 模拟 ASR 返回的语言，未提供时与实际转录一样按文字构成推断
+#[tauri::command]
+fn preview_replacements(sample: String, language: Option<String>) -> CommandResult<text::TranscriptOutcome> {
+    let language = language.or_else(|| text::detect_script_language(&sample));
+    let processed = text::process_transcript(&sample, language.as_deref());
+    if let Some(e) = processed.strip_error {
+        return Err(CommandError::invalid_input(e));
+    }
+    Ok(match processed.outcome {
+        text::TranscriptOutcome::Text(text) => text::TranscriptOutcome::Text(text::format_transcript(&text, false, None).output),
+        outcome => outcome,
+    })
+}
+
+// 键盘模式下超过该长度的文本改用剪贴板粘贴，None 表示不限制
+#[tauri::command]
+fn get_keyboard_max_length() -> Option<usize> {
+    get_setting("keyboard_max_length")
+}
+
+#[tauri::command]
+fn set_keyboard_max_length(max_length: Option<usize>) -> CommandResult<()> {
+    if max_length == Some(0) {
+        return Err(CommandError::invalid_input("Keyboard max length must be greater than 0"));
+    }
+    set_setting("keyboard_max_length", &max_length)?;
+    log::info!("Keyboard max length set to: {:?}", max_length);
+    Ok(())
+}
+
+// 长文本改用剪贴板时是否发送系统通知
+#[tauri::command]
+fn get_keyboard_fallback_notify() -> bool {
+    get_setting("keyboard_fallback_notify")
+}
+
+#[tauri::command]
+fn set_keyboard_fallback_notify(enabled: bool) -> CommandResult<()> {
+    set_setting("keyboard_fallback_notify", &enabled)?;
+    log::info!("Keyboard fallback notify set to: {}", enabled);
+    Ok(())
+}
+
+// 去掉转录结果的首尾空白（默认开启）
+#[tauri::command]
+fn get_trim_transcript() -> bool {
+    get_setting::<Option<bool>>("trim_transcript").unwrap_or(true)
+}
+
+#[tauri::command]
+fn set_trim_transcript(enabled: bool) -> CommandResult<()> {
+    set_setting("trim_transcript", &enabled)?;
+    log::info!("Trim transcript set to: {}", enabled);
+    Ok(())
+}
+
+// 转录后删除匹配的正则
+#[tauri::command]
+fn get_strip_patterns() -> Vec<String> {
+    get_setting("strip_patterns")
+}
+
+#[tauri::command]
+fn set_strip_patterns(patterns: Vec<String>) -> CommandResult<()> {
+    text::compile_patterns(&patterns)?;
+    set_setting("strip_patterns", &patterns)?;
+    log::info!("Strip patterns set: {} patterns", patterns.len());
+    Ok(())
+}
+
+// 替换规则按语言分组管理：language 为空时对应全局规则
+#[tauri::command]
+fn get_replacement_rules(language: Option<String>) -> Vec<text::ReplacementRule> {
+    let language = text::normalize_language_tag(language);
+    get_setting::<Vec<text::ReplacementRule>>("replacement_rules")
+        .into_iter()
+        .filter(|rule| rule.language == language)
+        .collect()
+}
+
+// 替换指定语言的全部规则，其他语言的规则保持不变
+#[tauri::command]
+fn set_replacement_rules(language: Option<String>, rules: Vec<text::ReplacementRule>) -> CommandResult<()> {
+    if rules.iter().any(|rule| rule.from.is_empty()) {
+        return Err(CommandError::invalid_input("Replacement source text cannot be empty"));
+    }
+    let language = text::normalize_language_tag(language);
+    let mut all: Vec<text::ReplacementRule> = get_setting("replacement_rules");
+    all.retain(|rule| rule.language != language);
+    let count = rules.len();
+    all.extend(rules.into_iter().map(|rule| text::ReplacementRule { language: language.clone(), ..rule }));
+    set_setting("replacement_rules", &all)?;
+    log::info!("Replacement rules set for {:?}: {} rules", language, count);
+    Ok(())
+}
+
+// 导出删除规则、口述标点词表和替换规则，便于团队共享
+#[tauri::command]
+fn export_replacements(path: String) -> CommandResult<()> {
+    text::export_rules(std::path::Path::new(&path))?;
+    log::info!("Text rules exported to: {}", path);
+    Ok(())
+}
+
+// 导入规则文件，merge 为 false 时替换现有规则；无效正则会被跳过并在结果中列出
+#[tauri::command]
+fn import_replacements(path: String, merge: bool) -> CommandResult<text::RuleImportReport> {
+    let report = text::import_rules(std::path::Path::new(&path), merge)?;
+    log::info!(
+        "Text rules imported from {}: {} patterns, {} punctuation words, {} replacements, {} invalid",
+        path, report.strip_patterns, report.spoken_punctuation, report.replacements, report.invalid.len()
+    );
+    Ok(report)
+}
+
+// 校验正则，返回第一个无效规则的错误
+#[tauri::command]
+fn validate_patterns(patterns: Vec<String>) -> CommandResult<()> {
+    text::compile_patterns(&patterns).map(|_| ()).map_err(Into::into)
+}
+
+// 命令模式：开关与短语→动作映射
+#[tauri::command]
+fn get_command_mode() -> voice_command::CommandModeSettings {
+    get_setting("command_mode")
+}
+
+#[tauri::command]
+fn set_command_mode(settings: voice_command::CommandModeSettings) -> CommandResult<()> {
+    let mut phrases = HashMap::new();
+    for (phrase, action) in settings.phrases {
+        let normalized = voice_command::normalize_phrase(&phrase);
+        if normalized.is_empty() {
+            return Err(CommandError::invalid_input(format!("Invalid command phrase: {:?}", phrase)));
+        }
+        phrases.insert(normalized, action);
+    }
+
+    let settings = voice_command::CommandModeSettings { enabled: settings.enabled, phrases };
+    set_setting("command_mode", &settings)?;
+    log::info!("Command mode set: enabled={}, {} phrases", settings.enabled, settings.phrases.len());
+    Ok(())
+}
+
+#[tauri::command]
+fn get_language_mode() -> LanguageMode {
+    get_setting("language_mode")
+}
+
+#[tauri::command]
+fn set_language_mode(state: tauri::State<'_, AppState>, mode: LanguageMode) -> CommandResult<()> {
+    set_setting("language_mode", &mode)?;
+    // 切换模式后重新开始记忆
+    *state.last_language.lock().map_err(|e| e.to_string())? = None;
+    log::info!("Language mode set to: {:?}", mode);
+    Ok(())
+}
+
+// 翻译设置
+#[tauri::command]
+fn get_translation_settings() -> TranslationSettings {
+    get_setting("translation")
+}
+
+#[tauri::command]
+fn set_translation_settings(settings: TranslationSettings) -> CommandResult<()> {
+    if let Some(ref endpoint) = settings.endpoint {
+        if !endpoint.starts_with("http://") && !endpoint.starts_with("https://") {
+            return Err(CommandError::invalid_input(format!("Invalid translation endpoint: {}", endpoint)));
+        }
+    }
+    set_setting("translation", &settings)?;
+    log::info!("Translation set to: {:?}", settings.translate_to);
+    Ok(())
+}
+
+// 按当前上下文（前台应用、录音模式）解析后，下一次录音实际会使用的设置
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct EffectiveConfig {
+    pub frontmost_app: Option<String>,
+    pub hotkey: HotkeyConfig,
+    pub recording_mode: RecordingMode,
+    pub output_mode: OutputMode,
+    pub output_target: OutputTarget,
+    pub keyboard_max_length: Option<usize>,
+    pub capture_source: audio::CaptureSource,
+    pub language_hint: Option<String>,
+    pub translate_to: Option<String>,
+    pub confirm_before_output: bool,  // 前台应用在 confirm_in_apps 中
+    pub markdown_assist: bool,        // 前台应用启用了 Markdown 辅助
+    pub trim_transcript: bool,
+    pub spoken_punctuation: bool,
+    pub filler_word_filter: bool,
+    pub dedupe_consecutive: bool,
+    pub notify_on_transcript: bool,
+}
+
+#[tauri::command]
+fn get_effective_config(state: tauri::State<'_, AppState>) -> CommandResult<EffectiveConfig> {
+    let frontmost_app = focus::get_frontmost_app();
+    let in_frontmost = |apps: &[String]| frontmost_app.as_ref().is_some_and(|id| apps.contains(id));
+
+    let markdown: text::MarkdownAssist = get_setting("markdown_assist");
+    let confirm_apps: Vec<String> = get_setting("confirm_in_apps");
+    let output_mode = *state.output_mode.lock().map_err(|e| e.to_string())?;
+    let recording_mode = *state.recording_mode.lock().map_err(|e| e.to_string())?;
+
+    Ok(EffectiveConfig {
+        hotkey: get_hotkey_config()?,
+        recording_mode,
+        output_mode,
+        output_target: get_setting("output_target"),
+        keyboard_max_length: get_setting("keyboard_max_length"),
+        capture_source: get_setting("capture_source"),
+        language_hint: resolve_language_hint(&state),
+        translate_to: get_setting::<TranslationSettings>("translation").translate_to,
+        confirm_before_output: in_frontmost(&confirm_apps),
+        markdown_assist: markdown.enabled && in_frontmost(&markdown.apps),
+        trim_transcript: get_setting::<Option<bool>>("trim_transcript").unwrap_or(true),
+        spoken_punctuation: get_setting::<text::SpokenPunctuation>("spoken_punctuation").enabled,
+        filler_word_filter: get_setting::<text::FillerWordFilter>("filler_word_filter").enabled,
+        dedupe_consecutive: get_setting("dedupe_consecutive"),
+        notify_on_transcript: get_setting("notify_on_transcript"),
+        frontmost_app,
+    })
+}
+
+// 运行时状态快照，用于复现问题和测试模式/焦点逻辑；录音器、sidecar 进程等句柄不包含在内
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct StateSnapshot {
+    pub output_mode: OutputMode,
+    pub recording_mode: RecordingMode,
+    pub previous_app: Option<String>,
+    pub continue_last: bool,
+    pub last_language: Option<String>,
+    pub last_output: Option<String>,
+    // 以下字段只读，恢复时忽略
+    #[serde(default)]
+    pub hotkey: HotkeyConfig,
+    #[serde(default)]
+    pub is_recording: bool,
+    #[serde(default)]
+    pub recording_session: u64,
+    #[serde(default)]
+    pub last_transcript: Option<String>,
+    #[serde(default)]
+    pub pending_output: Option<String>,
+    #[serde(default)]
+    pub sidecar_ready: bool,
+    #[serde(default)]
+    pub sidecar_failed: bool,
+    #[serde(default)]
+    pub recent_errors: Vec<RecentError>,
+}
+
+#[tauri::command]
+fn snapshot_state(state: tauri::State<'_, AppState>) -> CommandResult<StateSnapshot> {
+    Ok(StateSnapshot {
+        output_mode: *state.output_mode.lock().map_err(|e| e.to_string())?,
+        recording_mode: *state.recording_mode.lock().map_err(|e| e.to_string())?,
+        previous_app: state.previous_app.lock().map_err(|e| e.to_string())?.clone(),
+        continue_last: *state.continue_last.lock().map_err(|e| e.to_string())?,
+        last_language: state.last_language.lock().map_err(|e| e.to_string())?.clone(),
+        last_output: state.last_output.lock().map_err(|e| e.to_string())?.clone(),
+        hotkey: get_hotkey_config()?,
+        is_recording: *state.is_recording.lock().map_err(|e| e.to_string())?,
+        recording_session: *state.recording_session.lock().map_err(|e| e.to_string())?,
+        last_transcript: state.last_transcript.lock().map_err(|e| e.to_string())?
+            .as_ref()
+            .map(|(text, _)| text.clone()),
+        pending_output: state.pending_output.lock().map_err(|e| e.to_string())?
+            .as_ref()
+            .map(|pending| pending.text.clone()),
+        sidecar_ready: sidecar::is_ready(),
+        sidecar_failed: sidecar::has_failed(),
+        recent_errors: state.recent_errors.lock().map_err(|e| e.to_string())?.iter().cloned().collect(),
+    })
+}
+
+// 恢复快照中可安全设置的字段；录音中不允许恢复，避免打乱正在进行的会话
+#[tauri::command]
+fn restore_state(app_handle: tauri::AppHandle, snapshot: StateSnapshot) -> CommandResult<()> {
+    let state = app_handle.state::<AppState>();
+    if *state.is_recording.lock().map_err(|e| e.to_string())? {
+        return Err(CommandError::not_ready("Cannot restore state while recording"));
+    }
+
+    apply_output_mode(&app_handle, snapshot.output_mode)?;
+    apply_recording_mode(&app_handle, snapshot.recording_mode)?;
+    *state.previous_app.lock().map_err(|e| e.to_string())? = snapshot.previous_app;
+    *state.continue_last.lock().map_err(|e| e.to_string())? = snapshot.continue_last;
+    *state.last_language.lock().map_err(|e| e.to_string())? = snapshot.last_language;
+    *state.last_output.lock().map_err(|e| e.to_string())? = snapshot.last_output;
+
+    log::info!("Restored app state snapshot");
+    Ok(())
+}
+
+// 根据语言模式计算本次录音发送给 ASR 的语言提示
+pub fn resolve_language_hint(state: &AppState) -> Option<String> {
+    match get_setting::<LanguageMode>("language_mode") {
+        LanguageMode::Fixed(language) => Some(language),
+        LanguageMode::Auto => None,
+        LanguageMode::Sticky => state.last_language.lock().ok().and_then(|l| l.clone()),
+    }
+}
+
+#[tauri::command]
+fn stop_recording(app_handle: tauri::AppHandle, action: Option<StopAction>) -> CommandResult<()> {
+    let action = action.unwrap_or_default();
+    log::info!("stop_recording called with action: {:?}", action);
+    let result = match action {
+        StopAction::Confirm => hotkey::stop_recording_manually(&app_handle),
+        StopAction::Cancel => hotkey::cancel_recording_manually(&app_handle),
+    };
+    result.map_err(Into::into)
+}
+
+#[tauri::command]
+fn cancel_recording(app_handle: tauri::AppHandle) -> CommandResult<()> {
+    hotkey::cancel_recording_manually(&app_handle).map_err(Into::into)
+}
+
+// 强制重置卡住的录音状态
+#[tauri::command]
+fn force_reset_recording_state(app_handle: tauri::AppHandle) {
+    hotkey::force_reset_recording_state(&app_handle);
+}
+
+// 模拟一次转录（统计、历史、可选输出和事件），不需要麦克风和 ASR；只编译进 debug 构建
+#[cfg(debug_assertions)]
+#[tauri::command]
+fn inject_fake_transcript(app_handle: tauri::AppHandle, text: String, output: bool) -> CommandResult<()> {
+    if text.trim().is_empty() {
+        return Err(CommandError::invalid_input("Transcript text is empty"));
+    }
+    hotkey::inject_transcript(&app_handle, text, output);
+    Ok(())
+}
+
+fn get_config_path() -> PathBuf {
+    let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+    home.join(".mouth-high").join("config.json")
+}
+
+// 保留录音的存放目录
+pub fn get_recordings_dir() -> PathBuf {
+    let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+    home.join(".mouth-high").join("recordings")
+}
+
+// 读取完整配置，不存在或解析失败时返回空对象；旧版本配置在内存中升级到当前结构
+fn load_config() -> serde_json::Value {
+    let config_path = get_config_path();
+    if !config_path.exists() {
+        return json!({});
+    }
+    let content = fs::read_to_string(&config_path).unwrap_or_else(|_| "{}".to_string());
+    let mut config = serde_json::from_str(&content).unwrap_or_else(|_| json!({}));
+    migrate::migrate(&mut config);
+    config
+}
+
+// 启动时升级配置文件并写回，之后直接读取文件的代码也能拿到当前结构
+fn migrate_config_file() {
+    let config_path = get_config_path();
+    if !config_path.exists() {
+        return;
+    }
+    let content = match fs::read_to_string(&config_path) {
+        Ok(content) => content,
+        Err(e) => {
+            log::warn!("Failed to read config for migration: {}", e);
+            return;
+        }
+    };
+    let Ok(mut config) = serde_json::from_str::<serde_json::Value>(&content) else {
+        log::warn!("Config is not valid JSON, skipping migration");
+        return;
+    };
+
+    let applied = migrate::migrate(&mut config);
+    if applied.is_empty() {
+        return;
+    }
+    match save_config(&config) {
+        Ok(()) => log::info!("Config migrated to schema v{}: {}", migrate::CONFIG_SCHEMA_VERSION, applied.join(", ")),
+        Err(e) => log::warn!("Failed to save migrated config: {}", e),
+    }
+}
+
+// 导入其他设备或旧版本导出的设置文件：先升级到当前结构并校验，再替换当前配置。
+// 返回执行过的迁移
+#[tauri::command]
+fn import_settings(path: String) -> CommandResult<Vec<String>> {
+    let content = fs::read_to_string(&path)
+        .map_err(|e| CommandError::io(format!("Failed to read settings: {}", e)))?;
+    let mut config: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse settings: {}", e))?;
+    if !config.is_object() {
+        return Err(CommandError::invalid_input("Settings file must contain a JSON object"));
+    }
+
+    let applied = migrate::migrate(&mut config);
+    if let Some(history) = config.get("history") {
+        serde_json::from_value::<Vec<HistoryItem>>(history.clone())
+            .map_err(|e| format!("Invalid history in settings: {}", e))?;
+    }
+    if let Some(hotkey) = config.get("hotkey") {
+        serde_json::from_value::<HotkeyConfig>(hotkey.clone())
+            .map_err(|e| format!("Invalid hotkey in settings: {}", e))?;
+    }
+
+    // 导入文件中的历史记录合并进 history.jsonl
+    history::migrate_from_config(&mut config)?;
+    save_config(&config)?;
+    log::info!("Settings imported from {} (migrations: {:?})", path, applied);
+    Ok(applied)
+}
+
+// 先写入同目录下的临时文件再重命名，避免写入中断留下不完整的文件
+pub fn write_atomic(path: &std::path::Path, content: &[u8]) -> Result<(), String> {
+    let dir = path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or_else(|| std::path::Path::new("."));
+    fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create directory: {}", e))?;
+
+    let mut temp_file = tempfile::NamedTempFile::new_in(dir)
+        .map_err(|e| format!("Failed to create temp file: {}", e))?;
+    std::io::Write::write_all(&mut temp_file, content)
+        .map_err(|e| format!("Failed to write file: {}", e))?;
+    temp_file
+        .persist(path)
+        .map_err(|e| format!("Failed to write file: {}", e))?;
+    Ok(())
+}
+
+fn save_config(config: &serde_json::Value) -> Result<(), String> {
+    let config_path = get_config_path();
+
+    // Create directory if needed
+    if let Some(parent) = config_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+
+    let content = serde_json::to_string_pretty(config)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+
+    write_atomic(&config_path, content.as_bytes())
+}
+
+// 读取单个设置项，缺失或格式不对时使用默认值
+pub fn get_setting<T: serde::de::DeserializeOwned + Default>(key: &str) -> T {
+    load_config()
+        .get(key)
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default()
+}
+
+pub fn set_setting<T: serde::Serialize>(key: &str, value: &T) -> Result<(), String> {
+    let mut config = load_config();
+    config[key] = serde_json::to_value(value)
+        .map_err(|e| format!("Failed to serialize {}: {}", key, e))?;
+    save_config(&config)
+}
+
+#[tauri::command]
+fn get_api_key() -> CommandResult<Option<String>> {
+    let config_path = get_config_path();
+    if !config_path.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(&config_path)
+        .map_err(|e| CommandError::io(format!("Failed to read config: {}", e)))?;
+
+    let config: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse config: {}", e))?;
+
+    Ok(config.get("dashscope_api_key")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string()))
+}
+
+#[tauri::command]
+fn set_api_key(api_key: String) -> CommandResult<()> {
+    let config_path = get_config_path();
+
+    // Create directory if needed
+    if let Some(parent) = config_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| CommandError::io(format!("Failed to create config directory: {}", e)))?;
+    }
+
+    // Read existing config or create new
+    let mut config: serde_json::Value = if config_path.exists() {
+        let content = fs::read_to_string(&config_path).unwrap_or_else(|_| "{}".to_string());
+        serde_json::from_str(&content).unwrap_or_else(|_| json!({}))
+    } else {
+        json!({})
+    };
+
+    // Update API key
+    config["dashscope_api_key"] = json!(api_key);
+
+    // Write back
+    let content = serde_json::to_string_pretty(&config)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+
+    fs::write(&config_path, content)
+        .map_err(|e| CommandError::io(format!("Failed to write config: {}", e)))?;
+
+    log::info!("API key saved to {:?}", config_path);
+    Ok(())
+}
+
+#[tauri::command]
+fn is_api_key_configured() -> bool {
+    match get_api_key() {
+        Ok(Some(key)) => !key.is_empty(),
+        _ => false,
+    }
+}
+
+// 获取快捷键配置
+#[tauri::command]
+fn get_hotkey_config() -> CommandResult<HotkeyConfig> {
+    let config_path = get_config_path();
+    if !config_path.exists() {
+        // 返回默认配置
+        return Ok(HotkeyConfig {
+            modifiers: vec!["ctrl".to_string(), "shift".to_string()],
+            key: "r".to_string(),
+            mode: None,
+        });
+    }
+
+    let content = fs::read_to_string(&config_path)
+        .map_err(|e| CommandError::io(format!("Failed to read config: {}", e)))?;
+
+    let config: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse config: {}", e))?;
+
+    let hotkey_config: HotkeyConfig = config.get("hotkey")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_else(|| HotkeyConfig {
+            modifiers: vec!["ctrl".to_string(), "shift".to_string()],
+            key: "r".to_string(),
+            mode: None,
+        });
+
+    Ok(hotkey_config)
+}
+
+// 设置快捷键配置
+#[tauri::command]
+fn set_hotkey_config(config: HotkeyConfig) -> CommandResult<()> {
+    let config_path = get_config_path();
+
+    // Create directory if needed
+    if let Some(parent) = config_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| CommandError::io(format!("Failed to create config directory: {}", e)))?;
+    }
+
+    // Read existing config or create new
+    let mut full_config: serde_json::Value = if config_path.exists() {
+        let content = fs::read_to_string(&config_path).unwrap_or_else(|_| "{}".to_string());
+        serde_json::from_str(&content).unwrap_or_else(|_| json!({}))
+    } else {
+        json!({})
+    };
+
+    // Update hotkey config
+    full_config["hotkey"] = serde_json::to_value(&config)
+        .map_err(|e| format!("Failed to serialize hotkey config: {}", e))?;
+
+    // Write back
+    let content = serde_json::to_string_pretty(&full_config)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+
+    fs::write(&config_path, content)
+        .map_err(|e| CommandError::io(format!("Failed to write config: {}", e)))?;
+
+    log::info!("Hotkey config saved: {}", config.to_display_string());
+    Ok(())
+}
+
+// 更新快捷键并重新注册
+#[tauri::command]
+fn update_hotkey(app_handle: tauri::AppHandle, config: HotkeyConfig) -> CommandResult<()> {
+    hotkey::update_hotkey(&app_handle, &config).map_err(Into::into)
+}
+
+// 不修改配置，重新注册当前快捷键（辅助功能权限变动后快捷键失效时使用）
+#[tauri::command]
+fn reregister_hotkey(app_handle: tauri::AppHandle) -> CommandResult<()> {
+    hotkey::reregister_hotkey(&app_handle).map_err(Into::into)
+}
+
+// 快捷键可使用的按键名称
+#[tauri::command]
+fn get_supported_keys() -> Vec<String> {
+    hotkey::supported_keys()
+}
+
+// 跳过时间窗口内与上一次完全相同的转录（麦克风重复触发）
+#[tauri::command]
+fn get_dedupe_consecutive() -> bool {
+    get_setting("dedupe_consecutive")
+}
+
+#[tauri::command]
+fn set_dedupe_consecutive(enabled: bool) -> CommandResult<()> {
+    set_setting("dedupe_consecutive", &enabled)?;
+    log::info!("Dedupe consecutive transcripts set to: {}", enabled);
+    Ok(())
+}
+
+#[tauri::command]
+fn get_dedupe_window_secs() -> u64 {
+    get_setting::<Option<u64>>("dedupe_window_secs").unwrap_or(hotkey::DEFAULT_DEDUPE_WINDOW_SECS)
+}
+
+#[tauri::command]
+fn set_dedupe_window_secs(secs: u64) -> CommandResult<()> {
+    if secs == 0 || secs > 3600 {
+        return Err(CommandError::invalid_input("Dedupe window must be between 1 and 3600 seconds"));
+    }
+    set_setting("dedupe_window_secs", &secs)?;
+    log::info!("Dedupe window set to: {}s", secs);
+    Ok(())
+}
+
+// 转录完成后是否发送系统通知
+#[tauri::command]
+fn get_notify_on_transcript() -> bool {
+    get_setting("notify_on_transcript")
+}
+
+#[tauri::command]
+fn set_notify_on_transcript(enabled: bool) -> CommandResult<()> {
+    set_setting("notify_on_transcript", &enabled)?;
+    log::info!("Notify on transcript set to: {}", enabled);
+    Ok(())
+}
+
+// Hold 模式松开防抖（毫秒），松开后在此时间内再次按下不会停止录音；0 表示关闭
+#[tauri::command]
+fn get_hold_release_debounce_ms() -> u64 {
+    get_setting::<Option<u64>>("hold_release_debounce_ms").unwrap_or(hotkey::DEFAULT_HOLD_RELEASE_DEBOUNCE_MS)
+}
+
+#[tauri::command]
+fn set_hold_release_debounce_ms(ms: u64) -> CommandResult<()> {
+    if ms > 1000 {
+        return Err(CommandError::invalid_input("Release debounce must be at most 1000 ms"));
+    }
+    set_setting("hold_release_debounce_ms", &ms)?;
+    log::info!("Hold release debounce set to: {}ms", ms);
+    Ok(())
+}
+
+// Toggle 模式两次按下之间的最短间隔（毫秒），间隔内的按下视为按键抖动
+#[tauri::command]
+fn get_toggle_cooldown_ms() -> u64 {
+    get_setting::<Option<u64>>("toggle_cooldown_ms").unwrap_or(hotkey::DEFAULT_TOGGLE_COOLDOWN_MS)
+}
+
+#[tauri::command]
+fn set_toggle_cooldown_ms(ms: u64) -> CommandResult<()> {
+    if ms > 2000 {
+        return Err(CommandError::invalid_input("Toggle cooldown must be at most 2000 ms"));
+    }
+    set_setting("toggle_cooldown_ms", &ms)?;
+    log::info!("Toggle cooldown set to: {}ms", ms);
+    Ok(())
+}
+
+// 恢复宽限期内刚被取消的录音
+#[tauri::command]
+fn undo_cancel(app_handle: tauri::AppHandle) -> CommandResult<()> {
+    hotkey::undo_cancel(&app_handle).map_err(Into::into)
+}
+
+// 取消录音后可撤销的宽限期（毫秒），0 表示立即丢弃
+#[tauri::command]
+fn get_cancel_grace_ms() -> u64 {
+    get_setting::<Option<u64>>("cancel_grace_ms").unwrap_or(hotkey::DEFAULT_CANCEL_GRACE_MS)
+}
+
+#[tauri::command]
+fn set_cancel_grace_ms(ms: u64) -> CommandResult<()> {
+    if ms > 60_000 {
+        return Err(CommandError::invalid_input("Cancel grace period must be at most 60000 ms"));
+    }
+    set_setting("cancel_grace_ms", &ms)?;
+    log::info!("Cancel grace period set to: {}ms", ms);
+    Ok(())
+}
+
+// 最近一次录音的诊断信息（峰值、削波比例等）
+#[tauri::command]
+fn get_last_recording_diagnostics() -> Option<audio::RecordingDiagnostics> {
+    audio::last_diagnostics()
+}
+
+// 削波样本比例阈值，超过时发出 recording-clipped
+#[tauri::command]
+fn get_clip_threshold() -> f32 {
+    get_setting::<Option<f32>>("clip_threshold").unwrap_or(audio::DEFAULT_CLIP_THRESHOLD)
+}
+
+#[tauri::command]
+fn set_clip_threshold(threshold: f32) -> CommandResult<()> {
+    if !(threshold > 0.0 && threshold <= 1.0) {
+        return Err(CommandError::invalid_input("Clip threshold must be between 0 and 1"));
+    }
+    set_setting("clip_threshold", &threshold)?;
+    log::info!("Clip threshold set to: {}", threshold);
+    Ok(())
+}
+
+// 录音开头丢弃的时长（毫秒），用于去掉设备启动时的爆音，0 表示不丢弃
+#[tauri::command]
+fn get_warmup_discard_ms() -> u64 {
+    get_setting("warmup_discard_ms")
+}
+
+#[tauri::command]
+fn set_warmup_discard_ms(ms: u64) -> CommandResult<()> {
+    if ms > audio::MAX_WARMUP_DISCARD_MS {
+        return Err(CommandError::invalid_input(format!("Warm-up discard must be at most {} ms", audio::MAX_WARMUP_DISCARD_MS)));
+    }
+    set_setting("warmup_discard_ms", &ms)?;
+    log::info!("Warm-up discard set to: {} ms", ms);
+    Ok(())
+}
+
+// 获取额外的录音快捷键
+#[tauri::command]
+fn get_extra_hotkeys() -> Vec<HotkeyConfig> {
+    hotkey::get_extra_hotkeys()
+}
+
+// 设置额外的录音快捷键（每个可绑定自己的录音模式），并重新注册
+#[tauri::command]
+fn set_extra_hotkeys(app_handle: tauri::AppHandle, hotkeys: Vec<HotkeyConfig>) -> CommandResult<()> {
+    hotkey::set_extra_hotkeys(&app_handle, hotkeys).map_err(Into::into)
+}
+
+// 获取附加功能快捷键
+#[tauri::command]
+fn get_action_hotkeys() -> HashMap<hotkey::HotkeyAction, HotkeyConfig> {
+    hotkey::get_action_hotkeys()
+}
+
+// 设置附加功能快捷键，传入 None 表示清除
+#[tauri::command]
+fn set_action_hotkey(app_handle: tauri::AppHandle, action: hotkey::HotkeyAction, config: Option<HotkeyConfig>) -> CommandResult<()> {
+    hotkey::set_action_hotkey(&app_handle, action, config).map_err(Into::into)
+}
+
+// 输出前需要确认的应用（bundle id）
+#[tauri::command]
+fn get_confirm_in_apps() -> Vec<String> {
+    get_setting("confirm_in_apps")
+}
+
+#[tauri::command]
+fn set_confirm_in_apps(apps: Vec<String>) -> CommandResult<()> {
+    set_setting("confirm_in_apps", &apps)?;
+    log::info!("Confirm in apps set to: {:?}", apps);
+    Ok(())
+}
+
+// 切回目标应用并输出，不在主线程执行
+#[tauri::command]
+async fn approve_output(app_handle: tauri::AppHandle) -> CommandResult<()> {
+    hotkey::approve_output(&app_handle).map_err(Into::into)
+}
+
+#[tauri::command]
+fn reject_output(app_handle: tauri::AppHandle) -> CommandResult<()> {
+    hotkey::reject_output(&app_handle).map_err(Into::into)
+}
+
+// 禁止听写的应用（bundle id）
+#[tauri::command]
+fn get_disabled_apps() -> Vec<String> {
+    get_setting("disabled_apps")
+}
+
+#[tauri::command]
+fn set_disabled_apps(apps: Vec<String>) -> CommandResult<()> {
+    set_setting("disabled_apps", &apps)?;
+    log::info!("Disabled apps set to: {:?}", apps);
+    Ok(())
+}
+
+#[tauri::command]
+fn add_disabled_app(bundle_id: String) -> CommandResult<()> {
+    let mut apps = get_disabled_apps();
+    if !apps.contains(&bundle_id) {
+        apps.push(bundle_id);
+    }
+    set_disabled_apps(apps)
+}
+
+#[tauri::command]
+fn remove_disabled_app(bundle_id: String) -> CommandResult<()> {
+    let mut apps = get_disabled_apps();
+    apps.retain(|app| app != &bundle_id);
+    set_disabled_apps(apps)
+}
+
+// 前台应用全屏时不录音（默认关闭）
+#[tauri::command]
+fn get_suppress_in_fullscreen() -> bool {
+    get_setting("suppress_in_fullscreen")
+}
+
+#[tauri::command]
+fn set_suppress_in_fullscreen(enabled: bool) -> CommandResult<()> {
+    set_setting("suppress_in_fullscreen", &enabled)?;
+    log::info!("Suppress in fullscreen set to: {}", enabled);
+    Ok(())
+}
+
+// 鼠标侧键快捷键（4/5），None 表示未启用
+#[tauri::command]
+fn get_mouse_button() -> Option<u8> {
+    get_setting("mouse_button")
+}
+
+#[tauri::command]
+fn set_mouse_button(button: Option<u8>) -> CommandResult<()> {
+    if let Some(b) = button {
+        if !(4..=5).contains(&b) {
+            return Err(CommandError::invalid_input(format!("Unsupported mouse button: {}", b)));
+        }
+        if !cfg!(feature = "mouse-hotkey") {
+            return Err(CommandError::unsupported("Mouse button hotkeys are not available in this build"));
+        }
+    }
+
+    set_setting("mouse_button", &button)?;
+
+    #[cfg(feature = "mouse-hotkey")]
+    mouse::set_button(button);
+
+    Ok(())
+}
+
+// 录音来源：麦克风或系统声音（回环）
+#[tauri::command]
+fn get_capture_source() -> audio::CaptureSource {
+    get_setting("capture_source")
+}
+
+#[tauri::command]
+fn set_capture_source(source: audio::CaptureSource) -> CommandResult<()> {
+    if source == audio::CaptureSource::Loopback {
+        // 确认当前平台和构建能找到回环设备
+        audio::loopback_device()?;
+    }
+    set_setting("capture_source", &source)?;
+    log::info!("Capture source set to: {:?}", source);
+    Ok(())
+}
+
+#[tauri::command]
+fn list_input_devices() -> CommandResult<Vec<String>> {
+    audio::list_input_devices().map_err(Into::into)
+}
+
+// 回环来源使用的输入设备（macOS 上的虚拟声卡、Linux 上的 monitor 设备）
+#[tauri::command]
+fn get_loopback_device() -> Option<String> {
+    get_setting("loopback_device")
+}
+
+// 传入 None 表示自动选择（仅 Linux 可自动找到 monitor 设备）
+#[tauri::command]
+fn set_loopback_device(name: Option<String>) -> CommandResult<()> {
+    if let Some(ref name) = name {
+        if !audio::list_input_devices()?.contains(name) {
+            return Err(CommandError::not_found(format!("Input device not found: {}", name)));
+        }
+    }
+    set_setting("loopback_device", &name)?;
+    log::info!("Loopback device set to: {:?}", name);
+    Ok(())
+}
+
+#[tauri::command]
+fn get_usage_stats() -> CommandResult<UsageStats> {
+    let config_path = get_config_path();
+    if !config_path.exists() {
+        return Ok(UsageStats::default());
+    }
+
+    let content = fs::read_to_string(&config_path)
+        .map_err(|e| CommandError::io(format!("Failed to read config: {}", e)))?;
+
+    let config: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse config: {}", e))?;
+
+    let mut stats: UsageStats = config.get("stats")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default();
+
+    // Check if we need to reset today's stats
+    let today = Local::now().format("%Y-%m-%d").to_string();
+    if stats.today_date != today {
+        stats.today_characters = 0;
+        stats.today_date = today;
+    }
+
+    Ok(stats)
 }
 
 pub fn update_usage_stats(char_count: usize) -> Result<(), String> {
@@ -360,10 +1891,18 @@ pub fn update_usage_stats(char_count: usize) -> Result<(), String> {
         stats.today_date = today;
     }
 
+    // 进入新的计费周期时重新累计
+    let period_start = current_period_start(get_setting::<QuotaSettings>("quota").reset_day);
+    if stats.period_start != period_start {
+        stats.period_characters = 0;
+        stats.period_start = period_start;
+    }
+
     // Update stats
     stats.total_characters += char_count as u64;
     stats.total_transcriptions += 1;
     stats.today_characters += char_count as u64;
+    stats.period_characters += char_count as u64;
 
     // Save back
     config["stats"] = serde_json::to_value(&stats)
@@ -381,140 +1920,443 @@ pub fn update_usage_stats(char_count: usize) -> Result<(), String> {
     Ok(())
 }
 
-// 添加历史记录
-pub fn add_history_item(text: &str) -> Result<(), String> {
-    let config_path = get_config_path();
-    
-    // Create directory if needed
-    if let Some(parent) = config_path.parent() {
-        fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed to create config directory: {}", e))?;
-    }
-    
-    // Read existing config or create new
-    let mut config: serde_json::Value = if config_path.exists() {
-        let content = fs::read_to_string(&config_path).unwrap_or_else(|_| "{}".to_string());
-        serde_json::from_str(&content).unwrap_or_else(|_| json!({}))
+// 当前计费周期的开始日期（YYYY-MM-DD）
+fn current_period_start(reset_day: u32) -> String {
+    use chrono::{Datelike, Months, NaiveDate};
+
+    let today = Local::now().date_naive();
+    let day = reset_day.clamp(1, 28);
+    let this_month = NaiveDate::from_ymd_opt(today.year(), today.month(), day).unwrap_or(today);
+    let start = if today >= this_month {
+        this_month
     } else {
-        json!({})
+        this_month.checked_sub_months(Months::new(1)).unwrap_or(this_month)
     };
-    
-    let now = Local::now();
-    let item = HistoryItem {
-        id: format!("{}", now.timestamp_millis()),
-        text: text.to_string(),
-        timestamp: now.timestamp(),
-        date: now.format("%Y-%m-%d").to_string(),
-        char_count: text.chars().count(),
+    start.format("%Y-%m-%d").to_string()
+}
+
+fn quota_estimate() -> Result<QuotaEstimate, String> {
+    let settings: QuotaSettings = get_setting("quota");
+    let stats = get_usage_stats()?;
+    let period_start = current_period_start(settings.reset_day);
+    let used = if stats.period_start == period_start { stats.period_characters } else { 0 };
+
+    Ok(QuotaEstimate {
+        period_start,
+        used_characters: used,
+        budget_characters: settings.budget_characters,
+        remaining_characters: settings.budget_characters.map(|budget| budget.saturating_sub(used)),
+    })
+}
+
+// 本次转录使用量越过预算提醒线时发送 quota-warning
+pub fn check_quota(app: &tauri::AppHandle, added: usize) {
+    let settings: QuotaSettings = get_setting("quota");
+    let Some(budget) = settings.budget_characters else {
+        return;
     };
-    
-    // Get existing history or create new
-    let mut history: Vec<HistoryItem> = config.get("history")
-        .and_then(|v| serde_json::from_value(v.clone()).ok())
-        .unwrap_or_default();
-    
-    // Add new item at the beginning
-    history.insert(0, item);
-    
-    // Clean up old records based on retention setting
-    let retention: HistoryRetention = config.get("history_retention")
-        .and_then(|v| serde_json::from_value(v.clone()).ok())
-        .unwrap_or_default();
-    
-    let cutoff_timestamp = match retention {
-        HistoryRetention::SevenDays => now.timestamp() - 7 * 24 * 60 * 60,
-        HistoryRetention::ThirtyDays => now.timestamp() - 30 * 24 * 60 * 60,
-        HistoryRetention::NinetyDays => now.timestamp() - 90 * 24 * 60 * 60,
-        HistoryRetention::Forever => 0,
+    let estimate = match quota_estimate() {
+        Ok(estimate) => estimate,
+        Err(e) => {
+            log::warn!("Failed to estimate quota: {}", e);
+            return;
+        }
     };
-    
-    if cutoff_timestamp > 0 {
-        history.retain(|item| item.timestamp >= cutoff_timestamp);
+
+    let threshold = (budget as f64 * settings.warn_ratio.clamp(0.0, 1.0)) as u64;
+    let before = estimate.used_characters.saturating_sub(added as u64);
+    if estimate.used_characters >= threshold && before < threshold {
+        log::warn!("Quota warning: {} of {} characters used", estimate.used_characters, budget);
+        let _ = app.emit("quota-warning", estimate);
     }
-    
-    // Save back
-    config["history"] = serde_json::to_value(&history)
-        .map_err(|e| format!("Failed to serialize history: {}", e))?;
-    
-    let content = serde_json::to_string_pretty(&config)
-        .map_err(|e| format!("Failed to serialize config: {}", e))?;
-    
-    fs::write(&config_path, content)
-        .map_err(|e| format!("Failed to write config: {}", e))?;
-    
-    log::info!("History item added: {} chars", text.chars().count());
+}
+
+#[tauri::command]
+fn get_quota_estimate() -> CommandResult<QuotaEstimate> {
+    quota_estimate().map_err(Into::into)
+}
+
+#[tauri::command]
+fn get_quota_settings() -> QuotaSettings {
+    get_setting("quota")
+}
+
+#[tauri::command]
+fn set_quota_settings(settings: QuotaSettings) -> CommandResult<()> {
+    if !(1..=28).contains(&settings.reset_day) {
+        return Err(CommandError::invalid_input("Reset day must be between 1 and 28"));
+    }
+    if !(0.0..=1.0).contains(&settings.warn_ratio) {
+        return Err(CommandError::invalid_input("Warn ratio must be between 0 and 1"));
+    }
+    set_setting("quota", &settings)?;
+    log::info!("Quota settings set to: {:?}", settings);
+    Ok(())
+}
+
+// 统计导出格式版本，字段变化时递增
+const STATS_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, serde::Serialize, Default)]
+pub struct DailyStats {
+    pub date: String,
+    pub transcriptions: u64,
+    pub characters: u64,
+}
+
+#[derive(Clone, Debug, serde::Serialize, Default)]
+pub struct LanguageStats {
+    pub language: String,
+    pub transcriptions: u64,
+    pub characters: u64,
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct StatsExport {
+    pub schema_version: u32,
+    pub exported_at: String,
+    pub total_characters: u64,
+    pub total_transcriptions: u64,
+    pub daily: Vec<DailyStats>,
+    pub languages: Vec<LanguageStats>,
+    pub average_duration_ms: Option<u64>,
+}
+
+// 根据累计统计和历史记录生成导出数据（每日、语言和时长只覆盖仍保留的历史记录）
+fn build_stats_export() -> Result<StatsExport, String> {
+    let stats = get_usage_stats()?;
+    let history = history::load()?;
+
+    let mut daily: std::collections::BTreeMap<String, DailyStats> = std::collections::BTreeMap::new();
+    let mut languages: std::collections::BTreeMap<String, LanguageStats> = std::collections::BTreeMap::new();
+    let mut duration_total = 0u64;
+    let mut duration_count = 0u64;
+
+    for item in history.iter() {
+        let day = daily.entry(item.date.clone()).or_insert_with(|| DailyStats {
+            date: item.date.clone(),
+            ..Default::default()
+        });
+        day.transcriptions += 1;
+        day.characters += item.char_count as u64;
+
+        let language = item.language.clone().unwrap_or_else(|| "unknown".to_string());
+        let lang = languages.entry(language.clone()).or_insert_with(|| LanguageStats {
+            language,
+            ..Default::default()
+        });
+        lang.transcriptions += 1;
+        lang.characters += item.char_count as u64;
+
+        if let Some(duration) = item.duration_ms {
+            duration_total += duration;
+            duration_count += 1;
+        }
+    }
+
+    Ok(StatsExport {
+        schema_version: STATS_SCHEMA_VERSION,
+        exported_at: Local::now().to_rfc3339(),
+        total_characters: stats.total_characters,
+        total_transcriptions: stats.total_transcriptions,
+        daily: daily.into_values().collect(),
+        languages: languages.into_values().collect(),
+        average_duration_ms: (duration_count > 0).then(|| duration_total / duration_count),
+    })
+}
+
+// 导出统计数据为 JSON 文件，供外部工具使用
+#[tauri::command]
+fn export_stats(path: String) -> CommandResult<()> {
+    let export = build_stats_export()?;
+    let content = serde_json::to_string_pretty(&export)
+        .map_err(|e| format!("Failed to serialize stats: {}", e))?;
+
+    write_atomic(std::path::Path::new(&path), content.as_bytes()).map_err(CommandError::io)?;
+
+    log::info!("Stats exported to: {}", path);
+    Ok(())
+}
+
+// 添加历史记录：只在 history.jsonl 末尾追加一行，过期记录由定期清理删除
+pub fn add_history_item(item: HistoryItem) -> Result<(), String> {
+    history::append(&item)?;
+    log::info!("History item added: {} chars", item.char_count);
+    Ok(())
+}
+
+// 续写时的连接符：英文等需要空格分隔，中文等直接拼接
+fn continuation_joiner(previous: &str, next: &str) -> &'static str {
+    match (previous.chars().last(), next.chars().next()) {
+        (Some(a), Some(b))
+            if (a.is_ascii_alphanumeric() || a.is_ascii_punctuation()) && b.is_ascii_alphanumeric() => " ",
+        _ => "",
+    }
+}
+
+// 追加到最近一条历史记录，返回使用的连接符
+// 没有历史记录或超出续写窗口时返回 None，由调用方改为新增记录
+pub fn append_to_last_history(text: &str) -> Result<Option<String>, String> {
+    let now = Local::now();
+    let Some(joiner) = history::update(|history| match history.first_mut() {
+        Some(last) if now.timestamp() - last.timestamp <= CONTINUATION_WINDOW_SECS => {
+            let joiner = continuation_joiner(&last.text, text);
+            last.text.push_str(joiner);
+            last.text.push_str(text);
+            last.char_count = last.text.chars().count();
+            last.timestamp = now.timestamp();
+            Ok::<_, String>(Some(joiner))
+        }
+        _ => Ok(None),
+    })? else {
+        return Ok(None);
+    };
+
+    log::info!("Appended {} chars to last history item", text.chars().count());
+    Ok(Some(joiner.to_string()))
+}
+
+// 获取历史记录
+#[tauri::command]
+fn get_history() -> CommandResult<Vec<HistoryItem>> {
+    history::load_owned().map_err(Into::into)
+}
+
+// 分页结果
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct HistoryPage {
+    pub items: Vec<HistoryItem>,
+    pub total: usize,
+}
+
+// 分页获取历史记录，只复制当前页
+#[tauri::command]
+fn get_history_page(offset: usize, limit: usize) -> CommandResult<HistoryPage> {
+    let history = history::load()?;
+    Ok(HistoryPage {
+        items: history.iter().skip(offset).take(limit).cloned().collect(),
+        total: history.len(),
+    })
+}
+
+// 复制历史记录文本到剪贴板（不自动粘贴）
+#[tauri::command]
+fn copy_history_item(id: String) -> CommandResult<()> {
+    let history = get_history()?;
+    let item = history
+        .iter()
+        .find(|item| item.id == id)
+        .ok_or_else(|| CommandError::not_found(format!("History item not found: {}", id)))?;
+
+    input::copy_to_clipboard(&item.text)?;
+    log::info!("History item copied to clipboard: {}", id);
+    Ok(())
+}
+
+// 删除历史记录项
+#[tauri::command]
+fn delete_history_item(id: String) -> CommandResult<()> {
+    history::update(|history| {
+        history.retain(|item| {
+            if item.id == id {
+                remove_item_recording(item);
+            }
+            item.id != id
+        });
+        Ok::<_, String>(())
+    })?;
+    log::info!("History item deleted: {}", id);
+    Ok(())
+}
+
+// 导出全部历史记录
+#[tauri::command]
+fn export_history(path: String, format: export::ExportFormat) -> CommandResult<usize> {
+    let history = get_history()?;
+    export::export_items(&history, std::path::Path::new(&path), format)?;
+    log::info!("Exported {} history items to: {}", history.len(), path);
+    Ok(history.len())
+}
+
+// 导出单条历史记录
+#[tauri::command]
+fn export_history_item(id: String, path: String, format: export::ExportFormat) -> CommandResult<()> {
+    let history = get_history()?;
+    let item = history
+        .into_iter()
+        .find(|item| item.id == id)
+        .ok_or_else(|| CommandError::not_found(format!("History item not found: {}", id)))?;
+
+    export::export_items(std::slice::from_ref(&item), std::path::Path::new(&path), format)?;
+    log::info!("Exported history item {} to: {}", id, path);
+    Ok(())
+}
+
+// 将单条历史记录导出为字幕文件：有分段时间时逐段生成，否则整段录音作为一条字幕
+#[tauri::command]
+fn export_subtitles(id: String, path: String, format: export::SubtitleFormat) -> CommandResult<()> {
+    let history = history::load()?;
+    let item = history
+        .iter()
+        .find(|item| item.id == id)
+        .ok_or_else(|| CommandError::not_found(format!("History item not found: {}", id)))?;
+
+    let duration_ms = item.duration_ms.or_else(|| {
+        item.audio_path
+            .as_ref()
+            .and_then(|path| audio::wav_duration_ms(std::path::Path::new(path)).ok())
+    });
+    let content = export::format_subtitles(item, duration_ms, format)?;
+    write_atomic(std::path::Path::new(&path), content.as_bytes())?;
+    log::info!("Exported subtitles for history item {} to: {}", id, path);
     Ok(())
 }
 
-// 获取历史记录
+// 校验日期范围（YYYY-MM-DD，包含两端），返回规范化后的起止日期
+fn parse_date_range(date_from: &str, date_to: &str) -> CommandResult<(String, String)> {
+    let parse = |date: &str| {
+        chrono::NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+            .map_err(|e| CommandError::invalid_input(format!("Invalid date {:?}: {}", date, e)))
+    };
+    let (from, to) = (parse(date_from)?, parse(date_to)?);
+    if from > to {
+        return Err(CommandError::invalid_input(format!("Start date {} is after end date {}", from, to)));
+    }
+    Ok((from.format("%Y-%m-%d").to_string(), to.format("%Y-%m-%d").to_string()))
+}
+
+// 列出日期范围内的历史记录
+#[tauri::command]
+fn get_history_range(date_from: String, date_to: String) -> CommandResult<Vec<HistoryItem>> {
+    let (from, to) = parse_date_range(&date_from, &date_to)?;
+    Ok(history::load()?
+        .iter()
+        .filter(|item| item.date >= from && item.date <= to)
+        .cloned()
+        .collect())
+}
+
+// 删除日期范围内的全部历史记录（及其录音），返回删除的条数
+#[tauri::command]
+fn delete_history_range(date_from: String, date_to: String) -> CommandResult<usize> {
+    let (from, to) = parse_date_range(&date_from, &date_to)?;
+    let removed = history::update(|history| {
+        let before = history.len();
+        history.retain(|item| {
+            let in_range = item.date >= from && item.date <= to;
+            if in_range {
+                remove_item_recording(item);
+            }
+            !in_range
+        });
+        Ok::<_, String>(before - history.len())
+    })?;
+    log::info!("Deleted {} history items from {} to {}", removed, from, to);
+    Ok(removed)
+}
+
+// 设置历史记录的标签（去除空白和重复）
 #[tauri::command]
-fn get_history() -> Result<Vec<HistoryItem>, String> {
-    let config_path = get_config_path();
-    if !config_path.exists() {
-        return Ok(Vec::new());
+fn set_history_tags(id: String, tags: Vec<String>) -> CommandResult<HistoryItem> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim().to_string();
+        if !tag.is_empty() && !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
     }
-    
-    let content = fs::read_to_string(&config_path)
-        .map_err(|e| format!("Failed to read config: {}", e))?;
-    
-    let config: serde_json::Value = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse config: {}", e))?;
-    
-    let history: Vec<HistoryItem> = config.get("history")
-        .and_then(|v| serde_json::from_value(v.clone()).ok())
-        .unwrap_or_default();
-    
-    Ok(history)
+
+    let updated = history::update(|history| {
+        let item = history
+            .iter_mut()
+            .find(|item| item.id == id)
+            .ok_or_else(|| CommandError::not_found(format!("History item not found: {}", id)))?;
+        item.tags = normalized;
+        Ok::<_, CommandError>(item.clone())
+    })?;
+    log::info!("Tags updated for history item {}: {:?}", id, updated.tags);
+    Ok(updated)
 }
 
-// 删除历史记录项
 #[tauri::command]
-fn delete_history_item(id: String) -> Result<(), String> {
-    let config_path = get_config_path();
-    
-    let content = fs::read_to_string(&config_path)
-        .map_err(|e| format!("Failed to read config: {}", e))?;
-    
-    let mut config: serde_json::Value = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse config: {}", e))?;
-    
-    let mut history: Vec<HistoryItem> = config.get("history")
-        .and_then(|v| serde_json::from_value(v.clone()).ok())
-        .unwrap_or_default();
-    
-    history.retain(|item| item.id != id);
-    
-    config["history"] = serde_json::to_value(&history)
-        .map_err(|e| format!("Failed to serialize history: {}", e))?;
-    
-    let content = serde_json::to_string_pretty(&config)
-        .map_err(|e| format!("Failed to serialize config: {}", e))?;
-    
-    fs::write(&config_path, content)
-        .map_err(|e| format!("Failed to write config: {}", e))?;
-    
-    log::info!("History item deleted: {}", id);
+fn get_history_by_tag(tag: String) -> CommandResult<Vec<HistoryItem>> {
+    let tag = tag.trim();
+    Ok(get_history()?
+        .into_iter()
+        .filter(|item| item.tags.iter().any(|t| t == tag))
+        .collect())
+}
+
+// 自动用听写时的前台应用作为标签
+#[tauri::command]
+fn get_auto_tag_app() -> bool {
+    get_setting("auto_tag_app")
+}
+
+#[tauri::command]
+fn set_auto_tag_app(enabled: bool) -> CommandResult<()> {
+    set_setting("auto_tag_app", &enabled)?;
+    log::info!("Auto tag by app set to: {}", enabled);
     Ok(())
 }
 
+// 合并多条历史记录：按时间顺序拼接文本，替换原记录
+#[tauri::command]
+fn merge_history_items(ids: Vec<String>, joiner: String) -> CommandResult<HistoryItem> {
+    let unique: HashSet<&String> = ids.iter().collect();
+    if unique.len() < 2 {
+        return Err(CommandError::invalid_input("At least two history items are required to merge"));
+    }
+
+    let (merged, selected) = history::update(|history| {
+        if let Some(missing) = unique.iter().find(|id| !history.iter().any(|item| &item.id == **id)) {
+            return Err(CommandError::not_found(format!("History item not found: {}", missing)));
+        }
+
+        let (mut selected, mut remaining): (Vec<HistoryItem>, Vec<HistoryItem>) = history
+            .drain(..)
+            .partition(|item| unique.contains(&item.id));
+        selected.sort_by_key(|item| item.timestamp);
+
+        let earliest = &selected[0];
+        let text = selected
+            .iter()
+            .map(|item| item.text.as_str())
+            .collect::<Vec<_>>()
+            .join(&joiner);
+
+        let mut merged = HistoryItem::new(&text);
+        merged.timestamp = earliest.timestamp;
+        merged.date = earliest.date.clone();
+        merged.char_count = text.chars().count();
+        // 语言一致时保留，时长累加；录音无法拼接，随原记录一起删除
+        if selected.iter().all(|item| item.language == earliest.language) {
+            merged.language = earliest.language.clone();
+        }
+        merged.duration_ms = selected.iter().map(|item| item.duration_ms).sum();
+
+        // 按时间倒序插入，保持列表顺序
+        let position = remaining
+            .iter()
+            .position(|item| item.timestamp < merged.timestamp)
+            .unwrap_or(remaining.len());
+        remaining.insert(position, merged.clone());
+        *history = remaining;
+        Ok((merged, selected))
+    })?;
+    selected.iter().for_each(remove_item_recording);
+
+    log::info!("Merged {} history items into {}", selected.len(), merged.id);
+    Ok(merged)
+}
+
 // 清空历史记录
 #[tauri::command]
-fn clear_history() -> Result<(), String> {
-    let config_path = get_config_path();
-    
-    let content = fs::read_to_string(&config_path)
-        .map_err(|e| format!("Failed to read config: {}", e))?;
-    
-    let mut config: serde_json::Value = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse config: {}", e))?;
-    
-    config["history"] = json!([]);
-    
-    let content = serde_json::to_string_pretty(&config)
-        .map_err(|e| format!("Failed to serialize config: {}", e))?;
-    
-    fs::write(&config_path, content)
-        .map_err(|e| format!("Failed to write config: {}", e))?;
+fn clear_history() -> CommandResult<()> {
+    history::update(|history| {
+        history.drain(..).for_each(|item| remove_item_recording(&item));
+        Ok::<_, String>(())
+    })?;
     
     log::info!("History cleared");
     Ok(())
@@ -522,14 +2364,14 @@ fn clear_history() -> Result<(), String> {
 
 // 获取历史记录保留设置
 #[tauri::command]
-fn get_history_retention() -> Result<HistoryRetention, String> {
+fn get_history_retention() -> CommandResult<HistoryRetention> {
     let config_path = get_config_path();
     if !config_path.exists() {
         return Ok(HistoryRetention::default());
     }
     
     let content = fs::read_to_string(&config_path)
-        .map_err(|e| format!("Failed to read config: {}", e))?;
+        .map_err(|e| CommandError::io(format!("Failed to read config: {}", e)))?;
     
     let config: serde_json::Value = serde_json::from_str(&content)
         .map_err(|e| format!("Failed to parse config: {}", e))?;
@@ -543,13 +2385,13 @@ fn get_history_retention() -> Result<HistoryRetention, String> {
 
 // 设置历史记录保留设置
 #[tauri::command]
-fn set_history_retention(retention: HistoryRetention) -> Result<(), String> {
+fn set_history_retention(retention: HistoryRetention) -> CommandResult<()> {
     let config_path = get_config_path();
     
     // Create directory if needed
     if let Some(parent) = config_path.parent() {
         fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+            .map_err(|e| CommandError::io(format!("Failed to create config directory: {}", e)))?;
     }
     
     // Read existing config or create new
@@ -560,39 +2402,536 @@ fn set_history_retention(retention: HistoryRetention) -> Result<(), String> {
         json!({})
     };
     
-    config["history_retention"] = serde_json::to_value(&retention)
+    config["history_retention"] = serde_json::to_value(retention)
         .map_err(|e| format!("Failed to serialize retention: {}", e))?;
     
-    // Clean up old records based on new retention setting
-    if retention != HistoryRetention::Forever {
-        let now = Local::now();
-        let cutoff_timestamp = match retention {
-            HistoryRetention::SevenDays => now.timestamp() - 7 * 24 * 60 * 60,
-            HistoryRetention::ThirtyDays => now.timestamp() - 30 * 24 * 60 * 60,
-            HistoryRetention::NinetyDays => now.timestamp() - 90 * 24 * 60 * 60,
-            HistoryRetention::Forever => 0,
-        };
-        
-        let mut history: Vec<HistoryItem> = config.get("history")
-            .and_then(|v| serde_json::from_value(v.clone()).ok())
-            .unwrap_or_default();
-        
-        history.retain(|item| item.timestamp >= cutoff_timestamp);
-        
-        config["history"] = serde_json::to_value(&history)
-            .map_err(|e| format!("Failed to serialize history: {}", e))?;
-    }
-    
     let content = serde_json::to_string_pretty(&config)
         .map_err(|e| format!("Failed to serialize config: {}", e))?;
     
     fs::write(&config_path, content)
-        .map_err(|e| format!("Failed to write config: {}", e))?;
+        .map_err(|e| CommandError::io(format!("Failed to write config: {}", e)))?;
     
     log::info!("History retention set to: {:?}", retention);
+
+    // Clean up old records based on new retention setting
+    if retention != HistoryRetention::Forever {
+        run_retention_cleanup()?;
+    }
+    Ok(())
+}
+
+// 按保留设置删除过期记录（及其录音），返回删除的条数
+fn apply_retention(history: &mut Vec<HistoryItem>, retention: HistoryRetention, now: i64) -> usize {
+    let cutoff_timestamp = match retention {
+        HistoryRetention::SevenDays => now - 7 * 24 * 60 * 60,
+        HistoryRetention::ThirtyDays => now - 30 * 24 * 60 * 60,
+        HistoryRetention::NinetyDays => now - 90 * 24 * 60 * 60,
+        HistoryRetention::Forever => return 0,
+    };
+
+    let before = history.len();
+    history.retain(|item| {
+        let keep = item.timestamp >= cutoff_timestamp;
+        if !keep {
+            remove_item_recording(item);
+        }
+        keep
+    });
+    before - history.len()
+}
+
+// 独立于新录音执行一次保留清理，同时重写 history.jsonl 压缩掉无法解析的行
+fn run_retention_cleanup() -> Result<usize, String> {
+    let retention: HistoryRetention = get_setting("history_retention");
+    let removed = history::update(|history| {
+        Ok::<_, String>(apply_retention(history, retention, Local::now().timestamp()))
+    })?;
+    if removed > 0 {
+        log::info!("Retention cleanup removed {} history items", removed);
+    }
+    Ok(removed)
+}
+
+// 启动时清理一次，之后每天清理一次，保证空闲期间过期记录也会被删除
+fn start_retention_cleanup() {
+    std::thread::spawn(|| loop {
+        if let Err(e) = run_retention_cleanup() {
+            log::warn!("Retention cleanup failed: {}", e);
+        }
+        std::thread::sleep(std::time::Duration::from_secs(24 * 60 * 60));
+    });
+}
+
+// 删除历史记录关联的录音文件
+fn remove_item_recording(item: &HistoryItem) {
+    if let Some(ref path) = item.audio_path {
+        if let Err(e) = fs::remove_file(path) {
+            log::warn!("Failed to remove recording {}: {}", path, e);
+        }
+    }
+}
+
+// 将录音移动到保留目录，返回新路径
+pub fn keep_recording(audio_path: &std::path::Path, id: &str) -> Result<PathBuf, String> {
+    move_recording(audio_path, &get_recordings_dir(), id)
+}
+
+// 转录失败的录音存放目录
+pub fn get_failed_dir() -> PathBuf {
+    let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+    home.join(".mouth-high").join("failed")
+}
+
+// 转录失败时保存录音并添加占位历史记录，返回保存路径
+pub fn keep_failed_recording(audio_path: &std::path::Path) -> Result<PathBuf, String> {
+    let mut item = HistoryItem::new("");
+    let target = move_recording(audio_path, &get_failed_dir(), &item.id)?;
+    item.audio_path = Some(target.to_string_lossy().to_string());
+    item.duration_ms = audio::wav_duration_ms(&target).ok();
+    item.failed = true;
+    add_history_item(item)?;
+    Ok(target)
+}
+
+fn move_recording(audio_path: &std::path::Path, dir: &std::path::Path, id: &str) -> Result<PathBuf, String> {
+    fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create recordings directory: {}", e))?;
+
+    let target = dir.join(format!("{}.wav", id));
+    // 临时目录可能与用户目录不在同一文件系统，rename 失败时退回到复制
+    if fs::rename(audio_path, &target).is_err() {
+        fs::copy(audio_path, &target)
+            .map_err(|e| format!("Failed to keep recording: {}", e))?;
+        let _ = fs::remove_file(audio_path);
+    }
+
+    log::info!("Recording kept at {:?}", target);
+    Ok(target)
+}
+
+// 录音开始/结束提示音及其输出设备
+#[tauri::command]
+fn get_feedback_sounds() -> bool {
+    feedback::is_enabled()
+}
+
+#[tauri::command]
+fn set_feedback_sounds(enabled: bool) -> CommandResult<()> {
+    set_setting("feedback_sounds", &enabled)?;
+    log::info!("Feedback sounds set to: {}", enabled);
+    Ok(())
+}
+
+#[tauri::command]
+fn list_output_devices() -> CommandResult<Vec<String>> {
+    feedback::list_output_devices().map_err(Into::into)
+}
+
+#[tauri::command]
+fn get_feedback_output_device() -> Option<String> {
+    get_setting("feedback_output_device")
+}
+
+// 传入 None 表示使用默认输出设备
+#[tauri::command]
+fn set_feedback_output_device(name: Option<String>) -> CommandResult<()> {
+    if let Some(ref name) = name {
+        if !feedback::list_output_devices()?.contains(name) {
+            return Err(CommandError::not_found(format!("Output device not found: {}", name)));
+        }
+    }
+    set_setting("feedback_output_device", &name)?;
+    log::info!("Feedback output device set to: {:?}", name);
+    Ok(())
+}
+
+// 音频后端选择（Linux 上可在 ALSA/JACK 等之间切换）
+#[tauri::command]
+fn list_audio_hosts() -> Vec<String> {
+    audio::list_hosts()
+}
+
+#[tauri::command]
+fn get_audio_host() -> Option<String> {
+    get_setting("audio_host")
+}
+
+// 传入 None 表示使用系统默认后端，下次开始录音时生效
+#[tauri::command]
+fn set_audio_host(name: Option<String>) -> CommandResult<()> {
+    let name = match name {
+        Some(name) => {
+            let id = audio::find_host_id(&name)
+                .ok_or_else(|| format!("Audio host not available: {}", name))?;
+            Some(id.name().to_string())
+        }
+        None => None,
+    };
+    set_setting("audio_host", &name)?;
+    log::info!("Audio host set to: {:?}", name);
+    Ok(())
+}
+
+// recording-elapsed 事件发送间隔（毫秒）
+#[tauri::command]
+fn get_elapsed_interval_ms() -> u64 {
+    get_setting::<Option<u64>>("elapsed_interval_ms").unwrap_or(audio::DEFAULT_ELAPSED_INTERVAL_MS)
+}
+
+#[tauri::command]
+fn set_elapsed_interval_ms(interval_ms: u64) -> CommandResult<()> {
+    if !(50..=5000).contains(&interval_ms) {
+        return Err(CommandError::invalid_input("Elapsed interval must be between 50 and 5000 ms"));
+    }
+    set_setting("elapsed_interval_ms", &interval_ms)?;
+    log::info!("Recording elapsed interval set to: {}ms", interval_ms);
+    Ok(())
+}
+
+// 输入采样格式偏好，下次开始录音时生效
+#[tauri::command]
+fn get_sample_format() -> audio::SampleFormatPreference {
+    get_setting("sample_format")
+}
+
+#[tauri::command]
+fn set_sample_format(preference: audio::SampleFormatPreference) -> CommandResult<()> {
+    set_setting("sample_format", &preference)?;
+    log::info!("Sample format preference set to: {:?}", preference);
+    Ok(())
+}
+
+// 输入流缓冲区大小（帧），None 表示使用设备默认值；较小的值延迟更低但更耗 CPU
+#[tauri::command]
+fn get_buffer_size() -> Option<u32> {
+    get_setting("buffer_size")
+}
+
+#[tauri::command]
+fn set_buffer_size(frames: Option<u32>) -> CommandResult<()> {
+    if let Some(frames) = frames {
+        if let Some((min, max)) = audio::supported_buffer_range() {
+            if !(min..=max).contains(&frames) {
+                let message = format!("Buffer size must be between {} and {} frames", min, max);
+                return Err(CommandError::invalid_input(message).with_detail(json!({ "min": min, "max": max })));
+            }
+        }
+    }
+    set_setting("buffer_size", &frames)?;
+    log::info!("Input buffer size set to: {:?}", frames);
+    Ok(())
+}
+
+// 当前输入设备支持的缓冲区大小范围
+#[tauri::command]
+fn get_buffer_size_range() -> Option<(u32, u32)> {
+    audio::supported_buffer_range()
+}
+
+// 录音缓冲区预分配时长（秒）
+#[tauri::command]
+fn get_reserve_duration_secs() -> u32 {
+    get_setting::<Option<u32>>("reserve_duration_secs").unwrap_or(audio::DEFAULT_RESERVE_DURATION_SECS)
+}
+
+#[tauri::command]
+fn set_reserve_duration_secs(secs: u32) -> CommandResult<()> {
+    if secs > audio::MAX_RESERVE_DURATION_SECS {
+        return Err(CommandError::invalid_input(format!(
+            "Reserve duration must be at most {} seconds",
+            audio::MAX_RESERVE_DURATION_SECS
+        )));
+    }
+    set_setting("reserve_duration_secs", &secs)?;
+    log::info!("Recording buffer reserve set to: {}s", secs);
+    Ok(())
+}
+
+// 转录失败时保留录音以便重试
+#[tauri::command]
+fn get_on_error_keep_audio() -> bool {
+    get_setting("on_error_keep_audio")
+}
+
+#[tauri::command]
+fn set_on_error_keep_audio(enabled: bool) -> CommandResult<()> {
+    set_setting("on_error_keep_audio", &enabled)?;
+    log::info!("Keep audio on transcription error set to: {}", enabled);
+    Ok(())
+}
+
+#[tauri::command]
+fn get_keep_recordings() -> bool {
+    get_setting("keep_recordings")
+}
+
+#[tauri::command]
+fn set_keep_recordings(enabled: bool) -> CommandResult<()> {
+    set_setting("keep_recordings", &enabled)?;
+    log::info!("Keep recordings set to: {}", enabled);
+    Ok(())
+}
+
+// 获取配置、历史和录音的磁盘占用
+#[tauri::command]
+fn get_storage_usage() -> CommandResult<StorageUsage> {
+    let mut usage = StorageUsage::default();
+
+    let config_path = get_config_path();
+    if let Ok(meta) = fs::metadata(&config_path) {
+        usage.config_bytes = meta.len();
+    }
+
+    if let Ok(meta) = fs::metadata(history::history_path()) {
+        usage.history_bytes = meta.len();
+    }
+
+    let dir = get_recordings_dir();
+    if dir.exists() {
+        let entries = fs::read_dir(&dir)
+            .map_err(|e| CommandError::io(format!("Failed to read recordings directory: {}", e)))?;
+        for entry in entries.flatten() {
+            if let Ok(meta) = entry.metadata() {
+                if meta.is_file() {
+                    usage.recordings_bytes += meta.len();
+                    usage.recordings_count += 1;
+                }
+            }
+        }
+    }
+
+    Ok(usage)
+}
+
+// 删除所有保留的录音，并清空历史记录中的录音路径
+#[tauri::command]
+fn clear_recordings() -> CommandResult<usize> {
+    let dir = get_recordings_dir();
+    let mut removed = 0;
+    if dir.exists() {
+        let entries = fs::read_dir(&dir)
+            .map_err(|e| CommandError::io(format!("Failed to read recordings directory: {}", e)))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().map(|ext| ext == "wav").unwrap_or(false) {
+                match fs::remove_file(&path) {
+                    Ok(()) => removed += 1,
+                    Err(e) => log::warn!("Failed to remove recording {:?}: {}", path, e),
+                }
+            }
+        }
+    }
+
+    history::update(|history| {
+        for item in history.iter_mut() {
+            item.audio_path = None;
+        }
+        Ok::<_, String>(())
+    })?;
+
+    log::info!("Cleared {} recordings", removed);
+    Ok(removed)
+}
+
+// 测试麦克风：录制一小段并返回电平，不经过 ASR
+#[tauri::command]
+async fn test_microphone(duration_ms: u64) -> CommandResult<audio::MicTestResult> {
+    let duration_ms = duration_ms.clamp(100, 10_000);
+    tauri::async_runtime::spawn_blocking(move || audio::test_microphone(duration_ms))
+        .await
+        .map_err(|e| format!("Microphone test failed: {}", e))?.map_err(Into::into)
+}
+
+// 采集环境噪声频谱并保存，供频谱降噪使用
+#[tauri::command]
+async fn capture_noise_profile(duration_ms: u64) -> CommandResult<()> {
+    let duration_ms = duration_ms.clamp(500, 10_000);
+    let profile = tauri::async_runtime::spawn_blocking(move || audio::capture_noise_profile(duration_ms))
+        .await
+        .map_err(|e| format!("Noise profile capture failed: {}", e))??;
+    denoise::save_profile(&profile).map_err(Into::into)
+}
+
+// 频谱降噪（默认关闭，需先采集噪声频谱）
+#[tauri::command]
+fn get_spectral_denoise() -> bool {
+    get_setting("spectral_denoise")
+}
+
+#[tauri::command]
+fn set_spectral_denoise(enabled: bool) -> CommandResult<()> {
+    if enabled && denoise::load_profile().is_none() {
+        return Err(CommandError::not_ready("Capture a noise profile before enabling spectral denoise"));
+    }
+    set_setting("spectral_denoise", &enabled)?;
+    log::info!("Spectral denoise set to: {}", enabled);
     Ok(())
 }
 
+// 测量音频采集开销：回调次数、平均/最大回调耗时和加锁等待时间
+#[tauri::command]
+async fn profile_capture(duration_ms: u64) -> CommandResult<audio::CaptureReport> {
+    let duration_ms = duration_ms.clamp(100, 30_000);
+    tauri::async_runtime::spawn_blocking(move || audio::profile_capture(duration_ms))
+        .await
+        .map_err(|e| format!("Capture profiling failed: {}", e))?.map_err(Into::into)
+}
+
+// 批量转录文件夹中的音频，write_text 为 true 时在每个文件旁写入同名 .txt
+#[tauri::command]
+async fn transcribe_folder(
+    app_handle: tauri::AppHandle,
+    dir: String,
+    recursive: bool,
+    write_text: Option<bool>,
+) -> CommandResult<Vec<batch::BatchResult>> {
+    let write_text = write_text.unwrap_or(false);
+    tauri::async_runtime::spawn_blocking(move || {
+        batch::transcribe_folder(&app_handle, std::path::Path::new(&dir), recursive, write_text)
+    })
+    .await
+    .map_err(|e| format!("Batch transcription failed: {}", e))?.map_err(Into::into)
+}
+
+// 重新转录历史记录中保存的录音（失败的占位记录或保留的录音），更新并返回该记录
+#[tauri::command]
+async fn retranscribe(app_handle: tauri::AppHandle, id: String) -> CommandResult<HistoryItem> {
+    tauri::async_runtime::spawn_blocking(move || retranscribe_item(&app_handle, &id))
+        .await
+        .map_err(|e| format!("Retranscription failed: {}", e))?
+}
+
+// 只转录保留录音中的一段（毫秒），不修改原录音和历史记录
+#[tauri::command]
+async fn transcribe_segment(
+    app_handle: tauri::AppHandle,
+    id: String,
+    start_ms: u64,
+    end_ms: u64,
+) -> CommandResult<sidecar::TranscriptResult> {
+    tauri::async_runtime::spawn_blocking(move || transcribe_segment_of(&app_handle, &id, start_ms, end_ms))
+        .await
+        .map_err(|e| format!("Segment transcription failed: {}", e))?
+}
+
+fn transcribe_segment_of(app: &tauri::AppHandle, id: &str, start_ms: u64, end_ms: u64) -> CommandResult<sidecar::TranscriptResult> {
+    let audio_path = history::load()?
+        .iter()
+        .find(|item| item.id == id)
+        .ok_or_else(|| CommandError::not_found(format!("History item not found: {}", id)))?
+        .audio_path
+        .clone()
+        .ok_or_else(|| CommandError::not_found(format!("No recording kept for history item: {}", id)))?;
+    if !sidecar::is_ready() {
+        return Err(CommandError::not_ready("ASR service not ready"));
+    }
+
+    let audio_path = std::path::Path::new(&audio_path);
+    let duration_ms = audio::wav_duration_ms(audio_path)?;
+    if start_ms >= end_ms || end_ms > duration_ms {
+        return Err(CommandError::invalid_input(format!(
+            "Invalid range {}-{} ms for a {} ms recording",
+            start_ms, end_ms, duration_ms
+        )));
+    }
+
+    let segment = audio::slice_wav(audio_path, start_ms, end_ms)?;
+    let state = app.state::<AppState>();
+    let language = resolve_language_hint(&state);
+    let result = {
+        let sidecar = state.sidecar_manager.lock().map_err(|e| e.to_string())?;
+        match *sidecar {
+            Some(ref manager) => manager.transcribe(&segment, language.as_deref()),
+            None => Err("Sidecar not initialized".to_string()),
+        }
+    };
+    if let Err(e) = fs::remove_file(&segment) {
+        log::warn!("Failed to remove temp audio file: {}", e);
+    }
+    result.map_err(Into::into)
+}
+
+fn retranscribe_item(app: &tauri::AppHandle, id: &str) -> CommandResult<HistoryItem> {
+    let not_found = || CommandError::not_found(format!("History item not found: {}", id));
+    let audio_path = history::load()?
+        .iter()
+        .find(|item| item.id == id)
+        .ok_or_else(not_found)?
+        .audio_path
+        .clone()
+        .ok_or_else(|| CommandError::not_found(format!("No recording kept for history item: {}", id)))?;
+    if !sidecar::is_ready() {
+        return Err(CommandError::not_ready("ASR service not ready"));
+    }
+
+    let state = app.state::<AppState>();
+    let language = resolve_language_hint(&state);
+    let transcript = {
+        let sidecar = state.sidecar_manager.lock().map_err(|e| e.to_string())?;
+        match *sidecar {
+            Some(ref manager) => manager.transcribe(std::path::Path::new(&audio_path), language.as_deref())?,
+            None => return Err(CommandError::not_ready("Sidecar not initialized")),
+        }
+    };
+
+    // 转录耗时较长，完成后再在写入锁内更新记录，期间新增的记录不受影响
+    let updated = history::update(|history| {
+        let item = history.iter_mut().find(|item| item.id == id).ok_or_else(not_found)?;
+        let was_failed = item.failed;
+        item.text = transcript.text.clone();
+        item.char_count = transcript.text.chars().count();
+        item.language = transcript.language.or_else(|| text::detect_script_language(&transcript.text));
+        item.failed = false;
+
+        // 失败目录中的录音：按保留设置移入录音目录或删除
+        if was_failed {
+            let path = std::path::Path::new(&audio_path);
+            item.audio_path = if get_setting::<bool>("keep_recordings") {
+                Some(keep_recording(path, &item.id)?.to_string_lossy().to_string())
+            } else {
+                if let Err(e) = fs::remove_file(path) {
+                    log::warn!("Failed to remove failed recording: {}", e);
+                }
+                None
+            };
+            update_usage_stats(item.char_count)?;
+        }
+        Ok::<_, CommandError>(item.clone())
+    })?;
+    log::info!("Retranscribed history item {}", id);
+    Ok(updated)
+}
+
+// 回放保留录音的音量序列，用于调试波形组件；emit 为 true 时按实时节奏发送 audio-amplitude 事件
+#[tauri::command]
+fn replay_amplitude(app_handle: tauri::AppHandle, id: String, emit: Option<bool>) -> CommandResult<Vec<f32>> {
+    let history = get_history()?;
+    let item = history
+        .iter()
+        .find(|item| item.id == id)
+        .ok_or_else(|| CommandError::not_found(format!("History item not found: {}", id)))?;
+    let audio_path = item
+        .audio_path
+        .as_ref()
+        .ok_or_else(|| CommandError::not_found(format!("No recording kept for history item: {}", id)))?;
+
+    let series = audio::amplitude_series(std::path::Path::new(audio_path))?;
+
+    if emit.unwrap_or(false) {
+        let values = series.clone();
+        std::thread::spawn(move || {
+            for value in values {
+                if let Some(window) = app_handle.get_webview_window("recording-bar") {
+                    let _ = window.emit("audio-amplitude", value);
+                }
+                std::thread::sleep(std::time::Duration::from_millis(audio::AMPLITUDE_INTERVAL_MS as u64));
+            }
+        });
+    }
+
+    Ok(series)
+}
+
 pub fn run() {
     // Show info logs by default in dev; allow overriding via `RUST_LOG`.
     // Helps debugging issues like hotkey/cancel flows where users expect logs to appear.
@@ -602,36 +2941,110 @@ pub fn run() {
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .plugin(tauri_plugin_shell::init())
         .manage(AppState {
-            output_mode: Mutex::new(OutputMode::default()),
+            output_mode: Mutex::new(get_setting("output_mode")),
             is_recording: Mutex::new(false),
             recording_mode: Mutex::new(RecordingMode::default()),
             recording_session: Mutex::new(0),
             cancelled_sessions: Mutex::new(HashSet::new()),
             sidecar_manager: Mutex::new(None),
             previous_app: Mutex::new(None),
+            continue_last: Mutex::new(false),
+            last_language: Mutex::new(None),
+            last_output: Mutex::new(None),
+            last_transcript: Mutex::new(None),
+            pending_output: Mutex::new(None),
+            recent_errors: Mutex::new(VecDeque::new()),
         })
+        .manage(queue::TranscriptionQueue::new())
         .setup(|app| {
             let handle = app.handle().clone();
 
+            // 升级旧版本的配置文件
+            migrate_config_file();
+            history::migrate_config_history();
+
             // Initialize sidecar
             sidecar::init_sidecar(&handle)?;
 
+            // Start transcription queue workers
+            queue::start_worker(&handle);
+
+            // 定期按保留设置清理历史记录
+            start_retention_cleanup();
+
+            // 输入设备变化时按规则切换模式
+            device_rules::start_watcher(&handle);
+
+            // 定期刷新电源状态，供录音时按电池设置降低音量事件频率
+            power::start_monitor();
+
             // Setup tray
             tray::setup_tray(&handle)?;
 
             // Setup hotkey
             hotkey::setup_hotkey(&handle)?;
 
+            #[cfg(feature = "mouse-hotkey")]
+            mouse::setup_mouse_hotkey(&handle);
+
             log::info!("Mouth High initialized successfully");
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
-                set_output_mode, get_output_mode, 
-                get_recording_mode, set_recording_mode, stop_recording, cancel_recording,
-                get_api_key, set_api_key, is_api_key_configured, get_usage_stats,
-                get_hotkey_config, set_hotkey_config, update_hotkey,
-                get_history, delete_history_item, clear_history,
-                get_history_retention, set_history_retention
+                set_output_mode, get_output_mode, get_output_target, set_output_target, 
+                get_experimental_accessibility_output, set_experimental_accessibility_output,
+                get_recording_mode, set_recording_mode, get_device_mode_rules, set_device_mode_rules,
+                get_recent_errors, request_error_replay, stop_recording, cancel_recording,
+                set_continue_last, get_continue_last, get_escape_cancels_recording, set_escape_cancels_recording,
+                get_recording_bar_settings, set_recording_bar_settings, list_monitors, get_recording_bar_monitor, set_recording_bar_monitor,
+                get_show_bar_in_hold, set_show_bar_in_hold,
+                get_processing_timeout_secs, set_processing_timeout_secs, get_language_mode, set_language_mode,
+                get_already_recording_policy, set_already_recording_policy,
+                get_sidecar_not_ready_policy, set_sidecar_not_ready_policy, is_sidecar_ready, get_sidecar_capabilities,
+                get_sidecar_paths, set_sidecar_paths, validate_sidecar_paths,
+                get_max_concurrent_transcriptions, set_max_concurrent_transcriptions,
+                get_queue_overflow_policy, set_queue_overflow_policy, get_queue_depth, cancel_transcription, get_transcription_timing_stats,
+                get_self_focus_guard, set_self_focus_guard,
+                get_keyboard_backend, set_keyboard_backend, get_paste_timing, set_paste_timing, get_clipboard_paste_fallback, set_clipboard_paste_fallback, get_auto_paste_settings, set_auto_paste_overrides, get_memory_only, set_memory_only,
+                get_keyboard_max_length, set_keyboard_max_length, get_keyboard_fallback_notify, set_keyboard_fallback_notify,
+                get_trim_transcript, set_trim_transcript, get_strip_patterns, set_strip_patterns, get_replacement_rules, set_replacement_rules, validate_patterns, preview_replacements,
+                export_replacements, import_replacements,
+                get_command_mode, set_command_mode,
+                get_transcription_cache, set_transcription_cache, clear_transcription_cache,
+                get_spoken_punctuation, set_spoken_punctuation,
+                get_filler_word_filter, set_filler_word_filter,
+                get_markdown_assist, set_markdown_assist,
+                get_text_transform, set_text_transform, get_output_template, set_output_template,
+                get_amplitude_curve, set_amplitude_curve, get_amplitude_throttle, set_amplitude_throttle,
+                get_api_key, set_api_key, is_api_key_configured, get_usage_stats, export_stats,
+                get_quota_estimate, get_quota_settings, set_quota_settings,
+                get_hotkey_config, set_hotkey_config, update_hotkey, reregister_hotkey, get_supported_keys,
+                get_mouse_button, set_mouse_button, get_action_hotkeys, set_action_hotkey, get_extra_hotkeys, set_extra_hotkeys,
+                undo_cancel, get_cancel_grace_ms, set_cancel_grace_ms,
+                get_hold_release_debounce_ms, set_hold_release_debounce_ms, get_toggle_cooldown_ms, set_toggle_cooldown_ms,
+                get_notify_on_transcript, set_notify_on_transcript,
+                get_dedupe_consecutive, set_dedupe_consecutive, get_dedupe_window_secs, set_dedupe_window_secs,
+                get_last_recording_diagnostics, get_clip_threshold, set_clip_threshold,
+                get_warmup_discard_ms, set_warmup_discard_ms,
+                force_reset_recording_state,
+                #[cfg(debug_assertions)]
+                inject_fake_transcript,
+                get_disabled_apps, set_disabled_apps, add_disabled_app, remove_disabled_app,
+                get_suppress_in_fullscreen, set_suppress_in_fullscreen,
+                get_confirm_in_apps, set_confirm_in_apps, approve_output, reject_output,
+                get_history, get_history_page, copy_history_item, delete_history_item, get_history_range, delete_history_range, merge_history_items, export_history, export_history_item, export_subtitles,
+                set_history_tags, get_history_by_tag, get_auto_tag_app, set_auto_tag_app, clear_history,
+                get_history_retention, set_history_retention,
+                get_on_error_keep_audio, set_on_error_keep_audio,
+                get_keep_recordings, set_keep_recordings, get_reserve_duration_secs, set_reserve_duration_secs,
+                get_buffer_size, set_buffer_size, get_buffer_size_range, get_sample_format, set_sample_format,
+                list_audio_hosts, get_audio_host, set_audio_host,
+                get_feedback_sounds, set_feedback_sounds, list_output_devices,
+                get_feedback_output_device, set_feedback_output_device,
+                get_capture_source, set_capture_source, list_input_devices, get_loopback_device, set_loopback_device, import_settings,
+                get_translation_settings, set_translation_settings, get_effective_config, snapshot_state, restore_state,
+                get_elapsed_interval_ms, set_elapsed_interval_ms, get_storage_usage, clear_recordings,
+                test_microphone, profile_capture, capture_noise_profile, get_spectral_denoise, set_spectral_denoise, replay_amplitude, retranscribe, transcribe_segment, transcribe_folder
             ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
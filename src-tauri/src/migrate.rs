@@ -0,0 +1,108 @@
+// 配置文件迁移：按 schema_version 把旧版本的 config.json 升级到当前结构
+use serde_json::{json, Map, Value};
+
+// 当前配置结构版本，每增加一个迁移加一
+pub const CONFIG_SCHEMA_VERSION: u32 = 3;
+
+type Migration = fn(&mut Map<String, Value>);
+
+// (升级到的版本, 说明, 迁移函数)，按版本顺序执行
+const MIGRATIONS: &[(u32, &str, Migration)] = &[
+    (1, "normalize hotkey modifier names", normalize_hotkeys),
+    (2, "fill missing history item fields", fill_history_fields),
+    (3, "drop invalid keyboard_max_length", drop_zero_keyboard_limit),
+];
+
+// 将配置升级到当前版本，返回执行过的迁移说明；根不是对象时重置为空配置
+pub fn migrate(config: &mut Value) -> Vec<String> {
+    if !config.is_object() {
+        log::warn!("Config root is not an object, resetting");
+        *config = json!({});
+    }
+    let Some(map) = config.as_object_mut() else {
+        return Vec::new();
+    };
+
+    let version = map.get("schema_version").and_then(Value::as_u64).unwrap_or(0) as u32;
+    if version > CONFIG_SCHEMA_VERSION {
+        log::warn!("Config schema version {} is newer than supported {}", version, CONFIG_SCHEMA_VERSION);
+        return Vec::new();
+    }
+
+    let mut applied = Vec::new();
+    for (target, description, migration) in MIGRATIONS {
+        if version < *target {
+            migration(map);
+            applied.push(format!("v{}: {}", target, description));
+        }
+    }
+    map.insert("schema_version".to_string(), json!(CONFIG_SCHEMA_VERSION));
+    applied
+}
+
+fn normalize_hotkey(hotkey: &mut Value) {
+    let Some(hotkey) = hotkey.as_object_mut() else {
+        return;
+    };
+    if let Some(modifiers) = hotkey.get_mut("modifiers").and_then(Value::as_array_mut) {
+        for modifier in modifiers.iter_mut() {
+            if let Some(name) = modifier.as_str() {
+                let normalized = match name.to_lowercase().as_str() {
+                    "control" | "ctl" => "ctrl".to_string(),
+                    "option" | "opt" => "alt".to_string(),
+                    "command" | "meta" | "super" => "cmd".to_string(),
+                    other => other.to_string(),
+                };
+                *modifier = json!(normalized);
+            }
+        }
+    }
+    if let Some(key) = hotkey.get("key").and_then(Value::as_str).map(str::to_lowercase) {
+        hotkey.insert("key".to_string(), json!(key));
+    }
+}
+
+// 修饰键曾以 "Control"、"Command" 等形式保存，统一为 ctrl/alt/shift/cmd
+fn normalize_hotkeys(config: &mut Map<String, Value>) {
+    if let Some(hotkey) = config.get_mut("hotkey") {
+        normalize_hotkey(hotkey);
+    }
+    if let Some(hotkeys) = config.get_mut("extra_hotkeys").and_then(Value::as_array_mut) {
+        hotkeys.iter_mut().for_each(normalize_hotkey);
+    }
+    if let Some(hotkeys) = config.get_mut("action_hotkeys").and_then(Value::as_object_mut) {
+        hotkeys.values_mut().for_each(normalize_hotkey);
+    }
+}
+
+// 早期历史记录缺少 id / char_count / date，缺字段会导致整个历史列表解析失败
+fn fill_history_fields(config: &mut Map<String, Value>) {
+    let Some(history) = config.get_mut("history").and_then(Value::as_array_mut) else {
+        return;
+    };
+    for (index, item) in history.iter_mut().enumerate() {
+        let Some(item) = item.as_object_mut() else {
+            continue;
+        };
+        let text = item.get("text").and_then(Value::as_str).unwrap_or_default().to_string();
+        let timestamp = item.get("timestamp").and_then(Value::as_i64).unwrap_or(0);
+
+        item.entry("text").or_insert_with(|| json!(text));
+        item.entry("timestamp").or_insert_with(|| json!(timestamp));
+        item.entry("char_count").or_insert_with(|| json!(text.chars().count()));
+        item.entry("id").or_insert_with(|| json!(format!("{}{:03}", timestamp * 1000, index % 1000)));
+        item.entry("date").or_insert_with(|| {
+            let date = chrono::DateTime::from_timestamp(timestamp, 0)
+                .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d").to_string())
+                .unwrap_or_default();
+            json!(date)
+        });
+    }
+}
+
+// keyboard_max_length 为 0 时会让所有文字都走剪贴板，设置时已不允许 0
+fn drop_zero_keyboard_limit(config: &mut Map<String, Value>) {
+    if config.get("keyboard_max_length").and_then(Value::as_u64) == Some(0) {
+        config.remove("keyboard_max_length");
+    }
+}
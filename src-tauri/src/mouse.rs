@@ -0,0 +1,64 @@
+//! 鼠标侧键录音快捷键（需启用 `mouse-hotkey` feature）
+//!
+//! 全局快捷键插件只支持键盘按键，这里通过 rdev 的底层事件监听捕获鼠标按键 4/5，
+//! 并复用与键盘快捷键相同的 Hold/Toggle 处理逻辑。
+//!
+//! 平台支持：
+//! - Windows：支持（XBUTTON1/XBUTTON2）
+//! - Linux (X11)：支持（按键 8/9），Wayland 下无法监听全局事件
+//! - macOS：rdev 0.5 不上报侧键事件，暂不支持
+
+use rdev::{Button, EventType};
+use std::sync::atomic::{AtomicU8, Ordering};
+use tauri::AppHandle;
+use tauri_plugin_global_shortcut::ShortcutState;
+
+// 当前绑定的鼠标按键（4 或 5），0 表示未启用
+static MOUSE_BUTTON: AtomicU8 = AtomicU8::new(0);
+
+pub fn set_button(button: Option<u8>) {
+    MOUSE_BUTTON.store(button.unwrap_or(0), Ordering::SeqCst);
+    log::info!("Mouse button hotkey set to: {:?}", button);
+}
+
+// 将 rdev 上报的按键映射为通用的 4/5 编号
+fn button_number(button: Button) -> Option<u8> {
+    match button {
+        #[cfg(target_os = "windows")]
+        Button::Unknown(1) => Some(4),
+        #[cfg(target_os = "windows")]
+        Button::Unknown(2) => Some(5),
+        #[cfg(target_os = "linux")]
+        Button::Unknown(8) => Some(4),
+        #[cfg(target_os = "linux")]
+        Button::Unknown(9) => Some(5),
+        _ => None,
+    }
+}
+
+pub fn setup_mouse_hotkey(app: &AppHandle) {
+    set_button(crate::get_setting::<Option<u8>>("mouse_button"));
+
+    let handle = app.clone();
+    std::thread::spawn(move || {
+        // rdev::listen 会阻塞当前线程直到进程退出
+        let result = rdev::listen(move |event| {
+            let (button, event_state) = match event.event_type {
+                EventType::ButtonPress(button) => (button, ShortcutState::Pressed),
+                EventType::ButtonRelease(button) => (button, ShortcutState::Released),
+                _ => return,
+            };
+
+            let bound = MOUSE_BUTTON.load(Ordering::SeqCst);
+            if bound != 0 && button_number(button) == Some(bound) {
+                crate::hotkey::handle_trigger(&handle, event_state, None, 0);
+            }
+        });
+
+        if let Err(e) = result {
+            log::error!("Failed to listen for mouse events: {:?}", e);
+        }
+    });
+
+    log::info!("Mouse button listener started");
+}
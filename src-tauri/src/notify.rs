@@ -0,0 +1,55 @@
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 转录通知的最短间隔，连续听写时不会刷屏
+const TRANSCRIPT_NOTIFY_INTERVAL: Duration = Duration::from_secs(10);
+/// 通知中显示的转录文字上限（字符）
+const TRANSCRIPT_NOTIFY_MAX_CHARS: usize = 80;
+
+static LAST_TRANSCRIPT_NOTIFY: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// 转义 AppleScript 字符串中的反斜杠和引号
+fn escape_applescript(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// 发送系统通知
+pub fn show_notification(title: &str, body: &str) -> Result<(), String> {
+    let script = format!(
+        r#"display notification "{}" with title "{}""#,
+        escape_applescript(body),
+        escape_applescript(title)
+    );
+
+    let output = Command::new("osascript")
+        .args(["-e", &script])
+        .output()
+        .map_err(|e| e.to_string())?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).to_string())
+    }
+}
+
+/// 转录完成后发送截断的文字通知，距上次通知不足间隔时跳过
+pub fn notify_transcript(text: &str) {
+    {
+        let mut last = LAST_TRANSCRIPT_NOTIFY.lock().unwrap();
+        if last.is_some_and(|at| at.elapsed() < TRANSCRIPT_NOTIFY_INTERVAL) {
+            log::info!("Skipping transcript notification (rate limited)");
+            return;
+        }
+        *last = Some(Instant::now());
+    }
+
+    let mut body: String = text.chars().take(TRANSCRIPT_NOTIFY_MAX_CHARS).collect();
+    if text.chars().count() > TRANSCRIPT_NOTIFY_MAX_CHARS {
+        body.push('…');
+    }
+    if let Err(e) = show_notification("Mouth High", &body) {
+        log::warn!("Failed to show notification: {}", e);
+    }
+}
@@ -0,0 +1,80 @@
+// 电源状态检测：后台线程定期查询并缓存结果，开始录音时只读取缓存，不在录音路径上执行外部命令
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+// 电源状态的刷新间隔
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+static ON_BATTERY: AtomicBool = AtomicBool::new(false);
+
+// 当前是否使用电池供电；监控线程尚未完成首次查询或无法判断时视为接通电源
+pub fn on_battery() -> bool {
+    ON_BATTERY.load(Ordering::Relaxed)
+}
+
+// 启动时调用一次，立即查询并在之后定期刷新
+pub fn start_monitor() {
+    std::thread::spawn(|| loop {
+        let on_battery = query_on_battery();
+        if ON_BATTERY.swap(on_battery, Ordering::Relaxed) != on_battery {
+            log::info!("Power source changed, on battery: {}", on_battery);
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    });
+}
+
+#[cfg(target_os = "macos")]
+fn query_on_battery() -> bool {
+    std::process::Command::new("pmset")
+        .args(["-g", "batt"])
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).contains("'Battery Power'"))
+        .unwrap_or(false)
+}
+
+#[cfg(target_os = "linux")]
+fn query_on_battery() -> bool {
+    // 存在交流电源且未接通时视为使用电池
+    let Ok(entries) = std::fs::read_dir("/sys/class/power_supply") else {
+        return false;
+    };
+    let mut has_mains = false;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let kind = std::fs::read_to_string(path.join("type")).unwrap_or_default();
+        if kind.trim() == "Mains" {
+            has_mains = true;
+            if std::fs::read_to_string(path.join("online")).unwrap_or_default().trim() == "1" {
+                return false;
+            }
+        }
+    }
+    has_mains
+}
+
+#[cfg(target_os = "windows")]
+fn query_on_battery() -> bool {
+    #[repr(C)]
+    #[derive(Default)]
+    struct SystemPowerStatus {
+        ac_line_status: u8,
+        _battery_flag: u8,
+        _battery_life_percent: u8,
+        _system_status_flag: u8,
+        _battery_life_time: u32,
+        _battery_full_life_time: u32,
+    }
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetSystemPowerStatus(status: *mut SystemPowerStatus) -> i32;
+    }
+
+    let mut status = SystemPowerStatus::default();
+    // ACLineStatus 为 0 表示未接通交流电源（1 为接通，255 为未知）
+    unsafe { GetSystemPowerStatus(&mut status) != 0 && status.ac_line_status == 0 }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+fn query_on_battery() -> bool {
+    false
+}
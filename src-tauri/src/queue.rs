@@ -0,0 +1,226 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Condvar, Mutex};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::error::{CommandError, CommandResult};
+use crate::transcriber::RecordedAudio;
+
+// 队列上限，超出后按溢出策略处理，避免 ASR 积压过多
+const MAX_QUEUE_LEN: usize = 8;
+
+// 工作线程数量上限；实际并发数由 max_concurrent_transcriptions 设置决定
+pub const MAX_WORKERS: usize = 4;
+
+static NEXT_JOB_ID: AtomicU64 = AtomicU64::new(1);
+
+pub struct TranscriptionJob {
+    pub id: String,
+    pub audio: RecordedAudio,
+    pub session_id: u64,
+}
+
+// 队列事件负载：任务 id 与当前排队数量
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct JobEvent {
+    pub id: String,
+    pub pending: usize,
+}
+
+// 当前排队和正在处理的任务数
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct QueueDepth {
+    pub pending: usize,
+    pub active: usize,
+}
+
+// 队列已满时的处理策略
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum QueueOverflowPolicy {
+    #[default]
+    RejectNew,   // 拒绝新录音（queue-full）
+    DropOldest,  // 丢弃最早排队的任务（queue-overflow）
+}
+
+struct QueueState {
+    jobs: VecDeque<TranscriptionJob>,
+    active: usize,
+    running: Vec<(String, u64)>,  // 正在处理的任务 id 与录音会话 id
+}
+
+// 转录队列：录音结束后入队，由工作线程按顺序取出处理
+pub struct TranscriptionQueue {
+    state: Mutex<QueueState>,
+    available: Condvar,
+}
+
+impl TranscriptionQueue {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(QueueState {
+                jobs: VecDeque::new(),
+                active: 0,
+                running: Vec::new(),
+            }),
+            available: Condvar::new(),
+        }
+    }
+}
+
+// 允许同时进行的转录数；本地 sidecar 为单进程，默认 1
+pub fn max_concurrent() -> usize {
+    crate::get_setting::<Option<usize>>("max_concurrent_transcriptions")
+        .unwrap_or(1)
+        .clamp(1, MAX_WORKERS)
+}
+
+// 将录音加入转录队列，返回任务 id；队列已满时按溢出策略拒绝新任务或丢弃最早的任务
+pub fn enqueue(app: &AppHandle, audio: RecordedAudio, session_id: u64) -> Result<String, String> {
+    let queue = app.state::<TranscriptionQueue>();
+    let id = NEXT_JOB_ID.fetch_add(1, Ordering::SeqCst).to_string();
+    let policy: QueueOverflowPolicy = crate::get_setting("queue_overflow_policy");
+
+    let pending = {
+        let mut state = queue.state.lock().map_err(|e| e.to_string())?;
+        if state.jobs.len() >= MAX_QUEUE_LEN {
+            match policy {
+                QueueOverflowPolicy::RejectNew => {
+                    drop(state);
+                    log::warn!("Transcription queue full, dropping job {}", id);
+                    audio.discard();
+                    let _ = app.emit("queue-full", JobEvent { id: id.clone(), pending: MAX_QUEUE_LEN });
+                    return Err(format!("Transcription queue is full ({} jobs pending)", MAX_QUEUE_LEN));
+                }
+                QueueOverflowPolicy::DropOldest => {
+                    if let Some(oldest) = state.jobs.pop_front() {
+                        log::warn!("Transcription queue full, dropping oldest job {}", oldest.id);
+                        oldest.audio.discard();
+                        let _ = app.emit("queue-overflow", JobEvent { id: oldest.id, pending: state.jobs.len() });
+                    }
+                }
+            }
+        }
+        state.jobs.push_back(TranscriptionJob {
+            id: id.clone(),
+            audio,
+            session_id,
+        });
+        state.jobs.len()
+    };
+    queue.available.notify_all();
+
+    log::info!("Transcription job {} queued ({} pending)", id, pending);
+    let _ = app.emit("transcription-queued", JobEvent { id: id.clone(), pending });
+    Ok(id)
+}
+
+// 取消转录任务：排队中的任务直接移除；处理中的任务标记其会话为已取消，
+// sidecar 支持 cancel 时立即打断正在进行的请求，否则读取完响应（保持读写同步）后丢弃结果
+pub fn cancel(app: &AppHandle, job_id: &str) -> CommandResult<()> {
+    let queue = app.state::<TranscriptionQueue>();
+    let mut state = queue.state.lock().map_err(|e| e.to_string())?;
+
+    if let Some(index) = state.jobs.iter().position(|job| job.id == job_id) {
+        let job = state.jobs.remove(index).ok_or_else(|| CommandError::not_found("Transcription job not found"))?;
+        let pending = state.jobs.len();
+        drop(state);
+
+        job.audio.discard();
+        log::info!("Queued transcription job {} cancelled", job_id);
+        let _ = app.emit("transcription-cancelled", JobEvent { id: job.id, pending });
+        let _ = app.emit("processing-finished", job.session_id);
+        return Ok(());
+    }
+
+    let session_id = state
+        .running
+        .iter()
+        .find(|(id, _)| id == job_id)
+        .map(|(_, session_id)| *session_id)
+        .ok_or_else(|| CommandError::not_found("Transcription job not found"))?;
+    let pending = state.jobs.len();
+    drop(state);
+
+    app.state::<crate::AppState>()
+        .cancelled_sessions
+        .lock()
+        .map_err(|e| e.to_string())?
+        .insert(session_id);
+    if crate::sidecar::cancel_session(session_id) {
+        log::info!("Sent cancel to ASR service for job {}", job_id);
+    }
+    log::info!("In-flight transcription job {} cancelled", job_id);
+    let _ = app.emit("transcription-cancelled", JobEvent { id: job_id.to_string(), pending });
+    Ok(())
+}
+
+pub fn depth(app: &AppHandle) -> QueueDepth {
+    let queue = app.state::<TranscriptionQueue>();
+    let state = queue.state.lock().unwrap();
+    QueueDepth {
+        pending: state.jobs.len(),
+        active: state.active,
+    }
+}
+
+// BlockUntilReady 策略下，服务就绪（或启动失败）前不处理任务
+fn should_wait_for_sidecar() -> bool {
+    if crate::sidecar::is_ready() || crate::sidecar::has_failed() {
+        return false;
+    }
+    let waiting = crate::get_setting::<crate::SidecarNotReadyPolicy>("sidecar_not_ready_policy")
+        == crate::SidecarNotReadyPolicy::BlockUntilReady;
+    if waiting {
+        log::info!("Waiting for ASR service before processing queued jobs");
+    }
+    waiting
+}
+
+// 唤醒工作线程重新检查队列（如 ASR 服务就绪或并发设置变化时）
+pub fn wake(app: &AppHandle) {
+    if let Some(queue) = app.try_state::<TranscriptionQueue>() {
+        queue.available.notify_all();
+    }
+}
+
+// 启动工作线程池，同时处理的任务数受 max_concurrent_transcriptions 限制；
+// 并发为 1 时任务按入队顺序处理，历史记录和输出也因此按完成顺序进行
+pub fn start_worker(app: &AppHandle) {
+    for _ in 0..MAX_WORKERS {
+        let handle = app.clone();
+        std::thread::spawn(move || loop {
+            let (job, pending) = {
+                let queue = handle.state::<TranscriptionQueue>();
+                let mut state = queue.state.lock().unwrap();
+                loop {
+                    if !state.jobs.is_empty() && state.active < max_concurrent() && !should_wait_for_sidecar() {
+                        if let Some(job) = state.jobs.pop_front() {
+                            state.active += 1;
+                            state.running.push((job.id.clone(), job.session_id));
+                            break (job, state.jobs.len());
+                        }
+                    }
+                    state = queue.available.wait(state).unwrap();
+                }
+            };
+
+            log::info!("Transcription job {} started", job.id);
+            let _ = handle.emit("transcription-started", JobEvent { id: job.id.clone(), pending });
+
+            crate::hotkey::process_audio(&handle, job.audio, job.session_id);
+
+            {
+                let queue = handle.state::<TranscriptionQueue>();
+                let mut state = queue.state.lock().unwrap();
+                state.active -= 1;
+                state.running.retain(|(id, _)| *id != job.id);
+                queue.available.notify_all();
+            }
+
+            let _ = handle.emit("transcription-finished", JobEvent { id: job.id, pending });
+        });
+    }
+
+    log::info!("Transcription workers started");
+}
@@ -1,20 +1,199 @@
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::io::{BufRead, BufReader, Write};
 use std::path::{Path, PathBuf};
-use std::process::{Child, Command, Stdio};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::atomic::{AtomicU8, Ordering};
 use std::sync::{Arc, Mutex};
-use tauri::{AppHandle, Manager};
+use tauri::{AppHandle, Emitter, Manager};
 
+use crate::transcriber::Transcriber;
 use crate::AppState;
 
+// 分段信息（时间单位为秒）
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Segment {
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+}
+
+// 请求中的音频：临时文件路径，或内存中编码好的 WAV
+enum AudioSource<'a> {
+    Path(&'a Path),
+    Wav(&'a [u8]),
+}
+
+// 逐词时间戳（秒）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Word {
+    pub start: f64,
+    pub end: f64,
+    pub word: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct TranscriptResult {
     pub text: String,
     pub language: Option<String>,
+    // sidecar 返回时透传给前端，用于显示时间轴
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub segments: Option<Vec<Segment>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub words: Option<Vec<Word>>,
+    // 设置了翻译目标语言时 sidecar 返回的译文
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub translation: Option<String>,
+    // 本次请求耗时（毫秒），缓存命中时为空
+    #[serde(default, skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub elapsed_ms: Option<u64>,
+}
+
+// 计算滚动平均耗时的最近请求数
+const TIMING_WINDOW: usize = 20;
+
+// 最近的转录耗时（毫秒）和累计请求数
+static TIMINGS: Mutex<(VecDeque<u64>, u64)> = Mutex::new((VecDeque::new(), 0));
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct TimingStats {
+    pub count: u64,
+    pub last_ms: Option<u64>,
+    pub average_ms: Option<u64>,  // 最近 TIMING_WINDOW 次的平均值
+    pub min_ms: Option<u64>,
+    pub max_ms: Option<u64>,
+}
+
+fn record_timing(ms: u64) {
+    if let Ok(mut timings) = TIMINGS.lock() {
+        let (samples, count) = &mut *timings;
+        if samples.len() == TIMING_WINDOW {
+            samples.pop_front();
+        }
+        samples.push_back(ms);
+        *count += 1;
+    }
+}
+
+pub fn timing_stats() -> TimingStats {
+    let Ok(timings) = TIMINGS.lock() else {
+        return TimingStats::default();
+    };
+    let (samples, count) = &*timings;
+    TimingStats {
+        count: *count,
+        last_ms: samples.back().copied(),
+        average_ms: (!samples.is_empty()).then(|| samples.iter().sum::<u64>() / samples.len() as u64),
+        min_ms: samples.iter().min().copied(),
+        max_ms: samples.iter().max().copied(),
+    }
+}
+
+// ASR 服务状态：启动中、就绪、启动失败
+const STATUS_STARTING: u8 = 0;
+const STATUS_READY: u8 = 1;
+const STATUS_FAILED: u8 = 2;
+
+static STATUS: AtomicU8 = AtomicU8::new(STATUS_STARTING);
+
+// 与 sidecar 通信的协议版本，启动时握手校验
+pub const PROTOCOL_VERSION: u32 = 1;
+
+// sidecar 握手时声明支持的功能，只使用其中列出的功能
+static CAPABILITIES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+// sidecar 的 stdin 单独保存，转录请求等待响应期间也能发送 cancel
+static STDIN: Mutex<Option<ChildStdin>> = Mutex::new(None);
+
+// 当前正在 sidecar 中转录的录音会话，只对它发送 cancel
+static ACTIVE_SESSION: Mutex<Option<u64>> = Mutex::new(None);
+
+#[derive(Debug, Deserialize)]
+struct HelloResponse {
+    version: u32,
+    #[serde(default)]
+    capabilities: Vec<String>,
+}
+
+// 随转录请求发送的翻译参数
+struct TranslationRequest {
+    translate_to: String,
+    endpoint: Option<String>,
+}
+
+// 按 translation 设置构造翻译参数；sidecar 未声明支持翻译时不发送，
+// 由 translate::translate_via_endpoint 在转录完成后调用自定义接口
+fn translation_request() -> Option<TranslationRequest> {
+    let settings: crate::TranslationSettings = crate::get_setting("translation");
+    let translate_to = settings.translate_to.filter(|t| !t.trim().is_empty())?;
+    if !has_capability("translate") {
+        if settings.endpoint.is_none() {
+            log::warn!("ASR service does not support translation and no endpoint is set, skipping");
+        }
+        return None;
+    }
+    Some(TranslationRequest { translate_to, endpoint: settings.endpoint })
+}
+
+pub fn has_capability(name: &str) -> bool {
+    CAPABILITIES.lock().map(|caps| caps.iter().any(|c| c == name)).unwrap_or(false)
+}
+
+pub fn capabilities() -> Vec<String> {
+    CAPABILITIES.lock().map(|caps| caps.clone()).unwrap_or_default()
+}
+
+pub fn is_ready() -> bool {
+    STATUS.load(Ordering::SeqCst) == STATUS_READY
+}
+
+// 启动失败后不会再变为就绪，等待中的任务应直接处理（并得到错误）
+pub fn has_failed() -> bool {
+    STATUS.load(Ordering::SeqCst) == STATUS_FAILED
+}
+
+pub fn mark_failed() {
+    STATUS.store(STATUS_FAILED, Ordering::SeqCst);
+}
+
+// 标记（或清除）正在 sidecar 中转录的会话；调用方持有 sidecar_manager 锁
+pub fn set_active_session(session_id: Option<u64>) {
+    if let Ok(mut active) = ACTIVE_SESSION.lock() {
+        *active = session_id;
+    }
+}
+
+// 取消正在进行的转录请求：sidecar 声明支持 cancel 且该会话正在转录时发送 cancel 命令，
+// sidecar 会立即以错误响应该请求；返回是否已发送
+pub fn cancel_session(session_id: u64) -> bool {
+    if !has_capability("cancel") {
+        return false;
+    }
+    let Ok(active) = ACTIVE_SESSION.lock() else {
+        return false;
+    };
+    if *active != Some(session_id) {
+        return false;
+    }
+    let Ok(mut stdin) = STDIN.lock() else {
+        return false;
+    };
+    let Some(stdin) = stdin.as_mut() else {
+        return false;
+    };
+    let request = serde_json::json!({ "command": "cancel" });
+    match writeln!(stdin, "{}", request).and_then(|_| stdin.flush()) {
+        Ok(()) => true,
+        Err(e) => {
+            log::warn!("Failed to send cancel to ASR service: {}", e);
+            false
+        }
+    }
 }
 
 pub struct SidecarManager {
     process: Arc<Mutex<Option<Child>>>,
+    stdout: Arc<Mutex<Option<BufReader<ChildStdout>>>>,
     script_path: PathBuf,
     python_path: PathBuf,
 }
@@ -23,12 +202,13 @@ impl SidecarManager {
     pub fn new(script_path: PathBuf, python_path: PathBuf) -> Self {
         Self {
             process: Arc::new(Mutex::new(None)),
+            stdout: Arc::new(Mutex::new(None)),
             script_path,
             python_path,
         }
     }
 
-    pub fn start(&self) -> Result<(), String> {
+    pub fn start(&self, app: &AppHandle) -> Result<(), String> {
         let mut process_guard = self.process.lock().map_err(|e| e.to_string())?;
 
         if process_guard.is_some() {
@@ -39,57 +219,144 @@ impl SidecarManager {
         log::info!("  Python: {:?}", self.python_path);
         log::info!("  Script: {:?}", self.script_path);
 
-        let child = Command::new(&self.python_path)
+        STATUS.store(STATUS_STARTING, Ordering::SeqCst);
+
+        let mut child = Command::new(&self.python_path)
             .arg(&self.script_path)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::inherit()) // Show Python errors in console
             .spawn()
-            .map_err(|e| format!("Failed to spawn Python ASR service: {}", e))?;
+            .map_err(|e| {
+                mark_failed();
+                format!("Failed to spawn Python ASR service: {}", e)
+            })?;
+
+        // 握手请求：sidecar 就绪后会先处理这一行
+        let mut stdin = child.stdin.take();
+        if let Some(stdin) = stdin.as_mut() {
+            let hello = serde_json::json!({ "command": "hello", "version": PROTOCOL_VERSION });
+            if let Err(e) = writeln!(stdin, "{}", hello).and_then(|_| stdin.flush()) {
+                log::warn!("Failed to send handshake to ASR service: {}", e);
+            }
+        }
+        *STDIN.lock().map_err(|e| e.to_string())? = stdin;
 
+        // stdout 只包装一次，避免多次创建 BufReader 丢失已缓冲的数据
+        let reader = child.stdout.take().map(BufReader::new);
+        *self.stdout.lock().map_err(|e| e.to_string())? = reader;
         *process_guard = Some(child);
 
         // Wait for "ready" signal
         log::info!("Waiting for ASR service to initialize...");
+        let stdout = self.stdout.clone();
+        let handle = app.clone();
+        std::thread::spawn(move || wait_for_ready(&handle, &stdout));
 
         Ok(())
     }
 
-    pub fn transcribe(&self, audio_path: &Path) -> Result<TranscriptResult, String> {
-        let mut process_guard = self.process.lock().map_err(|e| e.to_string())?;
+    pub fn transcribe(&self, audio_path: &Path, language: Option<&str>) -> Result<TranscriptResult, String> {
+        // 开启缓存时，相同音频、语言提示和翻译目标直接返回上次的结果
+        let translation = translation_request();
+        let cache_hint = match translation {
+            Some(ref t) => Some(format!("{}>{}", language.unwrap_or(""), t.translate_to)),
+            None => language.map(str::to_string),
+        };
+        let cache_key = if crate::cache::is_enabled() {
+            match crate::cache::cache_key(audio_path, cache_hint.as_deref()) {
+                Ok(key) => Some(key),
+                Err(e) => {
+                    log::warn!("{}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        if let Some(ref key) = cache_key {
+            if let Some(result) = crate::cache::lookup(key) {
+                log::info!("Transcription cache hit");
+                return Ok(result);
+            }
+        }
+
+        let result = self.transcribe_uncached(AudioSource::Path(audio_path), language, translation.as_ref())?;
 
-        let process = process_guard
-            .as_mut()
-            .ok_or("ASR service not running")?;
+        if let Some(key) = cache_key {
+            if let Err(e) = crate::cache::store(key, &result) {
+                log::warn!("Failed to store transcription cache: {}", e);
+            }
+        }
+        Ok(result)
+    }
 
-        // Send audio path to service
-        let stdin = process
-            .stdin
-            .as_mut()
-            .ok_or("Failed to get stdin")?;
+    // 仅内存模式：WAV 在内存中编码后以 base64 随请求发送，不写临时文件，也不写转录缓存
+    fn transcribe_in_memory(&self, samples: &[f32], sample_rate: u32, language: Option<&str>) -> Result<TranscriptResult, String> {
+        if !has_capability("audio_base64") {
+            return Err("ASR service does not support in-memory audio".to_string());
+        }
+        let wav = crate::audio::encode_wav(samples, sample_rate)?;
+        self.transcribe_uncached(AudioSource::Wav(&wav), language, translation_request().as_ref())
+    }
 
-        let path_str = audio_path.to_string_lossy();
-        writeln!(stdin, "{}", path_str)
-            .map_err(|e| format!("Failed to write to ASR service: {}", e))?;
-        stdin.flush().map_err(|e| format!("Failed to flush stdin: {}", e))?;
+    fn transcribe_uncached(
+        &self,
+        audio: AudioSource,
+        language: Option<&str>,
+        translation: Option<&TranslationRequest>,
+    ) -> Result<TranscriptResult, String> {
+        let process_guard = self.process.lock().map_err(|e| e.to_string())?;
 
-        // Read response from service
-        let stdout = process
-            .stdout
-            .as_mut()
-            .ok_or("Failed to get stdout")?;
+        if process_guard.is_none() {
+            return Err("ASR service not running".to_string());
+        }
 
-        let mut reader = BufReader::new(stdout);
+        if !is_ready() {
+            return Err("ASR service not ready".to_string());
+        }
+
+        // 请求以单行 JSON 发送，携带可选的语言提示（sidecar 声明支持时）
+        let language = language.filter(|_| has_capability("language_hint"));
+        let mut request = serde_json::json!({ "language": language });
+        match audio {
+            AudioSource::Path(path) => request["audio_path"] = serde_json::json!(path.to_string_lossy()),
+            AudioSource::Wav(bytes) => {
+                use base64::Engine;
+                request["audio_base64"] = serde_json::json!(base64::engine::general_purpose::STANDARD.encode(bytes));
+            }
+        }
+        if let Some(translation) = translation {
+            request["translate_to"] = serde_json::json!(translation.translate_to);
+            request["translate_endpoint"] = serde_json::json!(translation.endpoint);
+        }
+        let started = std::time::Instant::now();
+        {
+            // Send audio path to service
+            let mut stdin = STDIN.lock().map_err(|e| e.to_string())?;
+            let stdin = stdin.as_mut().ok_or("Failed to get stdin")?;
+            writeln!(stdin, "{}", request)
+                .map_err(|e| format!("Failed to write to ASR service: {}", e))?;
+            stdin.flush().map_err(|e| format!("Failed to flush stdin: {}", e))?;
+        }
+
+        // Read response from service
+        let mut stdout = self.stdout.lock().map_err(|e| e.to_string())?;
+        let reader = stdout.as_mut().ok_or("Failed to get stdout")?;
         let mut line = String::new();
         reader
             .read_line(&mut line)
             .map_err(|e| format!("Failed to read from ASR service: {}", e))?;
 
+        let elapsed_ms = started.elapsed().as_millis() as u64;
         log::debug!("ASR response: {}", line.trim());
+        log::info!("ASR request took {} ms", elapsed_ms);
+        record_timing(elapsed_ms);
 
         // Parse JSON response
-        let result: TranscriptResult = serde_json::from_str(&line)
+        let mut result: TranscriptResult = serde_json::from_str(&line)
             .map_err(|e| format!("Failed to parse ASR response '{}': {}", line.trim(), e))?;
+        result.elapsed_ms = Some(elapsed_ms);
 
         Ok(result)
     }
@@ -99,7 +366,7 @@ impl SidecarManager {
 
         if let Some(ref mut process) = *process_guard {
             // Try to send quit command
-            if let Some(ref mut stdin) = process.stdin {
+            if let Some(ref mut stdin) = *STDIN.lock().map_err(|e| e.to_string())? {
                 let _ = writeln!(stdin, "quit");
                 let _ = stdin.flush();
             }
@@ -113,69 +380,232 @@ impl SidecarManager {
         }
 
         *process_guard = None;
+        *STDIN.lock().map_err(|e| e.to_string())? = None;
+        *self.stdout.lock().map_err(|e| e.to_string())? = None;
+        STATUS.store(STATUS_STARTING, Ordering::SeqCst);
         log::info!("ASR service stopped");
 
         Ok(())
     }
 }
 
+// sidecar 通过文件路径读取音频；收到样本时先写入临时 WAV
+impl Transcriber for SidecarManager {
+    fn transcribe_path(&self, path: &Path, language: Option<&str>) -> Result<TranscriptResult, String> {
+        self.transcribe(path, language)
+    }
+
+    fn transcribe_samples(&self, samples: &[f32], sample_rate: u32, language: Option<&str>) -> Result<TranscriptResult, String> {
+        if crate::get_setting::<bool>("memory_only") {
+            return self.transcribe_in_memory(samples, sample_rate, language);
+        }
+        let path = crate::audio::write_wav(samples, sample_rate)?;
+        let result = self.transcribe(&path, language);
+        if let Err(e) = std::fs::remove_file(&path) {
+            log::warn!("Failed to remove temp audio file: {}", e);
+        }
+        result
+    }
+}
+
 impl Drop for SidecarManager {
     fn drop(&mut self) {
         let _ = self.stop();
     }
 }
 
-pub fn init_sidecar(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
-    let state = app.state::<AppState>();
+// 读取握手响应，校验协议版本并保存 sidecar 声明的功能
+fn read_handshake(stdout: &Mutex<Option<BufReader<ChildStdout>>>) -> Result<(), String> {
+    let mut line = String::new();
+    {
+        let mut stdout = stdout.lock().map_err(|e| e.to_string())?;
+        let reader = stdout.as_mut().ok_or("Failed to get stdout")?;
+        reader
+            .read_line(&mut line)
+            .map_err(|e| format!("Failed to read ASR service handshake: {}", e))?;
+    }
+
+    let hello: HelloResponse = serde_json::from_str(&line).map_err(|_| {
+        format!(
+            "ASR service does not support protocol version {} (got {:?}), please update the sidecar",
+            PROTOCOL_VERSION,
+            line.trim()
+        )
+    })?;
+    if hello.version != PROTOCOL_VERSION {
+        return Err(format!(
+            "Incompatible ASR service protocol version {} (expected {})",
+            hello.version, PROTOCOL_VERSION
+        ));
+    }
+
+    log::info!("ASR service protocol v{}, capabilities: {:?}", hello.version, hello.capabilities);
+    *CAPABILITIES.lock().map_err(|e| e.to_string())? = hello.capabilities;
+    Ok(())
+}
 
-    // Find the Python script and venv paths
-    // Working directory is src-tauri when running in dev mode, so go up one level
-    let current_dir = std::env::current_dir()?;
-    log::info!("Current working directory: {:?}", current_dir);
+// 读取服务启动后输出的第一行，收到 {"status": "ready"} 并完成握手后标记为就绪，否则标记为启动失败
+fn wait_for_ready(app: &AppHandle, stdout: &Mutex<Option<BufReader<ChildStdout>>>) {
+    let mut line = String::new();
+    let result = {
+        let mut stdout = stdout.lock().unwrap();
+        match stdout.as_mut() {
+            Some(reader) => reader.read_line(&mut line).map_err(|e| e.to_string()),
+            None => Err("Failed to get stdout".to_string()),
+        }
+    };
 
-    // Try current dir first, then parent dir (for dev mode where cwd is src-tauri)
-    let src_python_dir = if current_dir.join("src-python").exists() {
-        current_dir.join("src-python")
-    } else if current_dir.parent().map(|p| p.join("src-python").exists()).unwrap_or(false) {
-        current_dir.parent().unwrap().join("src-python")
+    let ready = result.is_ok()
+        && serde_json::from_str::<serde_json::Value>(&line)
+            .map(|v| v.get("status").and_then(|s| s.as_str()) == Some("ready"))
+            .unwrap_or(false);
+
+    // 就绪后校验握手，版本不兼容时启动失败
+    let handshake = if ready { read_handshake(stdout) } else { Ok(()) };
+    if let Err(e) = handshake {
+        mark_failed();
+        log::error!("{}", e);
+        crate::emit_error(app, e);
+    } else if ready {
+        STATUS.store(STATUS_READY, Ordering::SeqCst);
+        log::info!("ASR service ready");
+        let _ = app.emit("sidecar-ready", ());
     } else {
-        current_dir.join("src-python") // fallback
+        mark_failed();
+        let reason = match result {
+            Ok(0) => "ASR service exited during startup".to_string(),
+            Ok(_) => format!("ASR service failed to start: {}", line.trim()),
+            Err(e) => format!("Failed to read from ASR service: {}", e),
+        };
+        log::error!("{}", reason);
+        crate::emit_error(app, reason);
+    }
+
+    // 唤醒等待服务就绪的转录任务
+    crate::queue::wake(app);
+}
+
+// 确定 Python 解释器和 ASR 脚本路径：优先使用配置中的覆盖值，否则按工作目录自动查找
+fn resolve_paths() -> Result<(PathBuf, PathBuf), String> {
+    let script_override: Option<String> = crate::get_setting("asr_script_path");
+    let python_override: Option<String> = crate::get_setting("python_path");
+
+    let src_python_dir = match script_override {
+        Some(ref script) => PathBuf::from(script).parent().map(Path::to_path_buf).unwrap_or_default(),
+        None => {
+            // Find the Python script and venv paths
+            // Working directory is src-tauri when running in dev mode, so go up one level
+            let current_dir = std::env::current_dir()
+                .map_err(|e| format!("Failed to get current directory: {}", e))?;
+            log::info!("Current working directory: {:?}", current_dir);
+
+            // Try current dir first, then parent dir (for dev mode where cwd is src-tauri)
+            if current_dir.join("src-python").exists() {
+                current_dir.join("src-python")
+            } else if current_dir.parent().map(|p| p.join("src-python").exists()).unwrap_or(false) {
+                current_dir.parent().unwrap().join("src-python")
+            } else {
+                current_dir.join("src-python") // fallback
+            }
+        }
+    };
+
+    let script_path = match script_override {
+        Some(script) => {
+            log::info!("Using configured ASR script: {}", script);
+            PathBuf::from(script)
+        }
+        None => src_python_dir.join("asr_service.py"),
     };
 
-    let script_path = src_python_dir.join("asr_service.py");
-    let venv_python = src_python_dir.join(".venv").join("bin").join("python3");
+    let python_path = match python_override {
+        Some(python) => {
+            log::info!("Using configured Python: {}", python);
+            PathBuf::from(python)
+        }
+        None => {
+            let venv_python = src_python_dir.join(".venv").join("bin").join("python3");
+            log::info!("Looking for venv Python at: {:?}", venv_python);
+
+            // Check if venv Python exists
+            if venv_python.exists() {
+                log::info!("Using virtual environment Python: {:?}", venv_python);
+                venv_python
+            } else {
+                log::warn!(
+                    "Virtual environment not found at {:?}. Using system Python. \
+                     Please run: cd src-python && python3 -m venv .venv && source .venv/bin/activate && pip install mlx-audio-plus",
+                    venv_python
+                );
+                PathBuf::from("python3")
+            }
+        }
+    };
 
-    log::info!("Using src-python dir: {:?}", src_python_dir);
     log::info!("Looking for script at: {:?}", script_path);
-    log::info!("Looking for venv Python at: {:?}", venv_python);
+    Ok((script_path, python_path))
+}
+
+// 检查 ASR 脚本和 Python 解释器是否可用，返回便于排查的诊断信息
+pub fn validate_paths() -> Result<String, String> {
+    let (script_path, python_path) = resolve_paths()?;
+
+    if !script_path.is_file() {
+        return Err(format!(
+            "ASR script not found at {}. Set asr_script_path to the location of asr_service.py.",
+            script_path.display()
+        ));
+    }
+
+    let output = Command::new(&python_path)
+        .arg("--version")
+        .output()
+        .map_err(|e| format!(
+            "Failed to run Python at {}: {}. Set python_path to a working interpreter.",
+            python_path.display(), e
+        ))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Python at {} exited with {}: {}",
+            python_path.display(),
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    // 旧版本 Python 会把版本号输出到 stderr
+    let version = if output.stdout.is_empty() { &output.stderr } else { &output.stdout };
+    Ok(format!(
+        "{} ({}), script {}",
+        python_path.display(),
+        String::from_utf8_lossy(version).trim(),
+        script_path.display()
+    ))
+}
+
+pub fn init_sidecar(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    let state = app.state::<AppState>();
+
+    let (script_path, python_path) = resolve_paths()?;
 
     // Check if script exists
     if !script_path.exists() {
-        log::warn!(
-            "Python ASR script not found at {:?}. Please ensure src-python/asr_service.py exists.",
+        let message = format!(
+            "Python ASR script not found at {:?}. Please ensure src-python/asr_service.py exists or set asr_script_path.",
             script_path
         );
-        let manager = SidecarManager::new(script_path, venv_python);
+        log::warn!("{}", message);
+        crate::emit_error(app, message);
+        mark_failed();
+        let manager = SidecarManager::new(script_path, python_path);
         let mut sidecar = state.sidecar_manager.lock().map_err(|e| e.to_string())?;
         *sidecar = Some(manager);
         return Ok(());
     }
 
-    // Check if venv Python exists
-    let python_path = if venv_python.exists() {
-        log::info!("Using virtual environment Python: {:?}", venv_python);
-        venv_python
-    } else {
-        log::warn!(
-            "Virtual environment not found at {:?}. Using system Python. \
-             Please run: cd src-python && python3 -m venv .venv && source .venv/bin/activate && pip install mlx-audio-plus",
-            venv_python
-        );
-        PathBuf::from("python3")
-    };
-
     let manager = SidecarManager::new(script_path, python_path);
-    manager.start()?;
+    manager.start(app)?;
 
     let mut sidecar = state.sidecar_manager.lock().map_err(|e| e.to_string())?;
     *sidecar = Some(manager);
@@ -0,0 +1,678 @@
+// 转录文本处理
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+// 根据文字构成粗略判断语言：中日韩字符占多数时视为中文，拉丁字母占多数时视为英文
+pub fn detect_script_language(text: &str) -> Option<String> {
+    let mut cjk = 0usize;
+    let mut latin = 0usize;
+    for c in text.chars() {
+        if is_cjk(c) {
+            cjk += 1;
+        } else if c.is_alphabetic() && c.is_ascii() {
+            latin += 1;
+        }
+    }
+
+    if cjk == 0 && latin == 0 {
+        None
+    } else if cjk * 2 >= latin {
+        // 一个汉字大致相当于一个英文单词的信息量，按字母数折算
+        Some("zh".to_string())
+    } else {
+        Some("en".to_string())
+    }
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x4E00..=0x9FFF      // CJK Unified Ideographs
+        | 0x3400..=0x4DBF    // Extension A
+        | 0xF900..=0xFAFF    // Compatibility Ideographs
+        | 0x20000..=0x2A6DF) // Extension B
+}
+
+// 输出文本大小写转换
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TextTransform {
+    #[default]
+    None,
+    Lower,
+    Upper,
+    Title,
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, Default)]
+#[serde(default)]
+pub struct TransformSettings {
+    pub transform: TextTransform,
+    pub title_small_words: bool,  // 标题格式下保持 a/of/the 等小词小写
+    pub apply_to_history: bool,   // 历史记录保存转换后的文本（默认保存原文）
+}
+
+// 标题格式中保持小写的英文小词（首尾单词除外）
+const SMALL_WORDS: &[&str] = &[
+    "a", "an", "and", "as", "at", "but", "by", "for", "in", "nor", "of", "on", "or", "the", "to", "via", "with",
+];
+
+pub fn apply_transform(text: &str, settings: &TransformSettings) -> String {
+    match settings.transform {
+        TextTransform::None => text.to_string(),
+        TextTransform::Lower => text.to_lowercase(),
+        TextTransform::Upper => text.to_uppercase(),
+        TextTransform::Title => to_title_case(text, settings.title_small_words),
+    }
+}
+
+fn to_title_case(text: &str, keep_small_words: bool) -> String {
+    let word_count = text.split_whitespace().count();
+    let mut result = String::with_capacity(text.len());
+    let mut word_index = 0;
+
+    // 按空白切分但保留原有空白
+    for part in text.split_inclusive(char::is_whitespace) {
+        let word = part.trim_end();
+        let trailing = &part[word.len()..];
+        if word.is_empty() {
+            result.push_str(trailing);
+            continue;
+        }
+
+        let lower = word.to_lowercase();
+        let is_edge = word_index == 0 || word_index + 1 == word_count;
+        if keep_small_words && !is_edge && SMALL_WORDS.contains(&lower.as_str()) {
+            result.push_str(&lower);
+        } else {
+            result.push_str(&capitalize(&lower));
+        }
+        result.push_str(trailing);
+        word_index += 1;
+    }
+
+    result
+}
+
+// 将第一个字母转为大写（支持 é → É 等非 ASCII 字母）
+fn capitalize(word: &str) -> String {
+    match word.char_indices().find(|(_, c)| c.is_alphabetic()) {
+        Some((index, c)) => {
+            let mut result = String::with_capacity(word.len());
+            result.push_str(&word[..index]);
+            result.extend(c.to_uppercase());
+            result.push_str(&word[index + c.len_utf8()..]);
+            result
+        }
+        None => word.to_string(),
+    }
+}
+
+// 输出模板，支持 {text} {date} {time} {char_count} 占位符
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, Default)]
+#[serde(default)]
+pub struct OutputTemplate {
+    pub template: Option<String>,
+    pub apply_to_history: bool,  // 历史记录也保存套用模板后的文本
+}
+
+pub fn validate_template(template: &str) -> Result<(), String> {
+    if template.contains("{text}") {
+        Ok(())
+    } else {
+        Err("Output template must contain {text}".to_string())
+    }
+}
+
+// 套用模板；模板缺少 {text} 时退回原文
+pub fn render_template(template: &str, text: &str) -> String {
+    if validate_template(template).is_err() {
+        log::warn!("Ignoring output template without {{text}}: {}", template);
+        return text.to_string();
+    }
+
+    let now = chrono::Local::now();
+    template
+        .replace("{date}", &now.format("%Y-%m-%d").to_string())
+        .replace("{time}", &now.format("%H:%M:%S").to_string())
+        .replace("{char_count}", &text.chars().count().to_string())
+        .replace("{text}", text)
+}
+
+// 转录文本经过处理流程后的结果
+#[derive(Clone, Debug, serde::Serialize)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+pub enum TranscriptOutcome {
+    FillerOnly,                                 // 只有语气词，不输出、不记录
+    Command(crate::voice_command::VoiceAction), // 命中命令短语，执行动作而不输出文字
+    Text(String),                               // 待输出的文字（尚未做大小写转换和套用模板）
+}
+
+pub struct ProcessedTranscript {
+    pub outcome: TranscriptOutcome,
+    pub strip_error: Option<String>,  // 删除规则无效时的错误，此时保留未删除的文字继续处理
+}
+
+// 实际转录和预览共用的处理流程：去空白、删除规则、替换规则、语气词过滤、命令短语、口述标点；
+// language 为识别出的语言，决定替换规则和词表
+pub fn process_transcript(text: &str, language: Option<&str>) -> ProcessedTranscript {
+    let trimmed = trim_transcript(text);
+    let (stripped, strip_error) = match strip_configured_patterns(&trimmed) {
+        Ok(stripped) => (stripped, None),
+        Err(e) => (trimmed, Some(e)),
+    };
+    let processed = |outcome| ProcessedTranscript { outcome, strip_error: strip_error.clone() };
+
+    // 只应用与识别语言匹配的替换规则和全局规则
+    let replacements: Vec<ReplacementRule> = crate::get_setting("replacement_rules");
+    let replaced = apply_replacements(&stripped, language, &replacements);
+
+    let filler = crate::get_setting::<FillerWordFilter>("filler_word_filter");
+    if is_filler_only(&replaced, language, &filler) {
+        return processed(TranscriptOutcome::FillerOnly);
+    }
+
+    let command_mode = crate::get_setting::<crate::voice_command::CommandModeSettings>("command_mode");
+    if let Some(action) = crate::voice_command::match_command(&replaced, &command_mode) {
+        return processed(TranscriptOutcome::Command(action));
+    }
+
+    // 口述标点（"comma" → ","），按识别出的语言选择词表
+    let spoken = crate::get_setting::<SpokenPunctuation>("spoken_punctuation");
+    processed(TranscriptOutcome::Text(apply_spoken_punctuation(&replaced, language, &spoken)))
+}
+
+// 处理后的文本：output 用于输出，history 用于保存历史记录
+pub struct ProcessedText {
+    pub output: String,
+    pub history: String,
+}
+
+// 按当前设置做大小写转换、Markdown 辅助（目标应用启用时传入）并套用输出模板
+// （续写时不套用，直接接在上一条后面）；实际转录和预览共用，Markdown 辅助不写入历史
+pub fn format_transcript(text: &str, continue_last: bool, markdown: Option<&MarkdownAssist>) -> ProcessedText {
+    let transform = crate::get_setting::<TransformSettings>("text_transform");
+    let mut output = apply_transform(text, &transform);
+    let mut history = if transform.apply_to_history { output.clone() } else { text.to_string() };
+
+    if let Some(markdown) = markdown {
+        output = apply_markdown_assist(&output, markdown);
+    }
+
+    let template = crate::get_setting::<OutputTemplate>("output_template");
+    if let Some(ref template_str) = template.template {
+        if !continue_last {
+            output = render_template(template_str, &output);
+            if template.apply_to_history {
+                history = output.clone();
+            }
+        }
+    }
+
+    ProcessedText { output, history }
+}
+
+// 已编译的删除规则，配置不变时复用
+static STRIP_CACHE: Mutex<Option<(Vec<String>, Vec<Regex>)>> = Mutex::new(None);
+
+// 编译全部正则，任一无效时返回带序号的错误
+pub fn compile_patterns(patterns: &[String]) -> Result<Vec<Regex>, String> {
+    patterns
+        .iter()
+        .enumerate()
+        .map(|(i, pattern)| {
+            Regex::new(pattern).map_err(|e| format!("Invalid pattern #{} {:?}: {}", i + 1, pattern, e))
+        })
+        .collect()
+}
+
+// 删除匹配 strip_patterns 的内容，并去掉因此留下的首尾空白
+pub fn strip_patterns(text: &str, patterns: &[String]) -> Result<String, String> {
+    if patterns.is_empty() {
+        return Ok(text.to_string());
+    }
+
+    let mut cache = STRIP_CACHE.lock().map_err(|e| e.to_string())?;
+    let stale = !matches!(*cache, Some((ref cached, _)) if cached.as_slice() == patterns);
+    if stale {
+        *cache = Some((patterns.to_vec(), compile_patterns(patterns)?));
+    }
+
+    let mut result = text.to_string();
+    if let Some((_, ref regexes)) = *cache {
+        for regex in regexes {
+            result = regex.replace_all(&result, "").into_owned();
+        }
+    }
+    Ok(result.trim().to_string())
+}
+
+// 去掉 ASR 结果的首尾空白（trim_transcript，默认开启）
+pub fn trim_transcript(text: &str) -> String {
+    if crate::get_setting::<Option<bool>>("trim_transcript").unwrap_or(true) {
+        text.trim().to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+// 按 strip_patterns 设置删除匹配内容
+pub fn strip_configured_patterns(text: &str) -> Result<String, String> {
+    let patterns: Vec<String> = crate::get_setting("strip_patterns");
+    strip_patterns(text, &patterns)
+}
+
+// 文字替换规则：language 为空时对所有语言生效，否则只在识别语言匹配时生效（"zh" 也匹配 "zh-CN"）
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ReplacementRule {
+    pub from: String,
+    pub to: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+}
+
+impl ReplacementRule {
+    fn applies_to(&self, language: Option<&str>) -> bool {
+        let Some(ref tag) = self.language else {
+            return true;
+        };
+        let Some(language) = language else {
+            return false;
+        };
+        let (language, tag) = (language.to_lowercase(), tag.to_lowercase());
+        language == tag
+            || language
+                .strip_prefix(tag.as_str())
+                .is_some_and(|rest| rest.starts_with(['-', '_']))
+    }
+}
+
+// 规则的语言标签统一为小写，空字符串视为全局规则
+pub fn normalize_language_tag(language: Option<String>) -> Option<String> {
+    language
+        .map(|tag| tag.trim().to_lowercase())
+        .filter(|tag| !tag.is_empty())
+}
+
+// 按顺序应用与识别语言匹配的替换规则和全局规则
+pub fn apply_replacements(text: &str, language: Option<&str>, rules: &[ReplacementRule]) -> String {
+    rules
+        .iter()
+        .filter(|rule| !rule.from.is_empty() && rule.applies_to(language))
+        .fold(text.to_string(), |result, rule| result.replace(&rule.from, &rule.to))
+}
+
+// 口述标点：把正文中的 "comma"、"逗号" 等词替换为标点，按识别语言选择对应的词表
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct SpokenPunctuation {
+    pub enabled: bool,
+    pub en: HashMap<String, String>,
+    pub zh: HashMap<String, String>,
+}
+
+impl Default for SpokenPunctuation {
+    fn default() -> Self {
+        let map = |pairs: &[(&str, &str)]| {
+            pairs.iter().map(|(word, mark)| (word.to_string(), mark.to_string())).collect()
+        };
+
+        SpokenPunctuation {
+            enabled: false,
+            en: map(&[
+                ("comma", ","),
+                ("period", "."),
+                ("full stop", "."),
+                ("question mark", "?"),
+                ("exclamation mark", "!"),
+                ("colon", ":"),
+                ("semicolon", ";"),
+            ]),
+            zh: map(&[
+                ("逗号", "，"),
+                ("句号", "。"),
+                ("问号", "？"),
+                ("感叹号", "！"),
+                ("冒号", "："),
+                ("分号", "；"),
+                ("顿号", "、"),
+            ]),
+        }
+    }
+}
+
+// 替换口述标点；英文标点前不留空格、后留一个空格，中文标点前后都不留空格。
+// ASR 自己在提示词后加的标点一并去掉，避免出现重复标点
+pub fn apply_spoken_punctuation(text: &str, language: Option<&str>, settings: &SpokenPunctuation) -> String {
+    if !settings.enabled {
+        return text.to_string();
+    }
+
+    let chinese = language.is_some_and(|lang| lang.starts_with("zh"));
+    let map = if chinese { &settings.zh } else { &settings.en };
+
+    // 长词优先，避免 "full stop" 中的部分先被其他词匹配
+    let mut words: Vec<(&String, &String)> = map.iter().filter(|(word, _)| !word.trim().is_empty()).collect();
+    words.sort_by_key(|(word, _)| std::cmp::Reverse(word.chars().count()));
+
+    let mut result = text.to_string();
+    for (word, mark) in words {
+        let escaped = regex::escape(word.trim());
+        let (pattern, replacement) = if chinese {
+            (format!(r"\s*{}[，。,.]?\s*", escaped), mark.to_string())
+        } else {
+            (format!(r"(?i)\s*\b{}\b[,.]?\s*", escaped), format!("{} ", mark))
+        };
+        match Regex::new(&pattern) {
+            Ok(regex) => result = regex.replace_all(&result, regex::NoExpand(&replacement)).into_owned(),
+            Err(e) => log::warn!("Invalid spoken punctuation word {:?}: {}", word, e),
+        }
+    }
+    result.trim_end().to_string()
+}
+
+// 语气词过滤：转录结果只有 "um"、"嗯" 之类的语气词时视为噪音，按语言选择词表
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct FillerWordFilter {
+    pub enabled: bool,
+    pub en: Vec<String>,
+    pub zh: Vec<String>,
+}
+
+impl Default for FillerWordFilter {
+    fn default() -> Self {
+        let list = |words: &[&str]| words.iter().map(|word| word.to_string()).collect();
+
+        FillerWordFilter {
+            enabled: false,
+            en: list(&["um", "umm", "uh", "uhh", "er", "erm", "ah", "hmm", "mm", "mhm", "okay", "ok"]),
+            zh: list(&["嗯", "啊", "呃", "额", "哦", "唔", "嗯哼"]),
+        }
+    }
+}
+
+// 文字是否只由语气词组成；忽略大小写和标点，空文字不算。语言未知时两个词表都使用
+pub fn is_filler_only(text: &str, language: Option<&str>, settings: &FillerWordFilter) -> bool {
+    if !settings.enabled {
+        return false;
+    }
+
+    let normalized: String = text
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect();
+    if normalized.trim().is_empty() {
+        return false;
+    }
+
+    let mut words: Vec<String> = match language {
+        Some(lang) if lang.starts_with("zh") => settings.zh.clone(),
+        Some(_) => settings.en.clone(),
+        None => settings.en.iter().chain(&settings.zh).cloned().collect(),
+    };
+    words.retain(|word| !word.trim().is_empty());
+    words.iter_mut().for_each(|word| *word = word.trim().to_lowercase());
+    // 长词优先，中文语气词之间没有空格，逐个去掉后检查是否还有剩余
+    words.sort_by_key(|word| std::cmp::Reverse(word.chars().count()));
+
+    normalized.split_whitespace().all(|token| {
+        let mut rest = token.to_string();
+        loop {
+            match words.iter().find(|word| rest.starts_with(word.as_str())) {
+                Some(word) if word.is_ascii() && rest.len() != word.len() => return false,
+                Some(word) => rest = rest[word.len()..].to_string(),
+                None => return rest.is_empty(),
+            }
+            if rest.is_empty() {
+                return true;
+            }
+        }
+    })
+}
+
+// 文本规则导出文件的格式版本
+const RULES_FILE_VERSION: u32 = 1;
+
+// 可在团队间共享的文本规则：删除规则（按顺序）、口述标点词表和替换规则
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct RuleBundle {
+    pub version: u32,
+    pub strip_patterns: Vec<String>,
+    pub spoken_punctuation: SpokenPunctuation,
+    pub replacements: Vec<ReplacementRule>,
+}
+
+// 导入结果：实际导入的规则数和被跳过的无效正则
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct RuleImportReport {
+    pub strip_patterns: usize,
+    pub spoken_punctuation: usize,
+    pub replacements: usize,
+    pub invalid: Vec<String>,
+}
+
+pub fn export_rules(path: &std::path::Path) -> Result<(), String> {
+    let bundle = RuleBundle {
+        version: RULES_FILE_VERSION,
+        strip_patterns: crate::get_setting("strip_patterns"),
+        spoken_punctuation: crate::get_setting("spoken_punctuation"),
+        replacements: crate::get_setting("replacement_rules"),
+    };
+    let content = serde_json::to_string_pretty(&bundle)
+        .map_err(|e| format!("Failed to serialize rules: {}", e))?;
+    crate::write_atomic(path, content.as_bytes())
+}
+
+// 导入规则；merge 为 true 时与现有规则合并（相同的删除规则和标点词去重，以导入的为准），否则整体替换
+pub fn import_rules(path: &std::path::Path, merge: bool) -> Result<RuleImportReport, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| format!("Failed to read rules: {}", e))?;
+    let bundle: RuleBundle = serde_json::from_str(&content).map_err(|e| format!("Failed to parse rules: {}", e))?;
+    if bundle.version > RULES_FILE_VERSION {
+        return Err(format!("Unsupported rules file version: {}", bundle.version));
+    }
+
+    // 无效正则跳过并报告，不影响其余规则
+    let mut invalid = Vec::new();
+    let imported: Vec<String> = bundle
+        .strip_patterns
+        .into_iter()
+        .filter(|pattern| match Regex::new(pattern) {
+            Ok(_) => true,
+            Err(e) => {
+                invalid.push(format!("{:?}: {}", pattern, e));
+                false
+            }
+        })
+        .collect();
+
+    let mut patterns: Vec<String> = if merge { crate::get_setting("strip_patterns") } else { Vec::new() };
+    for pattern in &imported {
+        if !patterns.contains(pattern) {
+            patterns.push(pattern.clone());
+        }
+    }
+
+    let mut punctuation = if merge {
+        crate::get_setting::<SpokenPunctuation>("spoken_punctuation")
+    } else {
+        SpokenPunctuation { enabled: bundle.spoken_punctuation.enabled, en: HashMap::new(), zh: HashMap::new() }
+    };
+    let punctuation_count = bundle.spoken_punctuation.en.len() + bundle.spoken_punctuation.zh.len();
+    punctuation.en.extend(bundle.spoken_punctuation.en);
+    punctuation.zh.extend(bundle.spoken_punctuation.zh);
+
+    // 相同原文和语言的替换规则以导入的为准
+    let mut replacements: Vec<ReplacementRule> = if merge { crate::get_setting("replacement_rules") } else { Vec::new() };
+    let replacement_count = bundle.replacements.len();
+    for mut rule in bundle.replacements {
+        rule.language = normalize_language_tag(rule.language);
+        replacements.retain(|existing| !(existing.from == rule.from && existing.language == rule.language));
+        replacements.push(rule);
+    }
+
+    crate::set_setting("strip_patterns", &patterns)?;
+    crate::set_setting("spoken_punctuation", &punctuation)?;
+    crate::set_setting("replacement_rules", &replacements)?;
+
+    Ok(RuleImportReport {
+        strip_patterns: imported.len(),
+        spoken_punctuation: punctuation_count,
+        replacements: replacement_count,
+        invalid,
+    })
+}
+
+// Markdown 辅助：口述 "bullet"、"heading two" 等提示词时输出对应的 Markdown 语法，只在指定应用中生效
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct MarkdownAssist {
+    pub enabled: bool,
+    pub apps: Vec<String>,                // 生效的应用（bundle id）
+    pub cues: HashMap<String, String>,    // 提示词 → 行首语法
+}
+
+impl Default for MarkdownAssist {
+    fn default() -> Self {
+        let cues = [
+            ("bullet", "- "),
+            ("numbered item", "1. "),
+            ("heading one", "# "),
+            ("heading two", "## "),
+            ("heading three", "### "),
+            ("列表项", "- "),
+            ("编号项", "1. "),
+            ("一级标题", "# "),
+            ("二级标题", "## "),
+            ("三级标题", "### "),
+        ];
+
+        MarkdownAssist {
+            enabled: false,
+            apps: Vec::new(),
+            cues: cues.iter().map(|(cue, prefix)| (cue.to_string(), prefix.to_string())).collect(),
+        }
+    }
+}
+
+// 把句首或分句开头的提示词替换为换行加 Markdown 行首语法，句中出现的同一个词不处理；
+// 位于开头时不加换行。提示词后 ASR 自动加的标点一并去掉
+pub fn apply_markdown_assist(text: &str, settings: &MarkdownAssist) -> String {
+    // 长词优先，避免 "heading two" 中的部分先被其他提示词匹配
+    let mut cues: Vec<(&String, &String)> = settings.cues.iter().filter(|(cue, _)| !cue.trim().is_empty()).collect();
+    cues.sort_by_key(|(cue, _)| std::cmp::Reverse(cue.chars().count()));
+
+    let mut result = text.to_string();
+    for (cue, prefix) in cues {
+        let escaped = regex::escape(cue.trim());
+        // 第一个分组是提示词前的句子/分句边界，替换时保留
+        let pattern = if cue.is_ascii() {
+            format!(r"(?i)(^|[.!?;,:。！？；，：])\s*\b{}\b[,.:]?\s*", escaped)
+        } else {
+            format!(r"(^|[.!?;,:。！？；，：])\s*{}[，。：,.:]?\s*", escaped)
+        };
+        match Regex::new(&pattern) {
+            Ok(regex) => {
+                result = regex
+                    .replace_all(&result, |caps: &regex::Captures| format!("{}\n{}", &caps[1], prefix))
+                    .into_owned();
+            }
+            Err(e) => log::warn!("Invalid markdown cue {:?}: {}", cue, e),
+        }
+    }
+    result.trim_start_matches('\n').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transform(transform: TextTransform, title_small_words: bool) -> TransformSettings {
+        TransformSettings { transform, title_small_words, apply_to_history: false }
+    }
+
+    #[test]
+    fn lower_and_upper_handle_mixed_case_and_accents() {
+        assert_eq!(apply_transform("HeLLo Élan", &transform(TextTransform::Lower, false)), "hello élan");
+        assert_eq!(apply_transform("crème brûlée", &transform(TextTransform::Upper, false)), "CRÈME BRÛLÉE");
+        assert_eq!(apply_transform("MiXeD", &transform(TextTransform::None, false)), "MiXeD");
+    }
+
+    #[test]
+    fn title_case_capitalizes_each_word() {
+        let settings = transform(TextTransform::Title, false);
+        assert_eq!(apply_transform("the QUICK brown fox", &settings), "The Quick Brown Fox");
+        assert_eq!(apply_transform("mcDONALD", &settings), "Mcdonald");
+    }
+
+    #[test]
+    fn title_case_capitalizes_accented_and_punctuated_words() {
+        let settings = transform(TextTransform::Title, false);
+        assert_eq!(apply_transform("élan vital", &settings), "Élan Vital");
+        assert_eq!(apply_transform("(hello) world", &settings), "(Hello) World");
+    }
+
+    #[test]
+    fn title_case_keeps_small_words_except_at_edges() {
+        let settings = transform(TextTransform::Title, true);
+        assert_eq!(apply_transform("a tale OF two cities", &settings), "A Tale of Two Cities");
+        assert_eq!(apply_transform("what it is for", &settings), "What It Is For");
+    }
+
+    #[test]
+    fn title_case_preserves_whitespace() {
+        let settings = transform(TextTransform::Title, false);
+        assert_eq!(apply_transform(" spaced  out ", &settings), " Spaced  Out ");
+    }
+
+    fn filler_filter() -> FillerWordFilter {
+        FillerWordFilter { enabled: true, ..FillerWordFilter::default() }
+    }
+
+    #[test]
+    fn filler_only_ignores_case_and_punctuation() {
+        let filter = filler_filter();
+        assert!(is_filler_only("Um.", Some("en"), &filter));
+        assert!(is_filler_only("UH, hmm...", Some("en"), &filter));
+        assert!(is_filler_only("嗯，", Some("zh"), &filter));
+        assert!(is_filler_only("嗯嗯啊。", Some("zh"), &filter));
+    }
+
+    #[test]
+    fn filler_mixed_with_content_is_kept() {
+        let filter = filler_filter();
+        assert!(!is_filler_only("Um, I think so", Some("en"), &filter));
+        assert!(!is_filler_only("umbrella", Some("en"), &filter));
+        assert!(!is_filler_only("嗯，好的", Some("zh"), &filter));
+    }
+
+    #[test]
+    fn filler_uses_both_lists_when_language_unknown() {
+        assert!(is_filler_only("uh 嗯", None, &filler_filter()));
+    }
+
+    #[test]
+    fn filler_filter_ignores_empty_text_and_disabled_setting() {
+        assert!(!is_filler_only("", Some("en"), &filler_filter()));
+        assert!(!is_filler_only("...", Some("en"), &filler_filter()));
+        assert!(!is_filler_only("um", Some("en"), &FillerWordFilter::default()));
+    }
+
+    #[test]
+    fn markdown_cues_apply_at_sentence_and_clause_starts() {
+        let settings = MarkdownAssist::default();
+        assert_eq!(apply_markdown_assist("Bullet eggs. Bullet milk.", &settings), "- eggs.\n- milk.");
+        assert_eq!(apply_markdown_assist("Groceries: heading two, fruit", &settings), "Groceries:\n## fruit");
+        assert_eq!(apply_markdown_assist("购物清单。列表项，鸡蛋", &settings), "购物清单。\n- 鸡蛋");
+    }
+
+    #[test]
+    fn markdown_cues_inside_a_sentence_are_kept() {
+        let settings = MarkdownAssist::default();
+        assert_eq!(apply_markdown_assist("The bullet train is fast.", &settings), "The bullet train is fast.");
+        assert_eq!(apply_markdown_assist("I need a heading two for this.", &settings), "I need a heading two for this.");
+    }
+}
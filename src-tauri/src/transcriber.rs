@@ -0,0 +1,55 @@
+// 转录后端抽象：录音结束后按后端需要交付 WAV 文件或内存中的样本
+use std::path::{Path, PathBuf};
+
+use crate::sidecar::TranscriptResult;
+
+// 录音的交付方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AudioHandoff {
+    Wav,      // 写入临时 WAV 文件（默认，sidecar 通过文件路径读取）
+    Samples,  // 直接交付样本，省去磁盘读写（进程内后端）
+}
+
+// 一次录音的结果
+pub enum RecordedAudio {
+    Wav(PathBuf),
+    Samples { samples: Vec<f32>, sample_rate: u32 },
+}
+
+impl RecordedAudio {
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            RecordedAudio::Wav(path) => Some(path),
+            RecordedAudio::Samples { .. } => None,
+        }
+    }
+
+    // 删除临时 WAV 文件（已被移走的文件和样本无需处理）
+    pub fn discard(&self) {
+        if let RecordedAudio::Wav(path) = self {
+            if path.exists() {
+                if let Err(e) = std::fs::remove_file(path) {
+                    log::warn!("Failed to remove temp audio file: {}", e);
+                }
+            }
+        }
+    }
+}
+
+pub trait Transcriber {
+    // 后端希望以哪种方式接收录音
+    fn handoff(&self) -> AudioHandoff {
+        AudioHandoff::Wav
+    }
+
+    fn transcribe_path(&self, path: &Path, language: Option<&str>) -> Result<TranscriptResult, String>;
+
+    fn transcribe_samples(&self, samples: &[f32], sample_rate: u32, language: Option<&str>) -> Result<TranscriptResult, String>;
+
+    fn transcribe_audio(&self, audio: &RecordedAudio, language: Option<&str>) -> Result<TranscriptResult, String> {
+        match audio {
+            RecordedAudio::Wav(path) => self.transcribe_path(path, language),
+            RecordedAudio::Samples { samples, sample_rate } => self.transcribe_samples(samples, *sample_rate, language),
+        }
+    }
+}
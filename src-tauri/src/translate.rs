@@ -0,0 +1,61 @@
+// ASR 服务不支持翻译时，直接从应用调用 translation 设置中的自定义翻译接口
+// 接口约定：POST {"text", "target"}，返回 {"text"}；通过系统自带的 curl 发送请求（支持 https）
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+// 单次翻译请求的超时（秒）
+const REQUEST_TIMEOUT_SECS: u32 = 10;
+
+#[derive(serde::Deserialize)]
+struct TranslationResponse {
+    text: String,
+}
+
+// 需要在应用内翻译时返回译文：设置了目标语言和接口，且 sidecar 未声明 translate 功能
+// （支持时翻译参数随转录请求发给 sidecar）；翻译失败时记录警告并返回 None，仍输出原文
+pub fn translate_via_endpoint(text: &str) -> Option<String> {
+    let settings: crate::TranslationSettings = crate::get_setting("translation");
+    let target = settings.translate_to.filter(|t| !t.trim().is_empty())?;
+    let endpoint = settings.endpoint?;
+    if crate::sidecar::has_capability("translate") || text.trim().is_empty() {
+        return None;
+    }
+
+    match post_translation(&endpoint, text, &target) {
+        Ok(translation) => Some(translation),
+        Err(e) => {
+            log::warn!("Failed to translate via {}: {}", endpoint, e);
+            None
+        }
+    }
+}
+
+fn post_translation(endpoint: &str, text: &str, target: &str) -> Result<String, String> {
+    let body = serde_json::json!({ "text": text, "target": target }).to_string();
+
+    let mut child = Command::new("curl")
+        .args(["-sS", "--fail", "--max-time", &REQUEST_TIMEOUT_SECS.to_string()])
+        .args(["-H", "Content-Type: application/json", "--data-binary", "@-"])
+        .arg(endpoint)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run curl: {}", e))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(body.as_bytes())
+            .map_err(|e| format!("Failed to send translation request: {}", e))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to read translation response: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+
+    let response: TranslationResponse = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Invalid translation response: {}", e))?;
+    Ok(response.text)
+}
@@ -4,13 +4,15 @@ use tauri::{
     AppHandle, Manager,
 };
 
+const TRAY_ID: &str = "main";
+
 pub fn setup_tray(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
     let quit = MenuItem::with_id(app, "quit", "退出", true, None::<&str>)?;
     let show = MenuItem::with_id(app, "show", "显示窗口", true, None::<&str>)?;
 
     let menu = Menu::with_items(app, &[&show, &quit])?;
 
-    let _tray = TrayIconBuilder::new()
+    let _tray = TrayIconBuilder::with_id(TRAY_ID)
         .icon(app.default_window_icon().unwrap().clone())
         .menu(&menu)
         .show_menu_on_left_click(false)
@@ -48,3 +50,19 @@ pub fn setup_tray(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+// 录音时切换为红点图标，结束（含取消、重置和出错）后恢复默认图标
+pub fn set_recording(app: &AppHandle, recording: bool) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+
+    let icon = if recording {
+        Some(tauri::include_image!("icons/tray-recording.png"))
+    } else {
+        app.default_window_icon().cloned()
+    };
+    if let Err(e) = tray.set_icon(icon) {
+        log::warn!("Failed to update tray icon: {}", e);
+    }
+}
@@ -0,0 +1,66 @@
+// 命令模式：整段转录匹配特定短语时执行动作，而不是输出文字
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VoiceAction {
+    NewLine,       // 回车
+    NewParagraph,  // 两次回车
+    ScratchThat,   // 删除上一次输出的文字
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct CommandModeSettings {
+    pub enabled: bool,
+    pub phrases: HashMap<String, VoiceAction>,
+}
+
+impl Default for CommandModeSettings {
+    fn default() -> Self {
+        let phrases = [
+            ("new line", VoiceAction::NewLine),
+            ("new paragraph", VoiceAction::NewParagraph),
+            ("scratch that", VoiceAction::ScratchThat),
+            ("换行", VoiceAction::NewLine),
+            ("新段落", VoiceAction::NewParagraph),
+            ("删掉刚才", VoiceAction::ScratchThat),
+        ]
+        .into_iter()
+        .map(|(phrase, action)| (phrase.to_string(), action))
+        .collect();
+
+        CommandModeSettings { enabled: false, phrases }
+    }
+}
+
+// 归一化：小写、合并空白、去掉首尾标点（ASR 常在句末加句号）
+pub fn normalize_phrase(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_matches(|c: char| c.is_ascii_punctuation() || "。，！？、；：".contains(c))
+        .trim()
+        .to_lowercase()
+}
+
+// 整段转录与某个短语完全匹配时返回对应动作
+pub fn match_command(text: &str, settings: &CommandModeSettings) -> Option<VoiceAction> {
+    if !settings.enabled {
+        return None;
+    }
+    let normalized = normalize_phrase(text);
+    settings.phrases.get(&normalized).copied()
+}
+
+// 执行动作；last_output 为上一次输出的文字，用于撤销
+pub fn run_action(action: VoiceAction, last_output: Option<&str>) -> Result<(), String> {
+    match action {
+        VoiceAction::NewLine => crate::input::press_key(enigo::Key::Return, 1),
+        VoiceAction::NewParagraph => crate::input::press_key(enigo::Key::Return, 2),
+        VoiceAction::ScratchThat => match last_output {
+            Some(text) => crate::input::press_key(enigo::Key::Backspace, text.chars().count()),
+            None => Err("Nothing to scratch".to_string()),
+        },
+    }
+}
@@ -51,6 +51,15 @@ function App() {
         setStatus("processing");
       });
 
+      // 处理结束（含出错）或超时后恢复空闲状态
+      const unlistenProcessingFinished = await listen("processing-finished", () => {
+        setStatus((prev) => (prev === "processing" ? "idle" : prev));
+      });
+
+      const unlistenProcessingTimeout = await listen("processing-timeout", () => {
+        setStatus((prev) => (prev === "processing" ? "idle" : prev));
+      });
+
       const unlistenTranscript = await listen<TranscriptEvent>("transcript", (event) => {
         setStatus("idle");
         setTranscript(event.payload.text);
@@ -63,6 +72,11 @@ function App() {
         console.error("Error:", event.payload);
       });
 
+      // 窗口可能晚于启动时的错误打开，订阅后补发最近一次错误
+      invoke("request_error_replay").catch((e) => {
+        console.error("Failed to replay last error:", e);
+      });
+
       const unlistenHotkey = await listen<string>("hotkey-registered", (event) => {
         setHotkey(event.payload);
       });
@@ -71,6 +85,11 @@ function App() {
         setStatus("idle");
       });
 
+      // 快捷键打开设置/历史页
+      const unlistenNavigate = await listen<NavItem>("navigate", (event) => {
+        setActiveNav(event.payload);
+      });
+
       // 监听录音模式变化
       const unlistenRecordingMode = await listen<RecordingMode>("recording-mode-changed", (event) => {
         setRecordingMode(event.payload);
@@ -102,11 +121,14 @@ function App() {
       return () => {
         unlistenRecording();
         unlistenProcessing();
+        unlistenProcessingFinished();
+        unlistenProcessingTimeout();
         unlistenTranscript();
         unlistenError();
         unlistenHotkey();
         unlistenRecordingCancelled();
         unlistenRecordingMode();
+        unlistenNavigate();
       };
     };
 
@@ -114,7 +114,9 @@ function HotkeyRecorder({ currentHotkey, onHotkeyChange }: HotkeyRecorderProps)
       setIsRecording(false);
     } catch (e: any) {
       let errorMsg = "设置失败";
-      if (typeof e === "string" && e.includes("RegisterEventHotKey")) {
+      // 命令返回结构化错误 { code, message, detail }
+      const message: string = typeof e === "string" ? e : e?.message ?? "";
+      if (message.includes("RegisterEventHotKey")) {
         errorMsg = "该快捷键已被系统占用";
       }
       setError(errorMsg);